@@ -0,0 +1,213 @@
+use crate::linter::LintError;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Component, Path};
+
+/// Version of the baseline file layout.
+pub const BASELINE_VERSION: u32 = 1;
+
+/// Lint findings accepted as-is, written by `lint --write-baseline` and read by
+/// `lint --baseline` so that only new findings are reported.
+///
+/// Findings are fingerprinted by file, rule and the whitespace-normalized source line
+/// they start on rather than by line number, so they survive edits elsewhere in the
+/// file. Identical fingerprints are counted, and only that many are suppressed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Baseline {
+    pub version: u32,
+    pub findings: Vec<BaselineEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BaselineEntry {
+    pub file: String,
+    pub rule: String,
+    pub snippet: String,
+    pub count: usize,
+}
+
+impl Baseline {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read: {}", path.display()))?;
+        let baseline: Baseline = serde_json::from_str(&text)
+            .with_context(|| format!("Invalid baseline: {}", path.display()))?;
+        if baseline.version != BASELINE_VERSION {
+            anyhow::bail!(
+                "Invalid baseline: {} (unsupported version {})",
+                path.display(),
+                baseline.version
+            );
+        }
+        Ok(baseline)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut text = serde_json::to_string_pretty(self)?;
+        text.push('\n');
+        fs::write(path, text).with_context(|| format!("Failed to write: {}", path.display()))
+    }
+
+    /// Record the findings of every file, given as `(file, content, errors)`.
+    pub fn from_findings<'a>(
+        files: impl IntoIterator<Item = (&'a str, &'a str, &'a [LintError])>,
+    ) -> Self {
+        let mut counts: BTreeMap<(String, String, String), usize> = BTreeMap::new();
+        for (file, content, errors) in files {
+            let file = normalize_path(file);
+            for error in errors {
+                let key = (file.clone(), error.rule.clone(), snippet(content, error));
+                *counts.entry(key).or_default() += 1;
+            }
+        }
+
+        Self {
+            version: BASELINE_VERSION,
+            findings: counts
+                .into_iter()
+                .map(|((file, rule, snippet), count)| BaselineEntry {
+                    file,
+                    rule,
+                    snippet,
+                    count,
+                })
+                .collect(),
+        }
+    }
+
+    /// Drop the findings of `file` that the baseline accounts for, keeping new ones.
+    pub fn filter(&self, file: &str, content: &str, errors: Vec<LintError>) -> Vec<LintError> {
+        let file = normalize_path(file);
+        let mut remaining: HashMap<(String, String), usize> = self
+            .findings
+            .iter()
+            .filter(|entry| entry.file == file)
+            .map(|entry| ((entry.rule.clone(), entry.snippet.clone()), entry.count))
+            .collect();
+
+        errors
+            .into_iter()
+            .filter(|error| {
+                let key = (error.rule.clone(), snippet(content, error));
+                match remaining.get_mut(&key) {
+                    Some(count) if *count > 0 => {
+                        *count -= 1;
+                        false
+                    }
+                    _ => true,
+                }
+            })
+            .collect()
+    }
+}
+
+/// The line a finding starts on, trimmed and with runs of whitespace collapsed.
+fn snippet(content: &str, error: &LintError) -> String {
+    let line = content
+        .lines()
+        .nth(error.line.saturating_sub(1))
+        .unwrap_or("");
+    line.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Spell `file` the same way regardless of how it was passed on the command line:
+/// relative to the current directory where possible, with `/` separators.
+fn normalize_path(file: &str) -> String {
+    let path = Path::new(file);
+    let relative = std::env::current_dir()
+        .ok()
+        .and_then(|cwd| path.strip_prefix(cwd).ok())
+        .unwrap_or(path);
+    let parts: Vec<_> = relative
+        .components()
+        .filter(|c| !matches!(c, Component::CurDir))
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect();
+    if parts.is_empty() {
+        file.to_string()
+    } else {
+        parts.join("/")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn warning(rule: &str, line: usize) -> LintError {
+        LintError {
+            rule: rule.to_string(),
+            line,
+            column: 1,
+            end_line: line,
+            end_column: 2,
+            message: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_findings_survive_line_shifts() {
+        let old = "SELECT *   FROM users;\n";
+        let errors = vec![warning("no-select-star", 1)];
+        let baseline = Baseline::from_findings([("q.sql", old, errors.as_slice())]);
+        assert_eq!(baseline.findings[0].snippet, "SELECT * FROM users;");
+
+        let new = "-- header\n\n  SELECT * FROM users;\n";
+        let remaining = baseline.filter("./q.sql", new, vec![warning("no-select-star", 3)]);
+        assert!(remaining.is_empty());
+    }
+
+    #[test]
+    fn test_only_recorded_count_is_suppressed() {
+        let content = "SELECT * FROM t;\nSELECT * FROM t;\n";
+        let errors = vec![warning("no-select-star", 1)];
+        let baseline = Baseline::from_findings([("q.sql", content, errors.as_slice())]);
+
+        let remaining = baseline.filter(
+            "q.sql",
+            content,
+            vec![warning("no-select-star", 1), warning("no-select-star", 2)],
+        );
+        assert_eq!(remaining.len(), 1);
+    }
+
+    #[test]
+    fn test_other_files_and_rules_are_not_suppressed() {
+        let content = "select * from t;\n";
+        let errors = vec![warning("no-select-star", 1)];
+        let baseline = Baseline::from_findings([("a.sql", content, errors.as_slice())]);
+
+        assert_eq!(
+            baseline
+                .filter("b.sql", content, vec![warning("no-select-star", 1)])
+                .len(),
+            1
+        );
+        assert_eq!(
+            baseline
+                .filter("a.sql", content, vec![warning("keyword-case", 1)])
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("sqlex-baseline.json");
+        let errors = vec![warning("keyword-case", 1)];
+        let baseline = Baseline::from_findings([("q.sql", "select 1;", errors.as_slice())]);
+        baseline.save(&path).unwrap();
+
+        let loaded = Baseline::load(&path).unwrap();
+        assert_eq!(loaded.findings, baseline.findings);
+
+        fs::write(&path, r#"{"version": 99, "findings": []}"#).unwrap();
+        let err = Baseline::load(&path).unwrap_err();
+        assert!(err.to_string().contains("Invalid baseline"));
+    }
+}
@@ -0,0 +1,144 @@
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory, relative to the working directory, that results are cached in.
+pub const CACHE_DIR_NAME: &str = ".sqlex_cache";
+
+/// On-disk cache of per-file results, so unchanged files are not parsed again.
+///
+/// Entries are keyed by a hash of everything that can change a result: the file
+/// content, the command, the effective settings and the message language. They live
+/// under a directory per sqlex version, so upgrading never reuses stale results.
+/// Failing to read or write an entry is never an error; the result is just computed.
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    /// A cache stored under `root` (normally [`CACHE_DIR_NAME`]).
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: root.into().join(env!("CARGO_PKG_VERSION")),
+        }
+    }
+
+    /// Return the cached result for `key`, or compute and store it.
+    ///
+    /// `key` lists everything besides the content that the result depends on.
+    pub fn get_or_compute<T: Serialize + DeserializeOwned>(
+        &self,
+        key: &[&str],
+        content: &str,
+        compute: impl FnOnce() -> Result<T>,
+    ) -> Result<T> {
+        let path = self.dir.join(format!("{}.json", hash(key, content)));
+        if let Some(value) = fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        {
+            return Ok(value);
+        }
+
+        let value = compute()?;
+        if let Ok(bytes) = serde_json::to_vec(&value) {
+            let _ = self.store(&path, &bytes);
+        }
+        Ok(value)
+    }
+
+    fn store(&self, path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+        if !self.dir.exists() {
+            fs::create_dir_all(&self.dir)?;
+            // Keep the cache out of version control without touching the project's files.
+            if let Some(root) = self.dir.parent() {
+                fs::write(root.join(".gitignore"), "*\n")?;
+            }
+        }
+        // Write under a unique name and rename, so concurrent runs never read half an entry.
+        let temp = path.with_extension(format!("{}.tmp", std::process::id()));
+        fs::write(&temp, bytes)?;
+        fs::rename(&temp, path)
+    }
+
+    /// Delete the cache directory at `root`. Returns whether there was one.
+    pub fn clean(root: &Path) -> Result<bool> {
+        if !root.exists() {
+            return Ok(false);
+        }
+        fs::remove_dir_all(root)
+            .with_context(|| format!("Failed to remove: {}", root.display()))?;
+        Ok(true)
+    }
+}
+
+fn hash(key: &[&str], content: &str) -> String {
+    let mut hasher = Sha256::new();
+    for part in key.iter().chain([&content]) {
+        // Length-prefix every part so that different splits never hash the same.
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part.as_bytes());
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_results_are_reused_for_same_key_and_content() {
+        let dir = TempDir::new().unwrap();
+        let cache = Cache::new(dir.path().join(CACHE_DIR_NAME));
+        let computed = Cell::new(0);
+        let compute = || {
+            computed.set(computed.get() + 1);
+            Ok(vec![1, 2, 3])
+        };
+
+        let first: Vec<i32> = cache
+            .get_or_compute(&["check", "generic"], "SELECT 1;", compute)
+            .unwrap();
+        let second: Vec<i32> = cache
+            .get_or_compute(&["check", "generic"], "SELECT 1;", compute)
+            .unwrap();
+        assert_eq!(first, second);
+        assert_eq!(computed.get(), 1);
+
+        let _: Vec<i32> = cache
+            .get_or_compute(&["check", "mysql"], "SELECT 1;", compute)
+            .unwrap();
+        let _: Vec<i32> = cache
+            .get_or_compute(&["check", "generic"], "SELECT 2;", compute)
+            .unwrap();
+        assert_eq!(computed.get(), 3);
+    }
+
+    #[test]
+    fn test_hash_separates_key_parts() {
+        assert_ne!(hash(&["ab", "c"], ""), hash(&["a", "bc"], ""));
+    }
+
+    #[test]
+    fn test_clean_removes_cache_dir() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join(CACHE_DIR_NAME);
+        let cache = Cache::new(&root);
+        let _: u8 = cache.get_or_compute(&[], "", || Ok(1)).unwrap();
+        assert!(root.join(".gitignore").exists());
+
+        assert!(Cache::clean(&root).unwrap());
+        assert!(!root.exists());
+        assert!(!Cache::clean(&root).unwrap());
+    }
+}
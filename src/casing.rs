@@ -0,0 +1,1037 @@
+//! Classifying words by their role, and the case each role should be written in.
+//!
+//! Every word is a keyword, a function name, a data type or an identifier, each with
+//! its own case rule (`keyword-case`, `function-case`, `type-case`, `identifier-case`).
+//! Roles come from the AST: each statement is parsed with the file's dialect, and
+//! the positions of its identifiers, function names and data types are collected.
+//! A word is a keyword when sqlparser knows it as one (its `Keyword` enum covers every
+//! dialect it supports) and the statement does not use it in another role: `status` in
+//! `SELECT status FROM orders` is a column even though `STATUS` is a keyword. In
+//! statements that do not parse, only words the dialect reserves count as keywords,
+//! since any other word may be a name, and no other roles are known.
+//!
+//! `[lint] extra-keywords` and `exclude-keywords` adjust the result for words sqlparser
+//! does not know or that a project uses as names everywhere.
+
+use crate::checker::split_statements;
+use crate::linter::{KeywordCase, LintConfig};
+use sqlparser::ast::{
+    AlterColumnOperation, AlterTableOperation, ArrayElemTypeDef, Assignment, AssignmentTarget,
+    ColumnDef, ColumnOption, ConflictTarget, DataType, Expr, ForeignKeyConstraint, FunctionArg,
+    FunctionArguments, FunctionReturnType, Grant, GrantObjects, Ident, JoinConstraint,
+    JoinOperator, MergeAction, NamedWindowDefinition, NamedWindowExpr, ObjectName, ObjectNamePart,
+    OnConflictAction, OnInsert, Query, Revoke, Select, SelectItem, SelectItemQualifiedWildcardKind,
+    Statement, TableAlias, TableConstraint, TableFactor, TableWithJoins, TypedString, Visit,
+    Visitor, WindowSpec, WindowType,
+};
+use sqlparser::dialect::{
+    BigQueryDialect, ClickHouseDialect, DatabricksDialect, Dialect, DuckDbDialect, HiveDialect,
+    MsSqlDialect, RedshiftSqlDialect, SnowflakeDialect,
+};
+use sqlparser::keywords::{Keyword, RESERVED_FOR_COLUMN_ALIAS, RESERVED_FOR_TABLE_ALIAS};
+use sqlparser::parser::{Parser, ParserError};
+use sqlparser::tokenizer::{Location, Token, TokenWithSpan, Word};
+use std::collections::{HashMap, HashSet};
+use std::ops::ControlFlow;
+
+/// Keywords that are called like functions but have their own syntax, so sqlparser
+/// parses them into dedicated expressions rather than function calls.
+const FUNCTION_KEYWORDS: &[Keyword] = &[
+    Keyword::CAST,
+    Keyword::CEIL,
+    Keyword::CONVERT,
+    Keyword::EXTRACT,
+    Keyword::FLOOR,
+    Keyword::OVERLAY,
+    Keyword::POSITION,
+    Keyword::SAFE_CAST,
+    Keyword::SUBSTR,
+    Keyword::SUBSTRING,
+    Keyword::TRIM,
+    Keyword::TRY_CAST,
+    Keyword::TRY_CONVERT,
+];
+
+/// Keywords particular to one dialect, beyond sqlparser's `Keyword` enum and common
+/// reserved lists.
+#[derive(Default)]
+struct DialectKeywords {
+    /// Words that are keywords where the parser accepts them.
+    keywords: &'static [&'static str],
+    /// Words that are always keywords, even where the parser takes them for names or
+    /// rejects the statement.
+    reserved: &'static [&'static str],
+}
+
+impl DialectKeywords {
+    fn new(dialect: &dyn Dialect) -> Self {
+        if dialect.is::<MsSqlDialect>() {
+            DialectKeywords {
+                keywords: &[
+                    "HOLDLOCK",
+                    "NOCOUNT",
+                    "PAGLOCK",
+                    "READCOMMITTED",
+                    "READPAST",
+                    "READUNCOMMITTED",
+                    "ROWLOCK",
+                    "TABLOCK",
+                    "TABLOCKX",
+                    "UPDLOCK",
+                    "XLOCK",
+                ],
+                reserved: &["GO", "NOLOCK", "PROC", "TOP"],
+            }
+        } else if dialect.is::<RedshiftSqlDialect>() {
+            DialectKeywords {
+                keywords: &["COMPOUND", "ENCODE", "INTERLEAVED"],
+                reserved: &["TOP"],
+            }
+        } else if dialect.is::<ClickHouseDialect>() {
+            DialectKeywords {
+                keywords: &[],
+                reserved: &["FINAL", "PREWHERE"],
+            }
+        } else if dialect.is::<DuckDbDialect>() {
+            DialectKeywords {
+                keywords: &["POSITIONAL", "SUMMARIZE"],
+                reserved: &["QUALIFY"],
+            }
+        } else if dialect.is::<HiveDialect>() {
+            DialectKeywords {
+                keywords: &["REDUCE", "SKEWED", "TRANSFORM"],
+                reserved: &[],
+            }
+        } else if dialect.is::<DatabricksDialect>() {
+            DialectKeywords {
+                keywords: &["LIVE", "SKEWED", "STREAMING", "WATERMARK"],
+                reserved: &["QUALIFY"],
+            }
+        } else if dialect.is::<SnowflakeDialect>() || dialect.is::<BigQueryDialect>() {
+            DialectKeywords {
+                keywords: &[],
+                reserved: &["QUALIFY"],
+            }
+        } else {
+            DialectKeywords::default()
+        }
+    }
+
+    fn knows(&self, word: &str) -> bool {
+        self.reserves(word) || self.keywords.iter().any(|w| w.eq_ignore_ascii_case(word))
+    }
+
+    fn reserves(&self, word: &str) -> bool {
+        self.reserved.iter().any(|w| w.eq_ignore_ascii_case(word))
+    }
+}
+
+/// Longest data type, in tokens, looked for when classifying type names.
+const MAX_TYPE_TOKENS: usize = 64;
+
+/// What a word is used as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Role {
+    Keyword,
+    Function,
+    Type,
+    Identifier,
+}
+
+impl Role {
+    /// Id of the lint rule enforcing this role's case.
+    pub(crate) fn rule(self) -> &'static str {
+        match self {
+            Role::Keyword => "keyword-case",
+            Role::Function => "function-case",
+            Role::Type => "type-case",
+            Role::Identifier => "identifier-case",
+        }
+    }
+
+    fn case(self, config: &LintConfig) -> KeywordCase {
+        match self {
+            Role::Keyword => config.keyword_case,
+            Role::Function => config.function_case,
+            Role::Type => config.type_case(),
+            Role::Identifier => config.identifier_case,
+        }
+    }
+}
+
+/// A word whose case does not follow its role's rule.
+pub(crate) struct Miscased {
+    /// Index of the word in the tokens.
+    pub index: usize,
+    pub role: Role,
+    /// The word as the rule wants it written.
+    pub expected: String,
+}
+
+/// The words of `tokens` whose case does not follow `config`, in source order.
+pub(crate) fn miscased(
+    tokens: &[TokenWithSpan],
+    dialect: &dyn Dialect,
+    config: &LintConfig,
+) -> Vec<Miscased> {
+    if [Role::Keyword, Role::Function, Role::Type, Role::Identifier]
+        .iter()
+        .all(|role| role.case(config) == KeywordCase::Ignore)
+    {
+        return Vec::new();
+    }
+
+    // With `consistent`, the first word of a role whose case is clear sets the case.
+    let mut first: HashMap<Role, KeywordCase> = HashMap::new();
+    let mut miscased = Vec::new();
+    for (index, role) in token_roles(tokens, dialect, config).into_iter().enumerate() {
+        let (Some(role), Token::Word(word)) = (role, &tokens[index].token) else {
+            continue;
+        };
+        let case = match role.case(config) {
+            KeywordCase::Ignore => continue,
+            KeywordCase::Consistent => match first.get(&role) {
+                Some(&case) => case,
+                None => {
+                    if let Some(case) = case_of(&word.value) {
+                        first.insert(role, case);
+                    }
+                    continue;
+                }
+            },
+            case => case,
+        };
+        let expected = recase(&word.value, case);
+        if expected != word.value {
+            miscased.push(Miscased {
+                index,
+                role,
+                expected,
+            });
+        }
+    }
+    miscased
+}
+
+/// `word` written in `case` (upper, lower or capitalized).
+fn recase(word: &str, case: KeywordCase) -> String {
+    match case {
+        KeywordCase::Upper => word.to_uppercase(),
+        KeywordCase::Lower => word.to_lowercase(),
+        KeywordCase::Capitalize => {
+            let mut chars = word.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first
+                    .to_uppercase()
+                    .chain(chars.flat_map(char::to_lowercase))
+                    .collect()
+            })
+        }
+        KeywordCase::Consistent | KeywordCase::Ignore => word.to_string(),
+    }
+}
+
+/// The case `word` is written in, if it is clearly one of upper, lower or capitalized.
+fn case_of(word: &str) -> Option<KeywordCase> {
+    let upper = word.to_uppercase();
+    let lower = word.to_lowercase();
+    if upper == lower {
+        // No letters to tell by.
+        None
+    } else if word == upper {
+        Some(KeywordCase::Upper)
+    } else if word == lower {
+        Some(KeywordCase::Lower)
+    } else if word == recase(word, KeywordCase::Capitalize) {
+        Some(KeywordCase::Capitalize)
+    } else {
+        None
+    }
+}
+
+/// For each of `tokens`, the role of the word it is, if any.
+pub(crate) fn token_roles(
+    tokens: &[TokenWithSpan],
+    dialect: &dyn Dialect,
+    config: &LintConfig,
+) -> Vec<Option<Role>> {
+    let names = Names::collect(tokens, dialect);
+    let code: Vec<usize> = (0..tokens.len())
+        .filter(|&i| !matches!(tokens[i].token, Token::Whitespace(_)))
+        .collect();
+
+    let mut roles = vec![None; tokens.len()];
+    for (n, &i) in code.iter().enumerate() {
+        let Token::Word(word) = &tokens[i].token else {
+            continue;
+        };
+        let next = code.get(n + 1).map(|&j| &tokens[j].token);
+        // A word qualified by, or qualifying, another (`t.status`) is always a name.
+        let qualified = next == Some(&Token::Period)
+            || n.checked_sub(1)
+                .is_some_and(|p| tokens[code[p]].token == Token::Period);
+        roles[i] = role(
+            word,
+            tokens[i].span.start,
+            qualified,
+            next == Some(&Token::LParen),
+            &names,
+            dialect,
+            config,
+        );
+    }
+    roles
+}
+
+fn role(
+    word: &Word,
+    start: Location,
+    qualified: bool,
+    called: bool,
+    names: &Names,
+    dialect: &dyn Dialect,
+    config: &LintConfig,
+) -> Option<Role> {
+    // Quoting makes case significant, so quoted words are left as written.
+    if word.quote_style.is_some() {
+        return None;
+    }
+    let listed = |words: &[String]| words.iter().any(|w| w.eq_ignore_ascii_case(&word.value));
+    if listed(&config.extra_keywords) || names.keywords.contains(&start) {
+        return Some(Role::Keyword);
+    }
+    // What the dialect reserves is a keyword even where sqlparser's grammar, more
+    // lenient than the database's, takes it for a name (e.g. ClickHouse's `FROM t FINAL`
+    // parses as an alias).
+    let table = DialectKeywords::new(dialect);
+    if table.reserves(&word.value) && !qualified && !listed(&config.exclude_keywords) {
+        return Some(Role::Keyword);
+    }
+    if names.functions.contains(&start) {
+        return Some(Role::Function);
+    }
+    if names.types.contains(&start) {
+        return Some(Role::Type);
+    }
+    if names.identifiers.contains(&start) {
+        return Some(Role::Identifier);
+    }
+    let known = word.keyword != Keyword::NoKeyword || table.knows(&word.value);
+    if qualified || listed(&config.exclude_keywords) || !known {
+        return None;
+    }
+    if names.unparsed.contains(&start) {
+        return is_reserved(word.keyword, dialect).then_some(Role::Keyword);
+    }
+    if called && FUNCTION_KEYWORDS.contains(&word.keyword) {
+        return Some(Role::Function);
+    }
+    Some(Role::Keyword)
+}
+
+/// Whether `dialect` never accepts `keyword` as an unquoted name.
+fn is_reserved(keyword: Keyword, dialect: &dyn Dialect) -> bool {
+    RESERVED_FOR_COLUMN_ALIAS.contains(&keyword)
+        || RESERVED_FOR_TABLE_ALIAS.contains(&keyword)
+        || dialect.is_reserved_for_identifier(keyword)
+}
+
+/// Start positions of the words the parser found in each role, and of every token in
+/// statements it could not parse.
+#[derive(Default)]
+struct Names {
+    keywords: HashSet<Location>,
+    identifiers: HashSet<Location>,
+    functions: HashSet<Location>,
+    types: HashSet<Location>,
+    unparsed: HashSet<Location>,
+    /// The data types the statements use, which the AST does not locate.
+    data_types: Vec<DataType>,
+}
+
+impl Names {
+    fn collect(tokens: &[TokenWithSpan], dialect: &dyn Dialect) -> Self {
+        let mut names = Names::default();
+        // Parse the whole file first: a statement may only be valid in the context of
+        // the others (e.g. a procedure body containing semicolons).
+        if let Ok(statements) = parse(tokens, dialect) {
+            let _ = statements.visit(&mut names);
+            names.add_types(tokens, dialect);
+            return names;
+        }
+        for statement in split_statements(tokens) {
+            match parse(statement, dialect) {
+                Ok(statements) => {
+                    let _ = statements.visit(&mut names);
+                    names.add_types(statement, dialect);
+                }
+                Err(_) => names
+                    .unparsed
+                    .extend(statement.iter().map(|t| t.span.start)),
+            }
+        }
+        names
+    }
+
+    /// Collect the built-in data types the statements use: each keyword in `tokens`
+    /// that starts a data type is parsed as one, and is a type name if the statements
+    /// contain that type.
+    fn add_types(&mut self, tokens: &[TokenWithSpan], dialect: &dyn Dialect) {
+        let mut i = 0;
+        while i < tokens.len() {
+            let start = tokens[i].span.start;
+            let candidate = matches!(&tokens[i].token, Token::Word(w) if w.keyword != Keyword::NoKeyword && w.quote_style.is_none())
+                && !self.identifiers.contains(&start)
+                && !self.functions.contains(&start);
+            if !candidate {
+                i += 1;
+                continue;
+            }
+            let window = &tokens[i..tokens.len().min(i + MAX_TYPE_TOKENS)];
+            let mut parser = Parser::new(dialect).with_tokens_with_locations(window.to_vec());
+            match parser.parse_data_type() {
+                Ok(data_type)
+                    if !matches!(data_type, DataType::Custom(..))
+                        && self.data_types.contains(&data_type) =>
+                {
+                    let end = i + parser.index();
+                    self.types.extend(
+                        tokens[i..end]
+                            .iter()
+                            .filter(|t| matches!(t.token, Token::Word(_)))
+                            .map(|t| t.span.start)
+                            .filter(|start| !self.identifiers.contains(start)),
+                    );
+                    i = end.max(i + 1);
+                }
+                _ => i += 1,
+            }
+        }
+    }
+
+    fn identifier(&mut self, ident: &Ident) {
+        self.identifiers.insert(ident.span.start);
+    }
+
+    fn name(&mut self, name: &ObjectName) {
+        for ident in name.0.iter().filter_map(ObjectNamePart::as_ident) {
+            self.identifier(ident);
+        }
+    }
+
+    /// The last part of a function's name is the function's, the rest qualify it.
+    fn function(&mut self, name: &ObjectName) {
+        let Some((last, qualifiers)) = name.0.split_last() else {
+            return;
+        };
+        if let Some(ident) = last.as_ident() {
+            self.functions.insert(ident.span.start);
+        }
+        for ident in qualifiers.iter().filter_map(ObjectNamePart::as_ident) {
+            self.identifier(ident);
+        }
+    }
+
+    /// A user-defined type's name is located; built-in ones are found by `add_types`.
+    fn data_type(&mut self, data_type: &DataType) {
+        match data_type {
+            DataType::Custom(name, _) => self.types.extend(
+                name.0
+                    .iter()
+                    .filter_map(ObjectNamePart::as_ident)
+                    .map(|ident| ident.span.start),
+            ),
+            DataType::Array(
+                ArrayElemTypeDef::AngleBracket(element)
+                | ArrayElemTypeDef::SquareBracket(element, _)
+                | ArrayElemTypeDef::Parenthesis(element),
+            ) => self.data_type(element),
+            _ => {}
+        }
+        self.data_types.push(data_type.clone());
+    }
+
+    fn alias(&mut self, alias: &TableAlias) {
+        self.identifier(&alias.name);
+        for column in &alias.columns {
+            self.identifier(&column.name);
+            if let Some(data_type) = &column.data_type {
+                self.data_type(data_type);
+            }
+        }
+    }
+
+    fn column(&mut self, column: &ColumnDef) {
+        self.identifier(&column.name);
+        self.data_type(&column.data_type);
+        for option in &column.options {
+            if let Some(name) = &option.name {
+                self.identifier(name);
+            }
+            if let ColumnOption::ForeignKey(foreign_key) = &option.option {
+                self.foreign_key(foreign_key);
+            }
+        }
+    }
+
+    fn constraint(&mut self, constraint: &TableConstraint) {
+        let name = match constraint {
+            TableConstraint::Unique(unique) => &unique.name,
+            TableConstraint::PrimaryKey(primary_key) => &primary_key.name,
+            TableConstraint::ForeignKey(foreign_key) => {
+                self.foreign_key(foreign_key);
+                &foreign_key.name
+            }
+            TableConstraint::Check(check) => &check.name,
+            _ => &None,
+        };
+        if let Some(name) = name {
+            self.identifier(name);
+        }
+    }
+
+    fn foreign_key(&mut self, foreign_key: &ForeignKeyConstraint) {
+        for column in foreign_key
+            .columns
+            .iter()
+            .chain(&foreign_key.referred_columns)
+        {
+            self.identifier(column);
+        }
+        self.name(&foreign_key.foreign_table);
+    }
+
+    fn assignments(&mut self, assignments: &[Assignment]) {
+        for assignment in assignments {
+            match &assignment.target {
+                AssignmentTarget::ColumnName(name) => self.name(name),
+                AssignmentTarget::Tuple(names) => names.iter().for_each(|n| self.name(n)),
+            }
+        }
+    }
+
+    fn window(&mut self, window: &WindowSpec) {
+        if let Some(name) = &window.window_name {
+            self.identifier(name);
+        }
+    }
+
+    fn joins(&mut self, tables: &[TableWithJoins]) {
+        for join in tables.iter().flat_map(|t| &t.joins) {
+            if let Some(JoinConstraint::Using(columns)) = join_constraint(&join.join_operator) {
+                columns.iter().for_each(|c| self.name(c));
+            }
+        }
+    }
+
+    fn alter_table(&mut self, operation: &AlterTableOperation) {
+        match operation {
+            AlterTableOperation::AddColumn { column_def, .. } => self.column(column_def),
+            AlterTableOperation::AddConstraint { constraint, .. } => self.constraint(constraint),
+            AlterTableOperation::DropColumn { column_names, .. } => {
+                column_names.iter().for_each(|c| self.identifier(c))
+            }
+            AlterTableOperation::DropConstraint { name, .. } => self.identifier(name),
+            AlterTableOperation::RenameColumn {
+                old_column_name: old,
+                new_column_name: new,
+            }
+            | AlterTableOperation::RenameConstraint {
+                old_name: old,
+                new_name: new,
+            } => {
+                self.identifier(old);
+                self.identifier(new);
+            }
+            AlterTableOperation::ChangeColumn {
+                old_name,
+                new_name,
+                data_type,
+                ..
+            } => {
+                self.identifier(old_name);
+                self.identifier(new_name);
+                self.data_type(data_type);
+            }
+            AlterTableOperation::ModifyColumn {
+                col_name,
+                data_type,
+                ..
+            } => {
+                self.identifier(col_name);
+                self.data_type(data_type);
+            }
+            AlterTableOperation::AlterColumn { column_name, op } => {
+                self.identifier(column_name);
+                if let AlterColumnOperation::SetDataType { data_type, .. } = op {
+                    self.data_type(data_type);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Visitor for Names {
+    type Break = ();
+
+    fn pre_visit_relation(&mut self, relation: &ObjectName) -> ControlFlow<()> {
+        self.name(relation);
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<()> {
+        for cte in query.with.iter().flat_map(|with| &with.cte_tables) {
+            self.alias(&cte.alias);
+            if let Some(from) = &cte.from {
+                self.identifier(from);
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_select(&mut self, select: &Select) -> ControlFlow<()> {
+        for item in &select.projection {
+            match item {
+                SelectItem::ExprWithAlias { alias, .. } => self.identifier(alias),
+                SelectItem::ExprWithAliases { aliases, .. } => {
+                    aliases.iter().for_each(|a| self.identifier(a))
+                }
+                SelectItem::QualifiedWildcard(
+                    SelectItemQualifiedWildcardKind::ObjectName(name),
+                    _,
+                ) => self.name(name),
+                _ => {}
+            }
+        }
+        if let Some(into) = &select.into {
+            self.name(&into.name);
+        }
+        for NamedWindowDefinition(name, window) in &select.named_window {
+            self.identifier(name);
+            match window {
+                NamedWindowExpr::NamedWindow(name) => self.identifier(name),
+                NamedWindowExpr::WindowSpec(window) => self.window(window),
+            }
+        }
+        self.joins(&select.from);
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_table_factor(&mut self, table_factor: &TableFactor) -> ControlFlow<()> {
+        match table_factor {
+            // Table hints such as T-SQL's `WITH (NOLOCK)` are kept as expressions, but
+            // are keywords.
+            TableFactor::Table { with_hints, .. } => {
+                self.keywords
+                    .extend(with_hints.iter().filter_map(|hint| match hint {
+                        Expr::Identifier(ident) => Some(ident.span.start),
+                        _ => None,
+                    }))
+            }
+            TableFactor::Function { name, .. } => self.function(name),
+            TableFactor::NestedJoin {
+                table_with_joins, ..
+            } => self.joins(std::slice::from_ref(table_with_joins)),
+            _ => {}
+        }
+        if let Some(alias) = table_alias(table_factor) {
+            self.alias(alias);
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expr(&mut self, expr: &Expr) -> ControlFlow<()> {
+        match expr {
+            Expr::Identifier(ident) | Expr::Named { name: ident, .. } => self.identifier(ident),
+            Expr::CompoundIdentifier(idents) => idents.iter().for_each(|i| self.identifier(i)),
+            Expr::QualifiedWildcard(name, _) => self.name(name),
+            Expr::MatchAgainst { columns, .. } => columns.iter().for_each(|c| self.name(c)),
+            Expr::Function(function) => {
+                self.function(&function.name);
+                match &function.over {
+                    Some(WindowType::NamedWindow(name)) => self.identifier(name),
+                    Some(WindowType::WindowSpec(window)) => self.window(window),
+                    None => {}
+                }
+                if let FunctionArguments::List(list) = &function.args {
+                    for arg in &list.args {
+                        if let FunctionArg::Named { name, .. } = arg {
+                            self.identifier(name);
+                        }
+                    }
+                }
+            }
+            Expr::Lambda(lambda) => {
+                for param in &lambda.params {
+                    self.identifier(&param.name);
+                    if let Some(data_type) = &param.data_type {
+                        self.data_type(data_type);
+                    }
+                }
+            }
+            Expr::Cast { data_type, .. }
+            | Expr::Convert {
+                data_type: Some(data_type),
+                ..
+            }
+            | Expr::TypedString(TypedString { data_type, .. }) => self.data_type(data_type),
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_statement(&mut self, statement: &Statement) -> ControlFlow<()> {
+        match statement {
+            Statement::CreateTable(create) => {
+                create.columns.iter().for_each(|c| self.column(c));
+                create.constraints.iter().for_each(|c| self.constraint(c));
+            }
+            Statement::CreateView(create) => {
+                self.name(&create.name);
+                for column in &create.columns {
+                    self.identifier(&column.name);
+                    if let Some(data_type) = &column.data_type {
+                        self.data_type(data_type);
+                    }
+                }
+            }
+            Statement::CreateIndex(create) => {
+                if let Some(name) = &create.name {
+                    self.name(name);
+                }
+            }
+            Statement::AlterTable(alter) => {
+                for operation in &alter.operations {
+                    self.alter_table(operation);
+                }
+            }
+            Statement::CreateFunction(create) => {
+                self.name(&create.name);
+                for arg in create.args.iter().flatten() {
+                    if let Some(name) = &arg.name {
+                        self.identifier(name);
+                    }
+                    self.data_type(&arg.data_type);
+                }
+                if let Some(
+                    FunctionReturnType::DataType(data_type) | FunctionReturnType::SetOf(data_type),
+                ) = &create.return_type
+                {
+                    self.data_type(data_type);
+                }
+                if let Some(language) = &create.language {
+                    self.identifier(language);
+                }
+            }
+            Statement::Drop { names, .. } => names.iter().for_each(|n| self.name(n)),
+            Statement::Comment { object_name, .. } => self.name(object_name),
+            Statement::Grant(Grant {
+                objects: Some(objects),
+                ..
+            })
+            | Statement::Revoke(Revoke {
+                objects: Some(objects),
+                ..
+            }) => match objects {
+                GrantObjects::Databases(names)
+                | GrantObjects::Schemas(names)
+                | GrantObjects::Sequences(names)
+                | GrantObjects::Tables(names)
+                | GrantObjects::Views(names) => names.iter().for_each(|n| self.name(n)),
+                _ => {}
+            },
+            Statement::Insert(insert) => {
+                insert.columns.iter().for_each(|c| self.name(c));
+                insert.after_columns.iter().for_each(|c| self.identifier(c));
+                self.assignments(&insert.assignments);
+                match &insert.on {
+                    Some(OnInsert::DuplicateKeyUpdate(assignments)) => {
+                        self.assignments(assignments)
+                    }
+                    Some(OnInsert::OnConflict(on_conflict)) => {
+                        match &on_conflict.conflict_target {
+                            Some(ConflictTarget::Columns(columns)) => {
+                                columns.iter().for_each(|c| self.identifier(c))
+                            }
+                            Some(ConflictTarget::OnConstraint(name)) => self.name(name),
+                            None => {}
+                        }
+                        if let OnConflictAction::DoUpdate(update) = &on_conflict.action {
+                            self.assignments(&update.assignments);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Statement::Merge(merge) => {
+                for clause in &merge.clauses {
+                    match &clause.action {
+                        MergeAction::Insert(insert) => {
+                            insert.columns.iter().for_each(|c| self.name(c))
+                        }
+                        MergeAction::Update(update) => self.assignments(&update.assignments),
+                        _ => {}
+                    }
+                }
+            }
+            Statement::Update(update) => {
+                self.assignments(&update.assignments);
+                self.joins(std::slice::from_ref(&update.table));
+            }
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+/// The alias of any kind of table factor.
+fn table_alias(table_factor: &TableFactor) -> Option<&TableAlias> {
+    match table_factor {
+        TableFactor::Table { alias, .. }
+        | TableFactor::Derived { alias, .. }
+        | TableFactor::TableFunction { alias, .. }
+        | TableFactor::Function { alias, .. }
+        | TableFactor::UNNEST { alias, .. }
+        | TableFactor::JsonTable { alias, .. }
+        | TableFactor::OpenJsonTable { alias, .. }
+        | TableFactor::NestedJoin { alias, .. }
+        | TableFactor::Pivot { alias, .. }
+        | TableFactor::Unpivot { alias, .. }
+        | TableFactor::MatchRecognize { alias, .. }
+        | TableFactor::XmlTable { alias, .. }
+        | TableFactor::SemanticView { alias, .. } => alias.as_ref(),
+    }
+}
+
+/// The `ON` or `USING` constraint of a join, for the kinds of join that have one.
+fn join_constraint(operator: &JoinOperator) -> Option<&JoinConstraint> {
+    match operator {
+        JoinOperator::Join(constraint)
+        | JoinOperator::Inner(constraint)
+        | JoinOperator::Left(constraint)
+        | JoinOperator::LeftOuter(constraint)
+        | JoinOperator::Right(constraint)
+        | JoinOperator::RightOuter(constraint)
+        | JoinOperator::FullOuter(constraint)
+        | JoinOperator::CrossJoin(constraint)
+        | JoinOperator::Semi(constraint)
+        | JoinOperator::LeftSemi(constraint)
+        | JoinOperator::RightSemi(constraint)
+        | JoinOperator::Anti(constraint)
+        | JoinOperator::LeftAnti(constraint)
+        | JoinOperator::RightAnti(constraint)
+        | JoinOperator::StraightJoin(constraint)
+        | JoinOperator::AsOf { constraint, .. } => Some(constraint),
+        _ => None,
+    }
+}
+
+fn parse(tokens: &[TokenWithSpan], dialect: &dyn Dialect) -> Result<Vec<Statement>, ParserError> {
+    Parser::new(dialect)
+        .with_tokens_with_locations(tokens.to_vec())
+        .parse_statements()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlparser::dialect::{GenericDialect, MySqlDialect, PostgreSqlDialect};
+    use sqlparser::tokenizer::Tokenizer;
+
+    fn tokenize(sql: &str, dialect: &dyn Dialect) -> Vec<TokenWithSpan> {
+        Tokenizer::new(dialect, sql)
+            .tokenize_with_location()
+            .unwrap()
+    }
+
+    /// The words of `sql` that play `role`.
+    fn words_in(sql: &str, dialect: &dyn Dialect, config: &LintConfig, role: Role) -> Vec<String> {
+        let tokens = tokenize(sql, dialect);
+        token_roles(&tokens, dialect, config)
+            .into_iter()
+            .zip(&tokens)
+            .filter(|(r, _)| *r == Some(role))
+            .map(|(_, t)| t.token.to_string())
+            .collect()
+    }
+
+    fn keywords_in(sql: &str, dialect: &dyn Dialect, config: &LintConfig) -> Vec<String> {
+        words_in(sql, dialect, config, Role::Keyword)
+    }
+
+    /// `sql` with every miscased word replaced by its expected spelling.
+    fn recased(sql: &str, config: &LintConfig) -> String {
+        let dialect = GenericDialect {};
+        let tokens = tokenize(sql, &dialect);
+        let mut words: Vec<String> = tokens.iter().map(|t| t.token.to_string()).collect();
+        for word in miscased(&tokens, &dialect, config) {
+            words[word.index] = word.expected;
+        }
+        words.concat()
+    }
+
+    #[test]
+    fn test_keywords_beyond_the_common_ones() {
+        let config = LintConfig::default();
+        assert_eq!(
+            keywords_in(
+                "insert into t (a) values (1) returning a",
+                &PostgreSqlDialect {},
+                &config
+            ),
+            ["insert", "into", "values", "returning"]
+        );
+        assert_eq!(
+            keywords_in(
+                "select a from t where b ilike 'x' and c > now() - interval '1 day'",
+                &PostgreSqlDialect {},
+                &config
+            ),
+            ["select", "from", "where", "ilike", "and", "interval"]
+        );
+    }
+
+    #[test]
+    fn test_names_that_are_keywords() {
+        let config = LintConfig::default();
+        let sql = "select status, name from orders as data where o.year > 1";
+        assert_eq!(
+            keywords_in(sql, &GenericDialect {}, &config),
+            ["select", "from", "as", "where"]
+        );
+        assert_eq!(
+            words_in(sql, &GenericDialect {}, &config, Role::Identifier),
+            ["status", "name", "orders", "data", "o", "year"]
+        );
+    }
+
+    #[test]
+    fn test_functions_and_types() {
+        let config = LintConfig::default();
+        let sql = "select count(*), cast(a as double precision), s.f(b) from t";
+        assert_eq!(
+            words_in(sql, &GenericDialect {}, &config, Role::Function),
+            ["count", "cast", "f"]
+        );
+        assert_eq!(
+            words_in(sql, &GenericDialect {}, &config, Role::Type),
+            ["double", "precision"]
+        );
+
+        let sql = "create table t (id integer, name varchar(10), tags my_type)";
+        assert_eq!(
+            words_in(sql, &GenericDialect {}, &config, Role::Type),
+            ["integer", "varchar", "my_type"]
+        );
+        assert_eq!(
+            keywords_in(sql, &GenericDialect {}, &config),
+            ["create", "table"]
+        );
+    }
+
+    #[test]
+    fn test_names_in_statements() {
+        let config = LintConfig::default();
+        let identifiers = |sql| words_in(sql, &PostgreSqlDialect {}, &config, Role::Identifier);
+        assert_eq!(
+            identifiers(
+                "insert into t (status) values (1) on conflict (status) do update set status = 2"
+            ),
+            ["t", "status", "status", "status"]
+        );
+        assert_eq!(
+            identifiers("alter table t rename column status to level"),
+            ["t", "status", "level"]
+        );
+        assert_eq!(
+            identifiers("with data (level) as (select 1) select level from data"),
+            ["data", "level", "level", "data"]
+        );
+    }
+
+    #[test]
+    fn test_unparsed_statements_only_recognize_reserved_words() {
+        let config = LintConfig::default();
+        assert_eq!(
+            keywords_in("select status from t where;", &MySqlDialect {}, &config),
+            ["select", "from", "where"]
+        );
+    }
+
+    #[test]
+    fn test_dialect_keywords() {
+        let config = LintConfig::default();
+        assert_eq!(
+            keywords_in("select id from t with (nolock);", &MsSqlDialect {}, &config),
+            ["select", "from", "with", "nolock"]
+        );
+        // The trailing word makes the statement unparsable, so only reserved words
+        // count; ClickHouse reserves FINAL and PREWHERE.
+        assert_eq!(
+            keywords_in(
+                "select id from t final prewhere x = 1 oops;",
+                &ClickHouseDialect {},
+                &config
+            ),
+            ["select", "from", "final", "prewhere"]
+        );
+        // GO is a batch separator in T-SQL and a plain word elsewhere.
+        assert_eq!(keywords_in("go", &MsSqlDialect {}, &config), ["go"]);
+        assert!(keywords_in("go", &GenericDialect {}, &config).is_empty());
+    }
+
+    #[test]
+    fn test_extra_and_excluded_keywords() {
+        let config = LintConfig {
+            extra_keywords: vec!["PIVOTAL".to_string()],
+            exclude_keywords: vec!["Returning".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            keywords_in(
+                "select pivotal from t returning x",
+                &GenericDialect {},
+                &config
+            ),
+            ["select", "pivotal", "from"]
+        );
+    }
+
+    #[test]
+    fn test_each_role_has_its_own_case() {
+        let config = LintConfig {
+            keyword_case: KeywordCase::Upper,
+            function_case: KeywordCase::Lower,
+            type_case: Some(KeywordCase::Capitalize),
+            identifier_case: KeywordCase::Lower,
+            ..Default::default()
+        };
+        assert_eq!(
+            recased("select MAX(Id), CAST(x AS int) from Users", &config),
+            "SELECT max(id), cast(x AS Int) FROM users"
+        );
+    }
+
+    #[test]
+    fn test_consistent_follows_the_first_word() {
+        let config = LintConfig {
+            keyword_case: KeywordCase::Consistent,
+            identifier_case: KeywordCase::Consistent,
+            ..Default::default()
+        };
+        assert_eq!(
+            recased("Select ID, name FROM Users where x = 1", &config),
+            "Select ID, NAME From USERS Where X = 1"
+        );
+    }
+
+    #[test]
+    fn test_case_of() {
+        assert_eq!(case_of("SELECT"), Some(KeywordCase::Upper));
+        assert_eq!(case_of("select"), Some(KeywordCase::Lower));
+        assert_eq!(case_of("Select"), Some(KeywordCase::Capitalize));
+        assert_eq!(case_of("userId"), None);
+        assert_eq!(case_of("_1"), None);
+    }
+}
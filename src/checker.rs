@@ -1,42 +1,102 @@
-use crate::cli::FixFormat;
+use crate::baseline::Baseline;
+use crate::cache::Cache;
+use crate::casing::miscased;
+use crate::config::{Resolver, Settings};
+use crate::detect;
 use crate::error::SqlexError;
+use crate::formatter::format_sql;
+use crate::git::Changes;
 use crate::highlight::SourceHighlighter;
 use crate::hints;
 use crate::i18n::Messages;
-use crate::linter::{is_sql_keyword, KeywordCase, LintConfig, Linter};
+use crate::linter::{LintConfig, LintError, Linter};
+use crate::report::{
+    Diagnostic, DiagnosticReporter, EditReporter, FileEdits, FileReport, OutputFormat,
+};
+use crate::suppression::Suppressions;
+use crate::target;
 use anyhow::{Context, Result};
+use clap::ValueEnum;
 use colored::Colorize;
+use rayon::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use similar::{ChangeTag, TextDiff};
 use sqlparser::dialect::{
-    BigQueryDialect, Dialect, GenericDialect, MySqlDialect, PostgreSqlDialect, SQLiteDialect,
+    AnsiDialect, BigQueryDialect, ClickHouseDialect, DatabricksDialect, Dialect, DuckDbDialect,
+    GenericDialect, HiveDialect, MsSqlDialect, MySqlDialect, PostgreSqlDialect, RedshiftSqlDialect,
+    SQLiteDialect, SnowflakeDialect,
 };
 use sqlparser::parser::Parser;
-use sqlparser::tokenizer::{Token, Tokenizer};
+use sqlparser::tokenizer::{Location, Span, Token, TokenWithSpan, Tokenizer};
+use std::borrow::Cow;
 use std::fs;
+use std::io::{self, Read, Write};
 use std::path::Path;
 use walkdir::WalkDir;
 
-fn get_dialect(name: &str) -> Result<Box<dyn Dialect>> {
+/// Output format for `fix --dry-run`.
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum FixFormat {
+    /// Summary of changes (default)
+    #[default]
+    Summary,
+    /// Unified diff format
+    Diff,
+    /// JSON document listing the edits
+    Json,
+    /// One JSON edit per line
+    Ndjson,
+}
+
+impl FixFormat {
+    fn output_format(self) -> Option<OutputFormat> {
+        match self {
+            FixFormat::Json => Some(OutputFormat::Json),
+            FixFormat::Ndjson => Some(OutputFormat::Ndjson),
+            FixFormat::Summary | FixFormat::Diff => None,
+        }
+    }
+}
+
+/// Names of the supported dialects, as accepted by [`get_dialect`]. Aliases are left out.
+pub const DIALECTS: &[&str] = &[
+    "generic",
+    "ansi",
+    "mysql",
+    "postgres",
+    "sqlite",
+    "bigquery",
+    "mssql",
+    "snowflake",
+    "redshift",
+    "clickhouse",
+    "duckdb",
+    "hive",
+    "databricks",
+];
+
+/// Look up a dialect by name (case-insensitive), e.g. `"mysql"` or `"postgres"`.
+pub fn get_dialect(name: &str) -> Result<Box<dyn Dialect>, SqlexError> {
     match name.to_lowercase().as_str() {
         "generic" => Ok(Box::new(GenericDialect {})),
+        "ansi" => Ok(Box::new(AnsiDialect {})),
         "mysql" => Ok(Box::new(MySqlDialect {})),
         "postgres" | "postgresql" => Ok(Box::new(PostgreSqlDialect {})),
         "sqlite" => Ok(Box::new(SQLiteDialect {})),
         "bigquery" => Ok(Box::new(BigQueryDialect {})),
-        _ => Err(SqlexError::UnsupportedDialect(name.to_string()).into()),
+        "mssql" | "tsql" | "sqlserver" => Ok(Box::new(MsSqlDialect {})),
+        "snowflake" => Ok(Box::new(SnowflakeDialect {})),
+        "redshift" => Ok(Box::new(RedshiftSqlDialect {})),
+        "clickhouse" => Ok(Box::new(ClickHouseDialect {})),
+        "duckdb" => Ok(Box::new(DuckDbDialect {})),
+        "hive" => Ok(Box::new(HiveDialect {})),
+        "databricks" => Ok(Box::new(DatabricksDialect {})),
+        _ => Err(SqlexError::UnsupportedDialect(name.to_string())),
     }
 }
 
-fn parse_keyword_case(name: &str) -> KeywordCase {
-    match name.to_lowercase().as_str() {
-        "lower" => KeywordCase::Lower,
-        "ignore" => KeywordCase::Ignore,
-        // "upper" and any unrecognized value default to upper.
-        _ => KeywordCase::Upper,
-    }
-}
-
-fn collect_sql_files(paths: &[String]) -> Vec<String> {
+fn collect_sql_files(paths: &[String], resolver: &Resolver) -> Result<Vec<String>> {
     let mut files = Vec::new();
 
     for path in paths {
@@ -44,22 +104,123 @@ fn collect_sql_files(paths: &[String]) -> Vec<String> {
         if p.is_file() && path.ends_with(".sql") {
             files.push(path.clone());
         } else if p.is_dir() {
-            for entry in WalkDir::new(p).into_iter().filter_map(|e| e.ok()) {
+            // Sorted so that reports come out in the same order on every platform.
+            for entry in WalkDir::new(p)
+                .sort_by_file_name()
+                .into_iter()
+                .filter_map(|e| e.ok())
+            {
                 let entry_path = entry.path();
-                if entry_path.is_file() && entry_path.extension().is_some_and(|ext| ext == "sql") {
+                if entry_path.is_file() && resolver.is_selected(entry_path)? {
                     files.push(entry_path.to_string_lossy().to_string());
                 }
             }
         }
     }
 
-    files
+    Ok(files)
+}
+
+/// Where `check`, `lint` and `fix` read SQL from.
+#[derive(Debug, Clone, Default)]
+pub struct Inputs {
+    /// Files or directories to process; `-` reads SQL from stdin.
+    pub paths: Vec<String>,
+    /// Inline SQL text, processed as if it were one more file.
+    pub sql: Option<String>,
+    /// Name to report for stdin or inline SQL, also used to look up its configuration.
+    pub stdin_filename: Option<String>,
+    /// Only process files that changed, and only lint their changed lines. Stdin and
+    /// inline SQL are always processed in full.
+    pub changes: Option<Changes>,
+}
+
+/// A single piece of SQL to process.
+pub(crate) enum Source {
+    File(String),
+    /// SQL read from stdin or given inline, with the name it is reported under.
+    Text {
+        name: String,
+        content: String,
+    },
+}
+
+impl Source {
+    pub(crate) fn name(&self) -> &str {
+        match self {
+            Source::File(path) => path,
+            Source::Text { name, .. } => name,
+        }
+    }
+
+    pub(crate) fn read(&self) -> Result<Cow<'_, str>> {
+        match self {
+            Source::File(path) => fs::read_to_string(path)
+                .map(Cow::Owned)
+                .with_context(|| format!("Failed to read: {}", path)),
+            Source::Text { content, .. } => Ok(Cow::Borrowed(content)),
+        }
+    }
+}
+
+pub(crate) fn collect_sources(inputs: &Inputs, resolver: &Resolver) -> Result<Vec<Source>> {
+    let text_name = |default: &str| {
+        inputs
+            .stdin_filename
+            .clone()
+            .unwrap_or_else(|| default.to_string())
+    };
+
+    let mut sources = Vec::new();
+    let mut stdin_read = false;
+
+    for path in &inputs.paths {
+        if path == "-" {
+            // stdin can only be consumed once, so repeated `-` arguments are ignored.
+            if !stdin_read {
+                let mut content = String::new();
+                io::stdin()
+                    .read_to_string(&mut content)
+                    .context("Failed to read: stdin")?;
+                sources.push(Source::Text {
+                    name: text_name("<stdin>"),
+                    content,
+                });
+                stdin_read = true;
+            }
+        } else {
+            let mut files = collect_sql_files(std::slice::from_ref(path), resolver)?;
+            if let Some(changes) = &inputs.changes {
+                files.retain(|file| changes.contains_file(Path::new(file)));
+            }
+            sources.extend(files.into_iter().map(Source::File));
+        }
+    }
+
+    if let Some(sql) = &inputs.sql {
+        sources.push(Source::Text {
+            name: text_name("<sql>"),
+            content: sql.clone(),
+        });
+    }
+
+    Ok(sources)
 }
 
-#[derive(Debug)]
+/// A syntax error reported by [`check_sql`].
+///
+/// Positions are 1-based; the end position is exclusive. sqlparser only reports where
+/// an error starts, so the end covers the offending token when it can be found and
+/// equals the start otherwise.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyntaxError {
+    /// `syntax-error`, or `target-version` for a construct the target database
+    /// version does not support yet.
+    pub rule: String,
     pub line: usize,
     pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
     pub message: String,
 }
 
@@ -95,128 +256,505 @@ fn parse_error_location(error_msg: &str) -> (usize, usize) {
     (line, column)
 }
 
-fn check_sql(content: &str, dialect: &dyn Dialect) -> Vec<SyntaxError> {
-    match Parser::parse_sql(dialect, content) {
-        Ok(_) => vec![],
-        Err(e) => {
-            let msg = e.to_string();
-            let (line, column) = parse_error_location(&msg);
-            vec![SyntaxError {
-                line,
-                column,
-                message: msg,
-            }]
-        }
+/// Parse `content` with `dialect` and return the syntax errors found (empty when valid).
+///
+/// sqlparser stops at the first error, so after each error parsing resumes with the
+/// statement after the broken one, reporting every broken statement at its original
+/// position. The rest of the file is always parsed as a whole, so statements that only
+/// parse in context (e.g. a procedure body containing semicolons) are not reported.
+pub fn check_sql(content: &str, dialect: &dyn Dialect) -> Vec<SyntaxError> {
+    let Err(first_error) = Parser::parse_sql(dialect, content) else {
+        return vec![];
+    };
+
+    // The tokenizer already failed inside parse_sql (e.g. an unterminated string), so
+    // there is nothing to split.
+    let Ok(tokens) = Tokenizer::new(dialect, content).tokenize_with_location() else {
+        return vec![syntax_error(first_error.to_string(), &[])];
+    };
+
+    let statements = split_statements(&tokens);
+    // Give the end of input a location so errors there point at the end of the file.
+    let end = tokens.last().map_or(Location::empty(), |t| t.span.end);
+    let mut errors = Vec::new();
+    let mut next = 0;
+    while let Some(statement) = statements.get(next) {
+        let start = tokens.partition_point(|t| t.span.start < statement[0].span.start);
+        let mut rest = tokens[start..].to_vec();
+        rest.push(TokenWithSpan::new(Token::EOF, Span::new(end, end)));
+        let Err(e) = Parser::new(dialect)
+            .with_tokens_with_locations(rest)
+            .parse_statements()
+        else {
+            break;
+        };
+        let error = syntax_error(e.to_string(), &tokens);
+        // Resume after the statement the error is in (the current one if the error
+        // has no position).
+        let at = Location::new(error.line as u64, error.column as u64);
+        next += statements[next..]
+            .iter()
+            .position(|s| s.last().is_some_and(|t| t.span.end >= at))
+            .unwrap_or(0)
+            + 1;
+        errors.push(error);
+    }
+
+    // The split should reproduce the error of the whole-file parse; fall back to it
+    // if it somehow does not.
+    if errors.is_empty() {
+        vec![syntax_error(first_error.to_string(), &tokens)]
+    } else {
+        errors
     }
 }
 
-pub fn check(paths: &[String], dialect_name: &str, messages: &Messages) -> Result<()> {
-    let dialect = get_dialect(dialect_name)?;
-    let files = collect_sql_files(paths);
+/// Syntax errors in `content` with `settings`: those of [`check_sql`], plus the
+/// constructs the target database version does not support yet.
+pub(crate) fn check_content(
+    content: &str,
+    dialect: &dyn Dialect,
+    settings: &Settings,
+    messages: &Messages,
+) -> Vec<SyntaxError> {
+    let mut errors = check_sql(content, dialect);
+    let Some(version) = &settings.target_version else {
+        return errors;
+    };
+    errors.extend(
+        target::unsupported(content, dialect, version)
+            .into_iter()
+            .map(|found| SyntaxError {
+                rule: "target-version".to_string(),
+                line: found.start.line as usize,
+                column: found.start.column as usize,
+                end_line: found.end.line as usize,
+                end_column: found.end.column as usize,
+                message: messages.unsupported_feature(
+                    found.feature,
+                    found.database,
+                    &found.since,
+                    &version.to_string(),
+                ),
+            }),
+    );
+    errors.sort_by_key(|e| (e.line, e.column));
+    errors
+}
 
-    if files.is_empty() {
-        eprintln!("{}", "No SQL files found".yellow());
-        return Ok(());
+/// Split a token stream into statements at semicolons, keeping each semicolon with
+/// its statement. Strings, comments and dollar-quoted bodies are single tokens, so
+/// semicolons inside them never split. Whitespace-only pieces are dropped.
+pub(crate) fn split_statements(tokens: &[TokenWithSpan]) -> Vec<&[TokenWithSpan]> {
+    let mut statements: Vec<&[TokenWithSpan]> = tokens
+        .split_inclusive(|t| t.token == Token::SemiColon)
+        .collect();
+
+    statements.retain(|statement| {
+        statement
+            .iter()
+            .any(|t| !matches!(t.token, Token::Whitespace(_) | Token::SemiColon))
+    });
+    statements
+}
+
+/// Build a [`SyntaxError`] from a parser message, extending its end over the token
+/// the error points at when there is one.
+fn syntax_error(message: String, tokens: &[TokenWithSpan]) -> SyntaxError {
+    let (line, column) = parse_error_location(&message);
+    let (end_line, end_column) = tokens
+        .iter()
+        .find(|t| (t.span.start.line as usize, t.span.start.column as usize) == (line, column))
+        .map(|t| (t.span.end.line as usize, t.span.end.column as usize))
+        .unwrap_or((line, column));
+    SyntaxError {
+        rule: "syntax-error".to_string(),
+        line,
+        column,
+        end_line,
+        end_column,
+        message,
     }
+}
 
-    let mut total_errors = 0;
+/// Read every source and run `f` on its content and settings, on up to `jobs` threads
+/// (`0`: one per CPU core). Results come back in source order, so reports do not
+/// depend on scheduling. Detected dialects are looked up in `cache`.
+pub(crate) fn process<T: Send>(
+    sources: &[Source],
+    resolver: &Resolver,
+    jobs: usize,
+    cache: Option<&Cache>,
+    messages: &Messages,
+    f: impl Fn(&str, &Settings) -> Result<T> + Sync,
+) -> Result<Vec<(String, T)>> {
+    // The resolver's config cache is not thread-safe, so look up settings up front.
+    let settings = sources
+        .iter()
+        .map(|source| resolver.settings_for(Path::new(source.name())))
+        .collect::<Result<Vec<_>>>()?;
+
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(jobs).build()?;
+    let results = pool.install(|| {
+        sources
+            .par_iter()
+            .zip(&settings)
+            .map(|(source, settings)| {
+                let content = source.read()?.into_owned();
+                let (settings, detection) =
+                    detect::resolve(settings, Path::new(source.name()), &content, cache)?;
+                let result = f(&content, &settings)?;
+                Ok((content, result, detection))
+            })
+            .collect::<Result<Vec<_>>>()
+    })?;
+
+    // Report detected dialects in input order, on stderr to keep reports parseable.
+    Ok(results
+        .into_iter()
+        .zip(sources)
+        .map(|((content, result, detection), source)| {
+            if let Some(detection) = detection {
+                eprintln!(
+                    "{}",
+                    messages
+                        .dialect_detected(source.name(), &detection)
+                        .dimmed()
+                );
+            }
+            (content, result)
+        })
+        .collect())
+}
 
-    for file in &files {
-        let content =
-            fs::read_to_string(file).with_context(|| format!("Failed to read: {}", file))?;
+/// Look `content` up in `cache` under `key`, computing the result on a miss (or
+/// without a cache).
+pub(crate) fn cached<T: Serialize + DeserializeOwned>(
+    cache: Option<&Cache>,
+    key: &[&str],
+    content: &str,
+    compute: impl FnOnce() -> Result<T>,
+) -> Result<T> {
+    match cache {
+        Some(cache) => cache.get_or_compute(key, content, compute),
+        None => compute(),
+    }
+}
 
-        let errors = check_sql(&content, dialect.as_ref());
+/// Lint `content`, reusing a cached result for the same settings. Messages are part of
+/// the key since warnings are stored already translated.
+fn lint_cached(
+    cache: Option<&Cache>,
+    content: &str,
+    settings: &Settings,
+    dialect: &dyn Dialect,
+    messages: &Messages,
+) -> Result<Vec<LintError>> {
+    let config = format!("{:?}", settings.lint);
+    let key = ["lint", &settings.dialect, &config, messages.lang()];
+    cached(cache, &key, content, || {
+        Ok(Linter::new(settings.lint.clone()).lint(content, dialect, messages))
+    })
+}
 
-        if errors.is_empty() {
-            println!("{}", messages.file_ok(file).green());
-        } else {
-            println!("{}", messages.file_error(file, errors.len()).red());
-            for error in &errors {
-                println!(
-                    "  {}",
-                    messages.syntax_error(error.line, error.column, &error.message)
-                );
+/// Check files and directories for syntax errors, printing a report to stdout.
+///
+/// Returns the total number of errors found.
+pub fn check(
+    inputs: &Inputs,
+    resolver: &Resolver,
+    jobs: usize,
+    cache: Option<&Cache>,
+    format: OutputFormat,
+    messages: &Messages,
+) -> Result<usize> {
+    let sources = collect_sources(inputs, resolver)?;
 
-                // Analyze error and provide hints
-                let hint = hints::analyze_error(&error.message, &content, error.line, messages);
+    if sources.is_empty() {
+        eprintln!("{}", "No SQL files found".yellow());
+        // Machine-readable formats still emit an (empty) report.
+        if format == OutputFormat::Text {
+            return Ok(0);
+        }
+    }
 
-                if let Some(ref h) = hint {
-                    println!("  {} {}", "💡".yellow(), h.hint.yellow());
-                }
+    let mut reporter = (format != OutputFormat::Text)
+        .then(|| DiagnosticReporter::new(format, "check", messages, io::stdout().lock()));
+    let mut total_errors = 0;
 
-                // Display highlighted source code with suspect line
-                let suspect_line = hint.and_then(|h| h.suspect_line);
-                let highlight = SourceHighlighter::display_error_with_hint(
-                    &content,
-                    error.line,
-                    error.column,
-                    suspect_line,
-                    2,
-                );
-                println!("{}", highlight);
-                println!();
-            }
-            total_errors += errors.len();
+    let results = process(
+        &sources,
+        resolver,
+        jobs,
+        cache,
+        messages,
+        |content, settings| {
+            let dialect = get_dialect(&settings.dialect)?;
+            let target = settings
+                .target_version
+                .as_ref()
+                .map(|version| version.to_string())
+                .unwrap_or_default();
+            let key = ["check", &settings.dialect, &target, messages.lang()];
+            cached(cache, &key, content, || {
+                Ok(check_content(content, dialect.as_ref(), settings, messages))
+            })
+        },
+    )?;
+
+    for (source, (content, errors)) in sources.iter().zip(results) {
+        let file = source.name();
+        total_errors += errors.len();
+
+        if let Some(reporter) = reporter.as_mut() {
+            let diagnostics = errors
+                .iter()
+                .map(|error| {
+                    let hint = syntax_hint(error, &content, messages);
+                    Diagnostic::syntax(file, error, hint.map(|h| h.hint))
+                })
+                .collect();
+            reporter.file(FileReport {
+                path: file.to_string(),
+                diagnostics,
+            })?;
+        } else {
+            print!(
+                "{}",
+                render_syntax_errors(file, &content, &errors, messages)
+            );
         }
     }
 
-    println!("{}", messages.summary(files.len(), total_errors));
+    match reporter {
+        Some(reporter) => reporter.finish()?,
+        None => println!("{}", messages.summary(sources.len(), total_errors)),
+    }
 
-    if total_errors > 0 {
-        std::process::exit(1);
+    Ok(total_errors)
+}
+
+/// A hint for a parser error. Version findings say what they need already.
+fn syntax_hint(
+    error: &SyntaxError,
+    content: &str,
+    messages: &Messages,
+) -> Option<hints::ErrorHint> {
+    if error.rule != "syntax-error" {
+        return None;
+    }
+    hints::analyze_error(&error.message, content, error.line, messages)
+}
+
+/// Text report for one file checked for syntax errors, with hints and highlighted
+/// source for each error.
+pub(crate) fn render_syntax_errors(
+    file: &str,
+    content: &str,
+    errors: &[SyntaxError],
+    messages: &Messages,
+) -> String {
+    if errors.is_empty() {
+        return format!("{}\n", messages.file_ok(file).green());
     }
 
-    Ok(())
+    let mut out = format!("{}\n", messages.file_error(file, errors.len()).red());
+    for error in errors {
+        let heading = match error.rule.as_str() {
+            "target-version" => messages.version_error(error.line, error.column, &error.message),
+            _ => messages.syntax_error(error.line, error.column, &error.message),
+        };
+        out += &format!("  {}\n", heading);
+
+        // Analyze error and provide hints
+        let hint = syntax_hint(error, content, messages);
+
+        if let Some(ref h) = hint {
+            out += &format!("  {} {}\n", "💡".yellow(), h.hint.yellow());
+        }
+
+        // Display highlighted source code with suspect line
+        let suspect_line = hint.and_then(|h| h.suspect_line);
+        let highlight = SourceHighlighter::display_error_with_hint(
+            content,
+            error.line,
+            error.column,
+            suspect_line,
+            2,
+        );
+        out += &format!("{}\n\n", highlight);
+    }
+    out
 }
 
+/// Fix files in place, or print what would change when `dry_run` is set. `check` is a
+/// dry run for CI that lists the files that would change, or their diffs.
+///
+/// Returns the number of files that were, or would be, changed.
 pub fn fix(
-    paths: &[String],
-    dialect_name: &str,
-    keyword_case: &str,
+    inputs: &Inputs,
+    resolver: &Resolver,
+    jobs: usize,
     dry_run: bool,
+    check: bool,
     format: FixFormat,
     messages: &Messages,
-) -> Result<()> {
-    let dialect = get_dialect(dialect_name)?;
-    let kw_case = parse_keyword_case(keyword_case);
-    let files = collect_sql_files(paths);
+) -> Result<usize> {
+    // `--check` reports like a dry run, minus the per-line summaries.
+    let dry_run = dry_run || check;
+    let sources = collect_sources(inputs, resolver)?;
+    if sources.len() > 1 && sources.iter().any(|s| matches!(s, Source::Text { .. })) {
+        // Fixed stdin or inline SQL is written to stdout, which would interleave with the rest.
+        anyhow::bail!("stdin or --sql input cannot be fixed together with other files");
+    }
+
+    let mut reporter = format
+        .output_format()
+        .map(|f| EditReporter::new(f, dry_run, io::stdout().lock()));
 
-    if files.is_empty() {
+    if sources.is_empty() {
         eprintln!("{}", "No SQL files found".yellow());
-        return Ok(());
+        // Machine-readable formats still emit an (empty) report.
+        if let Some(reporter) = reporter {
+            reporter.finish()?;
+        }
+        return Ok(0);
     }
 
-    for file in &files {
-        let content =
-            fs::read_to_string(file).with_context(|| format!("Failed to read: {}", file))?;
-
-        let new_content = fix_content(&content, dialect.as_ref(), kw_case)?;
+    let results = process(
+        &sources,
+        resolver,
+        jobs,
+        None,
+        messages,
+        |content, settings| {
+            let dialect = get_dialect(&settings.dialect)?;
+            Ok(fix_edits(content, dialect.as_ref(), &settings.lint))
+        },
+    )?;
+
+    let mut changed = 0;
+    for (source, (content, edits)) in sources.iter().zip(results) {
+        let file = source.name();
+        let new_content = apply_edits(&content, &edits);
+
+        if let (Source::Text { .. }, false) = (source, dry_run) {
+            // Like a filter, stdin and inline SQL are echoed back fixed, changed or not.
+            if let Some(reporter) = reporter.as_mut() {
+                reporter.file(FileEdits {
+                    path: file.to_string(),
+                    edits,
+                })?;
+            } else {
+                io::stdout().lock().write_all(new_content.as_bytes())?;
+            }
+            continue;
+        }
 
         if new_content != content {
-            if dry_run {
-                match format {
-                    FixFormat::Summary => {
-                        println!("{}", messages.would_fix(file).yellow());
-                        print_summary_diff(&content, &new_content);
-                    }
-                    FixFormat::Diff => {
-                        print_unified_diff(file, &content, &new_content);
-                    }
-                }
-            } else {
+            changed += 1;
+            if !dry_run {
                 fs::write(file, &new_content)
                     .with_context(|| format!("Failed to write: {}", file))?;
+            }
+            if let Some(reporter) = reporter.as_mut() {
+                reporter.file(FileEdits {
+                    path: file.to_string(),
+                    edits,
+                })?;
+            } else if !dry_run {
                 println!("{}", messages.fixed(file).green());
+            } else if let FixFormat::Diff = format {
+                print_unified_diff(file, &content, &new_content);
+            } else {
+                println!("{}", messages.would_fix(file).yellow());
+                if !check {
+                    print_summary_diff(&content, &new_content);
+                }
             }
         }
     }
 
-    Ok(())
+    if let Some(reporter) = reporter {
+        reporter.finish()?;
+    } else if check && changed > 0 {
+        println!("{}", messages.would_fix_count(changed).red());
+    }
+
+    Ok(changed)
+}
+
+/// Reformat files and directories in place with [`format_sql`]. Stdin and inline SQL
+/// are written to stdout. With `dry_run`, a diff of every file that would change is
+/// printed instead.
+///
+/// Returns the number of files that could not be formatted.
+pub fn format(
+    inputs: &Inputs,
+    resolver: &Resolver,
+    jobs: usize,
+    dry_run: bool,
+    messages: &Messages,
+) -> Result<usize> {
+    let sources = collect_sources(inputs, resolver)?;
+    if sources.len() > 1 && sources.iter().any(|s| matches!(s, Source::Text { .. })) {
+        // Formatted stdin or inline SQL is written to stdout, which would interleave with the rest.
+        anyhow::bail!("stdin or --sql input cannot be formatted together with other files");
+    }
+    if sources.is_empty() {
+        eprintln!("{}", "No SQL files found".yellow());
+        return Ok(0);
+    }
+
+    let results = process(
+        &sources,
+        resolver,
+        jobs,
+        None,
+        messages,
+        |content, settings| {
+            let dialect = get_dialect(&settings.dialect)?;
+            // A file that cannot be formatted is reported without stopping the others.
+            Ok(format_sql(
+                content,
+                dialect.as_ref(),
+                &settings.format,
+                &settings.lint,
+            ))
+        },
+    )?;
+
+    let mut failures = 0;
+    for (source, (content, formatted)) in sources.iter().zip(results) {
+        let file = source.name();
+        let formatted = match formatted {
+            Ok(formatted) => formatted,
+            Err(err) => {
+                failures += 1;
+                eprintln!("{}", format!("✗ {} - {:#}", file, err).red());
+                continue;
+            }
+        };
+
+        if let (Source::Text { .. }, false) = (source, dry_run) {
+            io::stdout().lock().write_all(formatted.as_bytes())?;
+        } else if formatted != content {
+            if dry_run {
+                print_unified_diff(file, &content, &formatted);
+            } else {
+                fs::write(file, &formatted)
+                    .with_context(|| format!("Failed to write: {}", file))?;
+                println!("{}", messages.formatted(file).green());
+            }
+        }
+    }
+
+    Ok(failures)
 }
 
 /// Build a mapping from (line, column) to byte offset in the source string.
 /// Both line and column are 1-based (matching sqlparser's Location).
-fn build_line_offsets(src: &str) -> Vec<usize> {
+pub(crate) fn build_line_offsets(src: &str) -> Vec<usize> {
     let mut offsets = vec![0]; // offsets[0] = byte offset of line 1
     for (i, b) in src.bytes().enumerate() {
         if b == b'\n' {
@@ -226,7 +764,12 @@ fn build_line_offsets(src: &str) -> Vec<usize> {
     offsets
 }
 
-fn location_to_byte_offset(src: &str, line_offsets: &[usize], line: u64, column: u64) -> usize {
+pub(crate) fn location_to_byte_offset(
+    src: &str,
+    line_offsets: &[usize],
+    line: u64,
+    column: u64,
+) -> usize {
     let line_idx = (line as usize).saturating_sub(1);
     // sqlparser's Location::column counts characters (1-based), not bytes. Adding it
     // directly to a byte offset corrupts the position whenever a line contains
@@ -247,63 +790,117 @@ fn location_to_byte_offset(src: &str, line_offsets: &[usize], line: u64, column:
     line_start + byte_in_line
 }
 
-/// Fix SQL content using token-based partial replacement.
-/// Only modifies keyword case and trailing semicolons, preserving all original formatting.
-fn fix_content(content: &str, dialect: &dyn Dialect, keyword_case: KeywordCase) -> Result<String> {
-    let mut result = content.to_string();
+/// A single text replacement made by the fixer.
+///
+/// `start..end` is the replaced byte range of the original content. Positions are
+/// 1-based with an exclusive end, matching [`LintError`](crate::LintError).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Edit {
+    /// Lint rule the edit fixes (one of the case rules or `trailing-semicolon`).
+    pub rule: String,
+    #[serde(skip)]
+    pub start: usize,
+    #[serde(skip)]
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub original: String,
+    pub replacement: String,
+}
 
-    // 1. Fix keyword case using tokenizer (preserves original whitespace/indentation).
-    // KeywordCase::Ignore leaves keyword casing untouched.
-    if keyword_case != KeywordCase::Ignore {
-        let mut tokenizer = Tokenizer::new(dialect, content);
-        match tokenizer.tokenize_with_location() {
-            Ok(tokens) => {
-                let line_offsets = build_line_offsets(content);
-
-                // Collect replacements: (byte_offset, original_len, replacement)
-                let mut replacements: Vec<(usize, usize, String)> = Vec::new();
-
-                for token_with_span in &tokens {
-                    if let Token::Word(word) = &token_with_span.token {
-                        if word.quote_style.is_none() && is_sql_keyword(&word.value) {
-                            let target = match keyword_case {
-                                KeywordCase::Lower => word.value.to_lowercase(),
-                                // Upper is the default; Ignore is handled above.
-                                _ => word.value.to_uppercase(),
-                            };
-                            if word.value != target {
-                                let offset = location_to_byte_offset(
-                                    content,
-                                    &line_offsets,
-                                    token_with_span.span.start.line,
-                                    token_with_span.span.start.column,
-                                );
-                                replacements.push((offset, word.value.len(), target));
-                            }
-                        }
-                    }
-                }
+impl Edit {
+    fn new(content: &str, rule: &str, start: usize, end: usize, replacement: String) -> Self {
+        let (line, column) = byte_offset_to_location(content, start);
+        let (end_line, end_column) = byte_offset_to_location(content, end);
+        Self {
+            rule: rule.to_string(),
+            start,
+            end,
+            line,
+            column,
+            end_line,
+            end_column,
+            original: content[start..end].to_string(),
+            replacement,
+        }
+    }
+}
 
-                // Apply replacements in reverse order to preserve byte offsets
-                for (offset, len, replacement) in replacements.into_iter().rev() {
-                    if offset + len <= result.len() {
-                        result.replace_range(offset..offset + len, &replacement);
-                    }
-                }
-            }
-            Err(_) => {
-                // Tokenization failed; skip keyword fix for this file
+/// Convert a byte offset into a 1-based (line, column) pair, counting columns in
+/// characters like sqlparser's `Location`.
+fn byte_offset_to_location(src: &str, offset: usize) -> (usize, usize) {
+    let before = &src[..offset];
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line = before.matches('\n').count() + 1;
+    let column = before[line_start..].chars().count() + 1;
+    (line, column)
+}
+
+/// Compute the edits [`fix_content`] would make, in source order. Only the rules
+/// `config` enables are fixed.
+pub fn fix_edits(content: &str, dialect: &dyn Dialect, config: &LintConfig) -> Vec<Edit> {
+    let mut edits = Vec::new();
+
+    // 1. Fix the case of keywords, function names, data types and identifiers using
+    // the tokenizer (preserves original whitespace/indentation).
+    if let Ok(tokens) = Tokenizer::new(dialect, content).tokenize_with_location() {
+        let line_offsets = build_line_offsets(content);
+        for word in miscased(&tokens, dialect, config) {
+            let span = tokens[word.index].span;
+            let offset =
+                location_to_byte_offset(content, &line_offsets, span.start.line, span.start.column);
+            let end =
+                location_to_byte_offset(content, &line_offsets, span.end.line, span.end.column);
+            if offset < end && end <= content.len() {
+                edits.push(Edit::new(
+                    content,
+                    word.role.rule(),
+                    offset,
+                    end,
+                    word.expected,
+                ));
             }
         }
     }
 
     // 2. Fix trailing semicolon
-    let trimmed = result.trim_end();
-    if !trimmed.is_empty() && !trimmed.ends_with(';') {
-        result = trimmed.to_string() + ";\n";
+    let trimmed = content.trim_end();
+    if config.trailing_semicolon && !trimmed.is_empty() && !trimmed.ends_with(';') {
+        edits.push(Edit::new(
+            content,
+            "trailing-semicolon",
+            trimmed.len(),
+            content.len(),
+            ";\n".to_string(),
+        ));
+    }
+
+    // Leave code alone where an inline comment suppresses the rule behind the edit.
+    if let Ok(tokens) = Tokenizer::new(dialect, content).tokenize_with_location() {
+        let mut suppressions = Suppressions::parse(&tokens);
+        edits.retain(|e| !suppressions.is_suppressed(&e.rule, e.line, e.column));
+    }
+
+    edits
+}
+
+/// Apply non-overlapping edits (as returned by [`fix_edits`]) to `content`.
+pub fn apply_edits(content: &str, edits: &[Edit]) -> String {
+    let mut result = content.to_string();
+    // Apply replacements in reverse order to preserve byte offsets
+    for edit in edits.iter().rev() {
+        result.replace_range(edit.start..edit.end, &edit.replacement);
     }
+    result
+}
 
-    Ok(result)
+/// Fix SQL content using token-based partial replacement.
+/// Only modifies the case of words and trailing semicolons, as far as `config` enables
+/// those rules, preserving all original formatting.
+pub fn fix_content(content: &str, dialect: &dyn Dialect, config: &LintConfig) -> Result<String> {
+    Ok(apply_edits(content, &fix_edits(content, dialect, config)))
 }
 
 fn print_summary_diff(old: &str, new: &str) {
@@ -349,72 +946,172 @@ fn print_unified_diff(file: &str, old: &str, new: &str) {
     }
 }
 
-pub fn lint(
-    paths: &[String],
-    dialect_name: &str,
-    keyword_case: &str,
-    no_select_star: bool,
-    require_alias: bool,
+/// Text report for one linted file.
+pub(crate) fn render_lint_warnings(
+    file: &str,
+    errors: &[LintError],
     messages: &Messages,
-) -> Result<()> {
-    let dialect = get_dialect(dialect_name)?;
-    let files = collect_sql_files(paths);
+) -> String {
+    if errors.is_empty() {
+        return format!("{}\n", messages.file_ok(file).green());
+    }
 
-    if files.is_empty() {
-        eprintln!("{}", "No SQL files found".yellow());
-        return Ok(());
+    let mut out = format!(
+        "{}\n",
+        format!("⚠ {} - {} warning(s)", file, errors.len()).yellow()
+    );
+    for error in errors {
+        out += &format!(
+            "{}\n",
+            messages.lint_warning(&error.rule, error.line, error.column, &error.message)
+        );
     }
+    out
+}
 
-    let kw_case = parse_keyword_case(keyword_case);
+/// Lint files and directories for style issues, printing a report to stdout. Findings
+/// recorded in `baseline` are left out.
+///
+/// Returns the total number of warnings found.
+pub fn lint(
+    inputs: &Inputs,
+    resolver: &Resolver,
+    jobs: usize,
+    cache: Option<&Cache>,
+    baseline: Option<&Baseline>,
+    format: OutputFormat,
+    messages: &Messages,
+) -> Result<usize> {
+    let sources = collect_sources(inputs, resolver)?;
 
-    let config = LintConfig {
-        keyword_case: kw_case,
-        no_select_star,
-        require_table_alias: require_alias,
-        trailing_semicolon: true,
-    };
+    if sources.is_empty() {
+        eprintln!("{}", "No SQL files found".yellow());
+        // Machine-readable formats still emit an (empty) report.
+        if format == OutputFormat::Text {
+            return Ok(0);
+        }
+    }
 
-    let linter = Linter::new(config);
+    let mut reporter = (format != OutputFormat::Text)
+        .then(|| DiagnosticReporter::new(format, "lint", messages, io::stdout().lock()));
     let mut total_warnings = 0;
 
-    for file in &files {
-        let content =
-            fs::read_to_string(file).with_context(|| format!("Failed to read: {}", file))?;
-
-        let errors = linter.lint(&content, dialect.as_ref(), messages);
-
-        if errors.is_empty() {
-            println!("{}", messages.file_ok(file).green());
+    let results = process(
+        &sources,
+        resolver,
+        jobs,
+        cache,
+        messages,
+        |content, settings| {
+            let dialect = get_dialect(&settings.dialect)?;
+            let errors = lint_cached(cache, content, settings, dialect.as_ref(), messages)?;
+            // Machine-readable reports attach the fixer's edits to the findings they resolve.
+            let edits = if format == OutputFormat::Text {
+                Vec::new()
+            } else {
+                fix_edits(content, dialect.as_ref(), &settings.lint)
+            };
+            Ok((errors, edits))
+        },
+    )?;
+
+    for (source, (content, (mut errors, mut edits))) in sources.iter().zip(results) {
+        let file = source.name();
+        if let Some(baseline) = baseline {
+            errors = baseline.filter(file, &content, errors);
+        }
+        if let (Some(changes), Source::File(path)) = (&inputs.changes, source) {
+            errors.retain(|error| changes.touches(Path::new(path), error.line, error.end_line));
+        }
+        total_warnings += errors.len();
+
+        if let Some(reporter) = reporter.as_mut() {
+            let diagnostics = errors
+                .iter()
+                .map(|error| {
+                    let fix = edits
+                        .iter()
+                        .position(|edit| {
+                            edit.rule == error.rule
+                                && (edit.rule == "trailing-semicolon"
+                                    || (edit.line, edit.column) == (error.line, error.column))
+                        })
+                        .map(|i| edits.swap_remove(i));
+                    Diagnostic::lint(file, error).with_fix(fix)
+                })
+                .collect();
+            reporter.file(FileReport {
+                path: file.to_string(),
+                diagnostics,
+            })?;
         } else {
-            println!(
-                "{}",
-                format!("⚠ {} - {} warning(s)", file, errors.len()).yellow()
-            );
-            for error in &errors {
-                println!(
-                    "{}",
-                    messages.lint_warning(&error.rule, error.line, error.column, &error.message)
-                );
-            }
-            total_warnings += errors.len();
+            print!("{}", render_lint_warnings(file, &errors, messages));
         }
     }
 
-    println!("{}", messages.lint_summary(files.len(), total_warnings));
-
-    if total_warnings > 0 {
-        std::process::exit(1);
+    match reporter {
+        Some(reporter) => reporter.finish()?,
+        None => println!("{}", messages.lint_summary(sources.len(), total_warnings)),
     }
 
-    Ok(())
+    Ok(total_warnings)
+}
+
+/// Lint files and directories and record every finding in a baseline file at `path`.
+///
+/// Returns the number of warnings recorded.
+pub fn write_baseline(
+    inputs: &Inputs,
+    resolver: &Resolver,
+    jobs: usize,
+    cache: Option<&Cache>,
+    path: &Path,
+    messages: &Messages,
+) -> Result<usize> {
+    let sources = collect_sources(inputs, resolver)?;
+    let findings = process(
+        &sources,
+        resolver,
+        jobs,
+        cache,
+        messages,
+        |content, settings| {
+            let dialect = get_dialect(&settings.dialect)?;
+            lint_cached(cache, content, settings, dialect.as_ref(), messages)
+        },
+    )?;
+
+    let baseline =
+        Baseline::from_findings(sources.iter().zip(&findings).map(
+            |(source, (content, errors))| (source.name(), content.as_str(), errors.as_slice()),
+        ));
+    baseline.save(path)?;
+
+    let total: usize = findings.iter().map(|(_, errors)| errors.len()).sum();
+    println!(
+        "{}",
+        messages
+            .baseline_written(&path.display().to_string(), total)
+            .green()
+    );
+    Ok(total)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::Overrides;
+    use crate::linter::KeywordCase;
     use std::fs;
     use tempfile::TempDir;
 
+    fn case(keyword_case: KeywordCase) -> LintConfig {
+        LintConfig {
+            keyword_case,
+            ..Default::default()
+        }
+    }
+
     #[test]
     fn test_get_dialect_known() {
         for name in [
@@ -434,6 +1131,19 @@ mod tests {
         assert!(get_dialect("MySQL").is_ok());
     }
 
+    #[test]
+    fn test_get_dialect_aliases() {
+        for name in DIALECTS {
+            assert!(
+                get_dialect(name).is_ok(),
+                "dialect {name} should be supported"
+            );
+        }
+        for alias in ["tsql", "sqlserver"] {
+            assert!(get_dialect(alias).unwrap().is::<MsSqlDialect>());
+        }
+    }
+
     #[test]
     fn test_get_dialect_unknown_errors() {
         let err = get_dialect("oracle").unwrap_err();
@@ -473,6 +1183,60 @@ mod tests {
         assert!(!errors[0].message.is_empty());
     }
 
+    #[test]
+    fn test_check_sql_reports_every_broken_statement() {
+        let d = GenericDialect {};
+        let sql =
+            "SELECT 1;\nSELECT FROM;\nSELECT 2;\nINSERT INTO VALUES (1);\nSELECT (1;\nSELECT 3;";
+        let errors = check_sql(sql, &d);
+        let positions: Vec<_> = errors.iter().map(|e| (e.line, e.column)).collect();
+        assert_eq!(positions, vec![(2, 12), (4, 21), (5, 10)]);
+    }
+
+    #[test]
+    fn test_check_sql_does_not_split_inside_strings_or_comments() {
+        let d = get_dialect("postgres").unwrap();
+        let sql = "SELECT 'a;b' -- c;d\nFROM t;\nSELECT $$x;y$$;\nSELECT FROM;";
+        let errors = check_sql(sql, d.as_ref());
+        assert_eq!(errors.len(), 1);
+        assert_eq!((errors[0].line, errors[0].column), (4, 12));
+    }
+
+    #[test]
+    fn test_check_sql_keeps_statements_that_only_parse_in_context() {
+        let d = get_dialect("mssql").unwrap();
+        let body = "CREATE PROCEDURE p AS BEGIN SELECT 1; SELECT 2; END;";
+        let sql = format!("{body}\nSELECT FROM;\n{body}\nSELECT (1;");
+        let errors = check_sql(&sql, d.as_ref());
+        let positions: Vec<_> = errors.iter().map(|e| (e.line, e.column)).collect();
+        assert_eq!(positions, vec![(2, 12), (4, 10)]);
+    }
+
+    #[test]
+    fn test_check_sql_locates_errors_at_end_of_input() {
+        let d = GenericDialect {};
+        let errors = check_sql("SELECT 1;\nSELECT id FROM users WHERE", &d);
+        assert_eq!(errors.len(), 1);
+        assert_eq!((errors[0].line, errors[0].column), (2, 27));
+        assert!(errors[0].message.contains("EOF"));
+    }
+
+    #[test]
+    fn test_check_sql_error_end_covers_offending_token() {
+        let d = GenericDialect {};
+        let errors = check_sql("SELECT * FROM t WHERE id = = 1;", &d);
+        assert_eq!(errors.len(), 1);
+        let e = &errors[0];
+        assert_eq!((e.line, e.column, e.end_line, e.end_column), (1, 28, 1, 29));
+    }
+
+    #[test]
+    fn test_check_sql_tokenizer_error_reports_single_error() {
+        let d = GenericDialect {};
+        let errors = check_sql("SELECT 1;\nSELECT 'unterminated", &d);
+        assert_eq!(errors.len(), 1);
+    }
+
     #[test]
     fn test_build_line_offsets() {
         // "a\nbb\nccc" → line starts at byte 0, 2, 5.
@@ -512,10 +1276,115 @@ mod tests {
         assert_eq!(location_to_byte_offset(src, &offsets, 1, 4), 7);
     }
 
+    #[test]
+    fn test_byte_offset_to_location_counts_characters() {
+        let src = "ab\n日本 x";
+        assert_eq!(byte_offset_to_location(src, 0), (1, 1));
+        assert_eq!(byte_offset_to_location(src, 3), (2, 1));
+        // "日本 " is 7 bytes but 3 characters.
+        assert_eq!(byte_offset_to_location(src, 10), (2, 4));
+    }
+
+    #[test]
+    fn test_fix_edits_describe_each_change() {
+        let d = GenericDialect {};
+        let edits = fix_edits("select id\nfrom t  \n", &d, &case(KeywordCase::Upper));
+        let summary: Vec<_> = edits
+            .iter()
+            .map(|e| (e.rule.as_str(), e.line, e.column, e.replacement.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("keyword-case", 1, 1, "SELECT"),
+                ("keyword-case", 2, 1, "FROM"),
+                ("trailing-semicolon", 2, 7, ";\n"),
+            ]
+        );
+        assert_eq!(edits[1].original, "from");
+        assert_eq!((edits[1].end_line, edits[1].end_column), (2, 5));
+    }
+
+    #[test]
+    fn test_fix_edits_skip_disabled_rules() {
+        let d = GenericDialect {};
+        let src = "select count(id) from t where x = cast(1 as int)";
+        // Types follow keywords unless set, so set them to disable each on its own.
+        let all = LintConfig {
+            function_case: KeywordCase::Upper,
+            type_case: Some(KeywordCase::Upper),
+            identifier_case: KeywordCase::Upper,
+            ..Default::default()
+        };
+        let rules = |config: &LintConfig| -> Vec<String> {
+            let mut rules: Vec<_> = fix_edits(src, &d, config)
+                .into_iter()
+                .map(|e| e.rule)
+                .collect();
+            rules.sort();
+            rules.dedup();
+            rules
+        };
+        assert_eq!(
+            rules(&all),
+            vec![
+                "function-case",
+                "identifier-case",
+                "keyword-case",
+                "trailing-semicolon",
+                "type-case"
+            ]
+        );
+
+        let without = |disable: fn(&mut LintConfig)| {
+            let mut config = all.clone();
+            disable(&mut config);
+            config
+        };
+        let disabled = [
+            (
+                "keyword-case",
+                without(|c| c.keyword_case = KeywordCase::Ignore),
+            ),
+            (
+                "function-case",
+                without(|c| c.function_case = KeywordCase::Ignore),
+            ),
+            (
+                "type-case",
+                without(|c| c.type_case = Some(KeywordCase::Ignore)),
+            ),
+            (
+                "identifier-case",
+                without(|c| c.identifier_case = KeywordCase::Ignore),
+            ),
+            (
+                "trailing-semicolon",
+                without(|c| c.trailing_semicolon = false),
+            ),
+        ];
+        for (rule, config) in disabled {
+            let mut expected = rules(&all);
+            expected.retain(|r| r != rule);
+            assert_eq!(rules(&config), expected, "{rule} disabled");
+        }
+    }
+
+    #[test]
+    fn test_apply_edits_matches_fix_content() {
+        let d = GenericDialect {};
+        let src = "select '日本' as x from t";
+        let edits = fix_edits(src, &d, &case(KeywordCase::Upper));
+        assert_eq!(
+            apply_edits(src, &edits),
+            fix_content(src, &d, &case(KeywordCase::Upper)).unwrap()
+        );
+    }
+
     #[test]
     fn test_fix_content_uppercases_keywords() {
         let d = GenericDialect {};
-        let out = fix_content("select id from users;", &d, KeywordCase::Upper).unwrap();
+        let out = fix_content("select id from users;", &d, &case(KeywordCase::Upper)).unwrap();
         assert!(out.contains("SELECT"));
         assert!(out.contains("FROM"));
         // Identifiers are preserved as-is.
@@ -526,7 +1395,7 @@ mod tests {
     #[test]
     fn test_fix_content_lowercases_keywords() {
         let d = GenericDialect {};
-        let out = fix_content("SELECT ID FROM USERS;", &d, KeywordCase::Lower).unwrap();
+        let out = fix_content("SELECT ID FROM USERS;", &d, &case(KeywordCase::Lower)).unwrap();
         assert!(out.contains("select"));
         assert!(out.contains("from"));
         // Identifiers (non-keywords) keep their original casing.
@@ -538,7 +1407,7 @@ mod tests {
     fn test_fix_content_ignore_leaves_keyword_case_untouched() {
         let d = GenericDialect {};
         // Ignore must not change casing, but still appends the trailing semicolon.
-        let out = fix_content("select Id from Users", &d, KeywordCase::Ignore).unwrap();
+        let out = fix_content("select Id from Users", &d, &case(KeywordCase::Ignore)).unwrap();
         assert!(out.contains("select Id from Users"));
         assert!(out.trim_end().ends_with(';'));
     }
@@ -548,7 +1417,7 @@ mod tests {
         let d = GenericDialect {};
         // An unterminated string literal makes the tokenizer fail. Keyword casing
         // is then left untouched, but the trailing semicolon is still appended.
-        let out = fix_content("select 'unterminated", &d, KeywordCase::Upper).unwrap();
+        let out = fix_content("select 'unterminated", &d, &case(KeywordCase::Upper)).unwrap();
         assert!(out.contains("select 'unterminated"));
         assert!(!out.contains("SELECT"));
         assert!(out.trim_end().ends_with(';'));
@@ -557,14 +1426,14 @@ mod tests {
     #[test]
     fn test_fix_content_adds_trailing_semicolon() {
         let d = GenericDialect {};
-        let out = fix_content("SELECT 1", &d, KeywordCase::Upper).unwrap();
+        let out = fix_content("SELECT 1", &d, &case(KeywordCase::Upper)).unwrap();
         assert!(out.trim_end().ends_with(';'));
     }
 
     #[test]
     fn test_fix_content_preserves_existing_semicolon() {
         let d = GenericDialect {};
-        let out = fix_content("SELECT 1;\n", &d, KeywordCase::Upper).unwrap();
+        let out = fix_content("SELECT 1;\n", &d, &case(KeywordCase::Upper)).unwrap();
         // Already terminated → no extra semicolon appended.
         assert_eq!(out.matches(';').count(), 1);
     }
@@ -577,7 +1446,7 @@ mod tests {
         let out = fix_content(
             "select '日本語テスト' as label from users",
             &d,
-            KeywordCase::Upper,
+            &case(KeywordCase::Upper),
         )
         .unwrap();
         // Keywords are uppercased and the multibyte literal is preserved intact.
@@ -591,10 +1460,18 @@ mod tests {
     fn test_fix_content_preserves_whitespace_and_quoted_identifiers() {
         let d = GenericDialect {};
         // Double whitespace between tokens must be preserved (token-based replacement).
-        let out = fix_content("select  id  from  users;", &d, KeywordCase::Upper).unwrap();
+        let out = fix_content("select  id  from  users;", &d, &case(KeywordCase::Upper)).unwrap();
         assert!(out.contains("SELECT  id  FROM  users"));
     }
 
+    fn collect(paths: &[&Path]) -> Vec<String> {
+        let paths: Vec<String> = paths
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+        collect_sql_files(&paths, &Resolver::new(Overrides::default())).unwrap()
+    }
+
     #[test]
     fn test_collect_sql_files_filters_extension() {
         let dir = TempDir::new().unwrap();
@@ -604,7 +1481,7 @@ mod tests {
         fs::write(&txt, "not sql").unwrap();
 
         // Directory traversal picks up only the .sql file.
-        let files = collect_sql_files(&[dir.path().to_string_lossy().to_string()]);
+        let files = collect(&[dir.path()]);
         assert_eq!(files.len(), 1);
         assert!(files[0].ends_with("a.sql"));
     }
@@ -614,7 +1491,7 @@ mod tests {
         let dir = TempDir::new().unwrap();
         let sql = dir.path().join("q.sql");
         fs::write(&sql, "SELECT 1;").unwrap();
-        let files = collect_sql_files(&[sql.to_string_lossy().to_string()]);
+        let files = collect(&[&sql]);
         assert_eq!(files.len(), 1);
     }
 
@@ -623,10 +1500,23 @@ mod tests {
         let dir = TempDir::new().unwrap();
         let txt = dir.path().join("q.txt");
         fs::write(&txt, "nope").unwrap();
-        let files = collect_sql_files(&[txt.to_string_lossy().to_string()]);
+        let files = collect(&[&txt]);
         assert!(files.is_empty());
     }
 
+    #[test]
+    fn test_collect_sql_files_applies_exclude_during_traversal() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("vendor")).unwrap();
+        fs::write(dir.path().join(".sqlex.toml"), "exclude = [\"vendor/**\"]").unwrap();
+        fs::write(dir.path().join("a.sql"), "SELECT 1;").unwrap();
+        fs::write(dir.path().join("vendor").join("b.sql"), "SELECT 2;").unwrap();
+
+        let files = collect(&[dir.path()]);
+        assert_eq!(files.len(), 1);
+        assert!(files[0].ends_with("a.sql"));
+    }
+
     #[test]
     fn test_dialect_specific_parsing() {
         // BigQuery accepts backtick-quoted identifiers.
@@ -636,5 +1526,11 @@ mod tests {
         // PostgreSQL accepts double-quoted identifiers.
         let pg = get_dialect("postgres").unwrap();
         assert!(check_sql("SELECT \"col\" FROM \"t\";", pg.as_ref()).is_empty());
+
+        // T-SQL variables are only understood by SQL Server.
+        let sql = "DECLARE @x INT = 1;";
+        let mssql = get_dialect("mssql").unwrap();
+        assert!(check_sql(sql, mssql.as_ref()).is_empty());
+        assert!(!check_sql(sql, get_dialect("generic").unwrap().as_ref()).is_empty());
     }
 }
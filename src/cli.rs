@@ -1,13 +1,17 @@
-use clap::{Parser, Subcommand, ValueEnum};
-
-#[derive(Debug, Clone, Copy, Default, ValueEnum)]
-pub enum FixFormat {
-    /// Summary of changes (default)
-    #[default]
-    Summary,
-    /// Unified diff format
-    Diff,
-}
+use crate::baseline::Baseline;
+use crate::cache::{Cache, CACHE_DIR_NAME};
+use crate::checker::{self, FixFormat, Inputs};
+use crate::config::{Overrides, Resolver};
+use crate::git::Changes;
+use crate::i18n;
+use crate::lsp;
+use crate::portability;
+use crate::report::OutputFormat;
+use crate::transpile;
+use crate::watch::{self, Task};
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use std::path::Path;
 
 #[derive(Parser)]
 #[command(name = "sqlex")]
@@ -26,58 +30,578 @@ pub struct Cli {
 pub enum Command {
     /// Check SQL files for syntax errors
     Check {
-        /// Files or directories to check
-        #[arg(required = true)]
+        /// Files or directories to check ("-" reads SQL from stdin)
+        #[arg(required_unless_present = "sql")]
         paths: Vec<String>,
 
-        /// SQL dialect (generic, mysql, postgres, sqlite, bigquery)
-        #[arg(short, long, default_value = "generic")]
-        dialect: String,
+        /// Inline SQL text to check
+        #[arg(long, allow_hyphen_values = true)]
+        sql: Option<String>,
+
+        /// File name to report for stdin or --sql input, also used for config lookup
+        #[arg(long)]
+        stdin_filename: Option<String>,
+
+        /// SQL dialect (generic, ansi, mysql, postgres, sqlite, bigquery, mssql, snowflake,
+        /// redshift, clickhouse, duckdb, hive, databricks), or auto to detect it per file
+        /// [default: generic]
+        #[arg(short, long)]
+        dialect: Option<String>,
+
+        /// Database version to check against, e.g. mysql@5.7: sets the dialect and reports
+        /// constructs that version does not support yet
+        #[arg(long, value_name = "DIALECT@VERSION", value_parser = parse_target, conflicts_with = "dialect")]
+        target: Option<(String, String)>,
+
+        /// Check each statement against every one of these comma-separated dialects and
+        /// report those only some of them accept
+        #[arg(long, value_delimiter = ',', value_name = "DIALECTS", conflicts_with_all = ["dialect", "target", "watch"])]
+        dialects: Vec<String>,
+
+        /// Number of files to process in parallel [default: one per CPU core]
+        #[arg(short, long, default_value_t = 0, hide_default_value = true)]
+        jobs: usize,
+
+        /// Re-check every file instead of reusing results cached in .sqlex_cache
+        #[arg(long)]
+        no_cache: bool,
+
+        /// Only check files changed since the merge base with this git revision
+        #[arg(long, value_name = "REV", conflicts_with = "watch")]
+        diff_base: Option<String>,
+
+        /// Output format (text, json, ndjson, sarif, github, junit, checkstyle)
+        #[arg(short, long, default_value = "text")]
+        format: OutputFormat,
+
+        /// Keep running and re-check files as they change
+        #[arg(short, long)]
+        watch: bool,
     },
 
     /// Fix SQL files automatically
     Fix {
-        /// Files or directories to fix
-        #[arg(required = true)]
+        /// Files or directories to fix ("-" reads SQL from stdin)
+        #[arg(required_unless_present = "sql")]
         paths: Vec<String>,
 
-        /// SQL dialect (generic, mysql, postgres, sqlite, bigquery)
-        #[arg(short, long, default_value = "generic")]
-        dialect: String,
+        /// Inline SQL text to fix; the fixed SQL is written to stdout (as is stdin input)
+        #[arg(long, allow_hyphen_values = true)]
+        sql: Option<String>,
 
-        /// Keyword case style to apply (upper, lower, ignore)
-        #[arg(long, default_value = "upper")]
-        keyword_case: String,
+        /// File name to report for stdin or --sql input, also used for config lookup
+        #[arg(long)]
+        stdin_filename: Option<String>,
+
+        /// SQL dialect (generic, ansi, mysql, postgres, sqlite, bigquery, mssql, snowflake,
+        /// redshift, clickhouse, duckdb, hive, databricks), or auto to detect it per file
+        /// [default: generic]
+        #[arg(short, long)]
+        dialect: Option<String>,
+
+        /// Number of files to process in parallel [default: one per CPU core]
+        #[arg(short, long, default_value_t = 0, hide_default_value = true)]
+        jobs: usize,
+
+        /// Keyword case style to apply (upper, lower, capitalize, consistent, ignore) [default: upper]
+        #[arg(long)]
+        keyword_case: Option<String>,
+
+        /// Function name case style (upper, lower, capitalize, consistent, ignore) [default: ignore]
+        #[arg(long)]
+        function_case: Option<String>,
+
+        /// Data type case style (upper, lower, capitalize, consistent, ignore) [default: the keyword case]
+        #[arg(long)]
+        type_case: Option<String>,
+
+        /// Unquoted identifier case style (upper, lower, capitalize, consistent, ignore) [default: ignore]
+        #[arg(long)]
+        identifier_case: Option<String>,
 
         /// Show what would be changed without modifying files
         #[arg(long)]
         dry_run: bool,
 
-        /// Output format for dry-run (summary, diff)
+        /// Exit with an error if any file is not already fixed, without modifying files
+        #[arg(long)]
+        check: bool,
+
+        /// Output format (summary, diff, json, ndjson); summary and diff apply to dry-run and check
         #[arg(short, long, default_value = "summary")]
         format: FixFormat,
     },
 
+    /// Reformat SQL files into a canonical layout
+    Format {
+        /// Files or directories to format ("-" reads SQL from stdin)
+        #[arg(required_unless_present = "sql")]
+        paths: Vec<String>,
+
+        /// Inline SQL text to format; the formatted SQL is written to stdout (as is stdin input)
+        #[arg(long, allow_hyphen_values = true)]
+        sql: Option<String>,
+
+        /// File name to report for stdin or --sql input, also used for config lookup
+        #[arg(long)]
+        stdin_filename: Option<String>,
+
+        /// SQL dialect (generic, ansi, mysql, postgres, sqlite, bigquery, mssql, snowflake,
+        /// redshift, clickhouse, duckdb, hive, databricks), or auto to detect it per file
+        /// [default: generic]
+        #[arg(short, long)]
+        dialect: Option<String>,
+
+        /// Number of files to process in parallel [default: one per CPU core]
+        #[arg(short, long, default_value_t = 0, hide_default_value = true)]
+        jobs: usize,
+
+        /// Keyword case style to apply (upper, lower, capitalize, consistent, ignore) [default: upper]
+        #[arg(long)]
+        keyword_case: Option<String>,
+
+        /// Function name case style (upper, lower, capitalize, consistent, ignore) [default: ignore]
+        #[arg(long)]
+        function_case: Option<String>,
+
+        /// Data type case style (upper, lower, capitalize, consistent, ignore) [default: the keyword case]
+        #[arg(long)]
+        type_case: Option<String>,
+
+        /// Unquoted identifier case style (upper, lower, capitalize, consistent, ignore) [default: ignore]
+        #[arg(long)]
+        identifier_case: Option<String>,
+
+        /// Spaces per indentation level [default: 4]
+        #[arg(long)]
+        indent_width: Option<usize>,
+
+        /// Comma placement when items are broken one per line (trailing, leading) [default: trailing]
+        #[arg(long)]
+        comma_style: Option<String>,
+
+        /// Line length beyond which a clause is broken one item per line [default: 80]
+        #[arg(long)]
+        max_line_length: Option<usize>,
+
+        /// Placement of a join's ON (same-line, new-line) [default: same-line]
+        #[arg(long)]
+        join_on: Option<String>,
+
+        /// Show a diff of what would change without modifying files
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Translate SQL files from one dialect to another
+    Transpile {
+        /// Files or directories to translate ("-" reads SQL from stdin)
+        #[arg(required_unless_present = "sql")]
+        paths: Vec<String>,
+
+        /// Inline SQL text to translate
+        #[arg(long, allow_hyphen_values = true)]
+        sql: Option<String>,
+
+        /// File name to report for stdin or --sql input, also used for config lookup
+        #[arg(long)]
+        stdin_filename: Option<String>,
+
+        /// Dialect the SQL is written in (mysql) [default: the configured dialect]
+        #[arg(long, value_name = "DIALECT")]
+        from: Option<String>,
+
+        /// Dialect to translate to (postgres)
+        #[arg(long, value_name = "DIALECT")]
+        to: String,
+
+        /// Write each translated file to the same relative path under this directory
+        /// instead of to stdout
+        #[arg(long, value_name = "DIR")]
+        out_dir: Option<String>,
+
+        /// Number of files to process in parallel [default: one per CPU core]
+        #[arg(short, long, default_value_t = 0, hide_default_value = true)]
+        jobs: usize,
+    },
+
     /// Lint SQL files for style issues
     Lint {
-        /// Files or directories to lint
-        #[arg(required = true)]
+        /// Files or directories to lint ("-" reads SQL from stdin)
+        #[arg(required_unless_present = "sql")]
         paths: Vec<String>,
 
-        /// SQL dialect (generic, mysql, postgres, sqlite, bigquery)
-        #[arg(short, long, default_value = "generic")]
-        dialect: String,
+        /// Inline SQL text to lint
+        #[arg(long, allow_hyphen_values = true)]
+        sql: Option<String>,
+
+        /// File name to report for stdin or --sql input, also used for config lookup
+        #[arg(long)]
+        stdin_filename: Option<String>,
+
+        /// SQL dialect (generic, ansi, mysql, postgres, sqlite, bigquery, mssql, snowflake,
+        /// redshift, clickhouse, duckdb, hive, databricks), or auto to detect it per file
+        /// [default: generic]
+        #[arg(short, long)]
+        dialect: Option<String>,
+
+        /// Number of files to process in parallel [default: one per CPU core]
+        #[arg(short, long, default_value_t = 0, hide_default_value = true)]
+        jobs: usize,
+
+        /// Re-lint every file instead of reusing results cached in .sqlex_cache
+        #[arg(long)]
+        no_cache: bool,
+
+        /// Only lint lines changed since the merge base with this git revision
+        #[arg(long, value_name = "REV", conflicts_with = "watch")]
+        diff_base: Option<String>,
+
+        /// Keyword case style (upper, lower, capitalize, consistent, ignore) [default: upper]
+        #[arg(long)]
+        keyword_case: Option<String>,
+
+        /// Function name case style (upper, lower, capitalize, consistent, ignore) [default: ignore]
+        #[arg(long)]
+        function_case: Option<String>,
+
+        /// Data type case style (upper, lower, capitalize, consistent, ignore) [default: the keyword case]
+        #[arg(long)]
+        type_case: Option<String>,
 
-        /// Keyword case style (upper, lower, ignore)
-        #[arg(long, default_value = "upper")]
-        keyword_case: String,
+        /// Unquoted identifier case style (upper, lower, capitalize, consistent, ignore) [default: ignore]
+        #[arg(long)]
+        identifier_case: Option<String>,
 
-        /// Disallow SELECT *
-        #[arg(long, default_value = "true")]
-        no_select_star: bool,
+        /// Disallow SELECT * (true, false) [default: true]
+        #[arg(long)]
+        no_select_star: Option<bool>,
 
         /// Require table aliases
         #[arg(long)]
         require_alias: bool,
+
+        /// Only report findings not recorded in this baseline file
+        #[arg(long, value_name = "PATH")]
+        baseline: Option<String>,
+
+        /// Record all current findings in a baseline file instead of reporting them
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["baseline", "watch", "diff_base"])]
+        write_baseline: Option<String>,
+
+        /// Output format (text, json, ndjson, sarif, github, junit, checkstyle)
+        #[arg(short, long, default_value = "text")]
+        format: OutputFormat,
+
+        /// Keep running and re-lint files as they change
+        #[arg(short, long)]
+        watch: bool,
+    },
+
+    /// Run a language server over stdio for editor integration
+    Lsp,
+
+    /// Manage the result cache
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
     },
 }
+
+#[derive(Subcommand)]
+pub enum CacheCommand {
+    /// Delete the cache in the current directory
+    Clean,
+}
+
+/// Split a `--target` value such as `mysql@5.7` into dialect and version.
+fn parse_target(value: &str) -> Result<(String, String), String> {
+    match value.split_once('@') {
+        Some((dialect, version)) if !dialect.is_empty() && !version.is_empty() => {
+            Ok((dialect.to_string(), version.to_string()))
+        }
+        _ => Err(format!(
+            "expected DIALECT@VERSION (e.g. mysql@5.7), got '{}'",
+            value
+        )),
+    }
+}
+
+/// Runs the `sqlex` command line on the process arguments.
+pub fn run() -> Result<()> {
+    let cli = Cli::parse();
+
+    // Initialize i18n based on locale or CLI flag
+    let lang = cli.lang.as_deref().unwrap_or_else(|| {
+        if i18n::is_japanese_locale() {
+            "ja"
+        } else {
+            "en"
+        }
+    });
+    let messages = i18n::Messages::new(lang);
+
+    match cli.command {
+        Command::Check {
+            paths,
+            sql,
+            stdin_filename,
+            dialect,
+            target,
+            dialects,
+            jobs,
+            no_cache,
+            diff_base,
+            format,
+            watch,
+        } => {
+            let inputs = Inputs {
+                paths,
+                sql,
+                stdin_filename,
+                changes: diff_base.as_deref().map(Changes::since).transpose()?,
+            };
+            let (dialect, target_version) = match target {
+                Some((dialect, version)) => (Some(dialect), Some(version)),
+                None => (dialect, None),
+            };
+            let overrides = Overrides {
+                dialect,
+                target_version,
+                ..Default::default()
+            };
+            let cache = (!no_cache).then(|| Cache::new(CACHE_DIR_NAME));
+            if watch {
+                ensure_text_output(format)?;
+                return watch::run(&inputs, &overrides, Task::Check, cache.as_ref(), &messages);
+            }
+            if !dialects.is_empty() {
+                if portability::check_portability(
+                    &inputs,
+                    &Resolver::new(overrides),
+                    &dialects,
+                    jobs,
+                    cache.as_ref(),
+                    format,
+                    &messages,
+                )? > 0
+                {
+                    std::process::exit(1);
+                }
+                return Ok(());
+            }
+            if checker::check(
+                &inputs,
+                &Resolver::new(overrides),
+                jobs,
+                cache.as_ref(),
+                format,
+                &messages,
+            )? > 0
+            {
+                std::process::exit(1);
+            }
+        }
+        Command::Fix {
+            paths,
+            sql,
+            stdin_filename,
+            dialect,
+            jobs,
+            keyword_case,
+            function_case,
+            type_case,
+            identifier_case,
+            dry_run,
+            check,
+            format,
+        } => {
+            let inputs = Inputs {
+                paths,
+                sql,
+                stdin_filename,
+                ..Default::default()
+            };
+            let overrides = Overrides {
+                dialect,
+                keyword_case,
+                function_case,
+                type_case,
+                identifier_case,
+                ..Default::default()
+            };
+            let changed = checker::fix(
+                &inputs,
+                &Resolver::new(overrides),
+                jobs,
+                dry_run,
+                check,
+                format,
+                &messages,
+            )?;
+            if check && changed > 0 {
+                std::process::exit(1);
+            }
+        }
+        Command::Format {
+            paths,
+            sql,
+            stdin_filename,
+            dialect,
+            jobs,
+            keyword_case,
+            function_case,
+            type_case,
+            identifier_case,
+            indent_width,
+            comma_style,
+            max_line_length,
+            join_on,
+            dry_run,
+        } => {
+            let inputs = Inputs {
+                paths,
+                sql,
+                stdin_filename,
+                ..Default::default()
+            };
+            let overrides = Overrides {
+                dialect,
+                keyword_case,
+                function_case,
+                type_case,
+                identifier_case,
+                indent_width,
+                comma_style,
+                max_line_length,
+                join_on,
+                ..Default::default()
+            };
+            if checker::format(&inputs, &Resolver::new(overrides), jobs, dry_run, &messages)? > 0 {
+                std::process::exit(1);
+            }
+        }
+        Command::Transpile {
+            paths,
+            sql,
+            stdin_filename,
+            from,
+            to,
+            out_dir,
+            jobs,
+        } => {
+            let inputs = Inputs {
+                paths,
+                sql,
+                stdin_filename,
+                ..Default::default()
+            };
+            let overrides = Overrides {
+                dialect: from,
+                ..Default::default()
+            };
+            if transpile::transpile(
+                &inputs,
+                &Resolver::new(overrides),
+                &to,
+                out_dir.as_deref().map(Path::new),
+                jobs,
+                &messages,
+            )? > 0
+            {
+                std::process::exit(1);
+            }
+        }
+        Command::Lint {
+            paths,
+            sql,
+            stdin_filename,
+            dialect,
+            jobs,
+            no_cache,
+            diff_base,
+            keyword_case,
+            function_case,
+            type_case,
+            identifier_case,
+            no_select_star,
+            require_alias,
+            baseline,
+            write_baseline,
+            format,
+            watch,
+        } => {
+            let inputs = Inputs {
+                paths,
+                sql,
+                stdin_filename,
+                changes: diff_base.as_deref().map(Changes::since).transpose()?,
+            };
+            let overrides = Overrides {
+                dialect,
+                keyword_case,
+                function_case,
+                type_case,
+                identifier_case,
+                no_select_star,
+                // `--require-alias` is a plain flag, so only its presence overrides the config.
+                require_table_alias: require_alias.then_some(true),
+                ..Default::default()
+            };
+            let cache = (!no_cache).then(|| Cache::new(CACHE_DIR_NAME));
+            if let Some(path) = write_baseline {
+                let resolver = Resolver::new(overrides);
+                checker::write_baseline(
+                    &inputs,
+                    &resolver,
+                    jobs,
+                    cache.as_ref(),
+                    Path::new(&path),
+                    &messages,
+                )?;
+                return Ok(());
+            }
+            let baseline = baseline
+                .map(|path| Baseline::load(Path::new(&path)))
+                .transpose()?;
+            if watch {
+                ensure_text_output(format)?;
+                let task = Task::Lint {
+                    baseline: baseline.as_ref(),
+                };
+                return watch::run(&inputs, &overrides, task, cache.as_ref(), &messages);
+            }
+            let resolver = Resolver::new(overrides);
+            if checker::lint(
+                &inputs,
+                &resolver,
+                jobs,
+                cache.as_ref(),
+                baseline.as_ref(),
+                format,
+                &messages,
+            )? > 0
+            {
+                std::process::exit(1);
+            }
+        }
+        Command::Lsp => lsp::run(&messages)?,
+        Command::Cache {
+            command: CacheCommand::Clean,
+        } => {
+            if Cache::clean(Path::new(CACHE_DIR_NAME))? {
+                println!("{}", messages.cache_cleaned(CACHE_DIR_NAME));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// `--watch` redraws a human-readable report; other formats are meant for one-shot runs.
+fn ensure_text_output(format: OutputFormat) -> Result<()> {
+    if format != OutputFormat::Text {
+        anyhow::bail!("--watch only supports text output");
+    }
+    Ok(())
+}
@@ -0,0 +1,705 @@
+use crate::formatter::{parse_comma_style, parse_join_on, FormatConfig};
+use crate::linter::{parse_keyword_case, LintConfig};
+use crate::target::{ensure_versioned, Version};
+use anyhow::{Context, Result};
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// Name of the configuration file looked up from each checked file's directory upward.
+pub const CONFIG_FILE_NAME: &str = ".sqlex.toml";
+
+/// Contents of a `.sqlex.toml` file. Every setting is optional; anything left out
+/// is inherited from a config file in a parent directory or falls back to the
+/// built-in default, and CLI flags take precedence over all of them.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    /// Stop inheriting settings from config files in parent directories.
+    pub root: bool,
+    /// SQL dialect for files under this directory.
+    pub dialect: Option<String>,
+    /// Version of the database the dialect stands for, e.g. `"5.7"` for MySQL 5.7.
+    pub target_version: Option<String>,
+    /// Glob patterns (relative to the config file) selecting files during directory traversal.
+    pub include: Vec<String>,
+    /// Glob patterns (relative to the config file) skipped during directory traversal.
+    pub exclude: Vec<String>,
+    /// Per-rule lint settings.
+    pub lint: LintSettings,
+    /// Layout settings for `sqlex format`.
+    pub format: FormatSettings,
+    /// Settings applied only to files matching a set of globs, in declaration order.
+    pub overrides: Vec<Override>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct LintSettings {
+    pub keyword_case: Option<String>,
+    pub function_case: Option<String>,
+    pub type_case: Option<String>,
+    pub identifier_case: Option<String>,
+    pub no_select_star: Option<bool>,
+    pub require_table_alias: Option<bool>,
+    pub trailing_semicolon: Option<bool>,
+    pub extra_keywords: Option<Vec<String>>,
+    pub exclude_keywords: Option<Vec<String>>,
+}
+
+impl LintSettings {
+    /// Layer `other` on top of `self`: values set in `other` win.
+    fn merge(&mut self, other: &LintSettings) {
+        if other.keyword_case.is_some() {
+            self.keyword_case.clone_from(&other.keyword_case);
+        }
+        if other.function_case.is_some() {
+            self.function_case.clone_from(&other.function_case);
+        }
+        if other.type_case.is_some() {
+            self.type_case.clone_from(&other.type_case);
+        }
+        if other.identifier_case.is_some() {
+            self.identifier_case.clone_from(&other.identifier_case);
+        }
+        self.no_select_star = other.no_select_star.or(self.no_select_star);
+        self.require_table_alias = other.require_table_alias.or(self.require_table_alias);
+        self.trailing_semicolon = other.trailing_semicolon.or(self.trailing_semicolon);
+        if other.extra_keywords.is_some() {
+            self.extra_keywords.clone_from(&other.extra_keywords);
+        }
+        if other.exclude_keywords.is_some() {
+            self.exclude_keywords.clone_from(&other.exclude_keywords);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct FormatSettings {
+    pub indent_width: Option<usize>,
+    pub comma_style: Option<String>,
+    pub max_line_length: Option<usize>,
+    pub join_on: Option<String>,
+}
+
+impl FormatSettings {
+    /// Layer `other` on top of `self`: values set in `other` win.
+    fn merge(&mut self, other: &FormatSettings) {
+        self.indent_width = other.indent_width.or(self.indent_width);
+        if other.comma_style.is_some() {
+            self.comma_style.clone_from(&other.comma_style);
+        }
+        self.max_line_length = other.max_line_length.or(self.max_line_length);
+        if other.join_on.is_some() {
+            self.join_on.clone_from(&other.join_on);
+        }
+    }
+}
+
+/// An `[[overrides]]` section.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Override {
+    /// Glob patterns (relative to the config file) of the files this section applies to.
+    pub files: Vec<String>,
+    pub dialect: Option<String>,
+    pub target_version: Option<String>,
+    pub lint: LintSettings,
+    pub format: FormatSettings,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read: {}", path.display()))?;
+        toml::from_str(&text).with_context(|| format!("Invalid config: {}", path.display()))
+    }
+}
+
+/// Settings given on the command line. `None` means the flag was not passed, so the
+/// config files (or the built-in defaults) decide.
+#[derive(Debug, Clone, Default)]
+pub struct Overrides {
+    pub dialect: Option<String>,
+    pub target_version: Option<String>,
+    pub keyword_case: Option<String>,
+    pub function_case: Option<String>,
+    pub type_case: Option<String>,
+    pub identifier_case: Option<String>,
+    pub no_select_star: Option<bool>,
+    pub require_table_alias: Option<bool>,
+    pub indent_width: Option<usize>,
+    pub comma_style: Option<String>,
+    pub max_line_length: Option<usize>,
+    pub join_on: Option<String>,
+}
+
+/// Effective settings for one file after merging defaults, config files and CLI flags.
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub dialect: String,
+    /// Version of the target database; constructs it predates are reported by `check`.
+    pub target_version: Option<Version>,
+    pub lint: LintConfig,
+    pub format: FormatConfig,
+}
+
+/// A loaded config file together with its compiled glob patterns.
+#[derive(Debug)]
+struct Layer {
+    dir: PathBuf,
+    config: Config,
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+    overrides: Vec<GlobSet>,
+}
+
+impl Layer {
+    fn load(dir: &Path, path: &Path) -> Result<Self> {
+        let config = Config::load(path)?;
+        let overrides = config
+            .overrides
+            .iter()
+            .map(|o| build_glob_set(&o.files).map(|set| set.unwrap_or_else(GlobSet::empty)))
+            .collect::<Result<_>>()
+            .with_context(|| format!("Invalid config: {}", path.display()))?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            include: build_glob_set(&config.include)
+                .with_context(|| format!("Invalid config: {}", path.display()))?,
+            exclude: build_glob_set(&config.exclude)
+                .with_context(|| format!("Invalid config: {}", path.display()))?,
+            overrides,
+            config,
+        })
+    }
+
+    fn relative<'a>(&self, file: &'a Path) -> &'a Path {
+        file.strip_prefix(&self.dir).unwrap_or(file)
+    }
+}
+
+/// Resolves the settings that apply to each file.
+///
+/// Every directory from the file upward may contain a `.sqlex.toml`. Settings from
+/// outer directories are applied first, so nearer files win; a config with
+/// `root = true` cuts the chain off. Within one file, matching `[[overrides]]`
+/// sections are applied after its top-level settings.
+pub struct Resolver {
+    overrides: Overrides,
+    chains: RefCell<HashMap<PathBuf, Rc<Vec<Rc<Layer>>>>>,
+}
+
+impl Resolver {
+    pub fn new(overrides: Overrides) -> Self {
+        Self {
+            overrides,
+            chains: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn settings_for(&self, file: &Path) -> Result<Settings> {
+        let file = absolute(file);
+        let chain = self.chain(parent_dir(&file))?;
+
+        let mut dialect = None;
+        let mut target_version = None;
+        let mut lint = LintSettings::default();
+        let mut format = FormatSettings::default();
+        for layer in chain.iter() {
+            dialect = layer.config.dialect.clone().or(dialect);
+            target_version = layer.config.target_version.clone().or(target_version);
+            lint.merge(&layer.config.lint);
+            format.merge(&layer.config.format);
+
+            let relative = layer.relative(&file);
+            for (section, globs) in layer.config.overrides.iter().zip(&layer.overrides) {
+                if globs.is_match(relative) {
+                    dialect = section.dialect.clone().or(dialect);
+                    target_version = section.target_version.clone().or(target_version);
+                    lint.merge(&section.lint);
+                    format.merge(&section.format);
+                }
+            }
+        }
+
+        let defaults = LintConfig::default();
+        let format_defaults = FormatConfig::default();
+        let keyword_case = self
+            .overrides
+            .keyword_case
+            .as_deref()
+            .or(lint.keyword_case.as_deref())
+            .map(parse_keyword_case)
+            .unwrap_or(defaults.keyword_case);
+        let case = |flag: &Option<String>, setting: &Option<String>| {
+            flag.as_deref()
+                .or(setting.as_deref())
+                .map(parse_keyword_case)
+        };
+
+        let dialect = self
+            .overrides
+            .dialect
+            .clone()
+            .or(dialect)
+            .unwrap_or_else(|| "generic".to_string());
+        let target_version = self
+            .overrides
+            .target_version
+            .clone()
+            .or(target_version)
+            .map(|version| Version::parse(&version))
+            .transpose()?;
+        if target_version.is_some() {
+            ensure_versioned(&dialect)?;
+        }
+
+        Ok(Settings {
+            dialect,
+            target_version,
+            lint: LintConfig {
+                keyword_case,
+                function_case: case(&self.overrides.function_case, &lint.function_case)
+                    .unwrap_or(defaults.function_case),
+                type_case: case(&self.overrides.type_case, &lint.type_case),
+                identifier_case: case(&self.overrides.identifier_case, &lint.identifier_case)
+                    .unwrap_or(defaults.identifier_case),
+                no_select_star: self
+                    .overrides
+                    .no_select_star
+                    .or(lint.no_select_star)
+                    .unwrap_or(defaults.no_select_star),
+                require_table_alias: self
+                    .overrides
+                    .require_table_alias
+                    .or(lint.require_table_alias)
+                    .unwrap_or(defaults.require_table_alias),
+                trailing_semicolon: lint
+                    .trailing_semicolon
+                    .unwrap_or(defaults.trailing_semicolon),
+                extra_keywords: lint.extra_keywords.unwrap_or(defaults.extra_keywords),
+                exclude_keywords: lint.exclude_keywords.unwrap_or(defaults.exclude_keywords),
+            },
+            format: FormatConfig {
+                indent_width: self
+                    .overrides
+                    .indent_width
+                    .or(format.indent_width)
+                    .unwrap_or(format_defaults.indent_width),
+                comma_style: self
+                    .overrides
+                    .comma_style
+                    .as_deref()
+                    .or(format.comma_style.as_deref())
+                    .map(parse_comma_style)
+                    .unwrap_or(format_defaults.comma_style),
+                max_line_length: self
+                    .overrides
+                    .max_line_length
+                    .or(format.max_line_length)
+                    .unwrap_or(format_defaults.max_line_length),
+                join_on: self
+                    .overrides
+                    .join_on
+                    .as_deref()
+                    .or(format.join_on.as_deref())
+                    .map(parse_join_on)
+                    .unwrap_or(format_defaults.join_on),
+            },
+        })
+    }
+
+    /// Whether a file found by directory traversal should be checked.
+    ///
+    /// A file is skipped when any config in its chain excludes it. The nearest config
+    /// with `include` patterns decides inclusion; without one every `*.sql` file is
+    /// selected. Files passed explicitly on the command line bypass this check.
+    pub fn is_selected(&self, file: &Path) -> Result<bool> {
+        let absolute = absolute(file);
+        let chain = self.chain(parent_dir(&absolute))?;
+
+        let excluded = chain.iter().any(|layer| {
+            layer
+                .exclude
+                .as_ref()
+                .is_some_and(|set| set.is_match(layer.relative(&absolute)))
+        });
+        if excluded {
+            return Ok(false);
+        }
+
+        let include = chain
+            .iter()
+            .rev()
+            .find_map(|layer| layer.include.as_ref().map(|set| (layer, set)));
+        Ok(match include {
+            Some((layer, set)) => set.is_match(layer.relative(&absolute)),
+            None => file.extension().is_some_and(|ext| ext == "sql"),
+        })
+    }
+
+    /// Config layers that apply to `dir`, outermost first.
+    fn chain(&self, dir: &Path) -> Result<Rc<Vec<Rc<Layer>>>> {
+        if let Some(chain) = self.chains.borrow().get(dir) {
+            return Ok(Rc::clone(chain));
+        }
+
+        let candidate = dir.join(CONFIG_FILE_NAME);
+        let own = if candidate.is_file() {
+            Some(Rc::new(Layer::load(dir, &candidate)?))
+        } else {
+            None
+        };
+
+        let mut layers = match (&own, dir.parent()) {
+            (Some(layer), _) if layer.config.root => Vec::new(),
+            (_, Some(parent)) => self.chain(parent)?.as_ref().clone(),
+            (_, None) => Vec::new(),
+        };
+        layers.extend(own);
+
+        let chain = Rc::new(layers);
+        self.chains
+            .borrow_mut()
+            .insert(dir.to_path_buf(), Rc::clone(&chain));
+        Ok(chain)
+    }
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn parent_dir(file: &Path) -> &Path {
+    file.parent().unwrap_or(file)
+}
+
+fn build_glob_set(patterns: &[String]) -> Result<Option<GlobSet>> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        // `*` stays within one path component, like .gitignore; use `**` to cross directories.
+        let glob: Glob = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .with_context(|| format!("Invalid glob pattern: {}", pattern))?;
+        builder.add(glob);
+    }
+    Ok(Some(builder.build()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formatter::{CommaStyle, JoinOn};
+    use crate::linter::KeywordCase;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_full_config() {
+        let config: Config = toml::from_str(
+            r#"
+dialect = "postgres"
+include = ["**/*.sql"]
+exclude = ["vendor/**"]
+
+[lint]
+keyword-case = "lower"
+no-select-star = false
+require-table-alias = true
+trailing-semicolon = false
+extra-keywords = ["PIVOT"]
+exclude-keywords = ["name"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.dialect.as_deref(), Some("postgres"));
+        assert_eq!(config.exclude, vec!["vendor/**"]);
+        assert_eq!(config.lint.keyword_case.as_deref(), Some("lower"));
+        assert_eq!(config.lint.require_table_alias, Some(true));
+        assert_eq!(config.lint.extra_keywords, Some(vec!["PIVOT".to_string()]));
+        assert_eq!(config.lint.exclude_keywords, Some(vec!["name".to_string()]));
+    }
+
+    #[test]
+    fn test_unknown_keys_are_rejected() {
+        assert!(toml::from_str::<Config>("dialekt = \"mysql\"").is_err());
+    }
+
+    fn write_config(dir: &Path, text: &str) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join(CONFIG_FILE_NAME), text).unwrap();
+    }
+
+    #[test]
+    fn test_parse_overrides_sections() {
+        let config: Config = toml::from_str(
+            r#"
+[[overrides]]
+files = ["migrations/**"]
+dialect = "postgres"
+
+[[overrides]]
+files = ["legacy/**"]
+lint = { keyword-case = "ignore" }
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.overrides.len(), 2);
+        assert_eq!(config.overrides[0].dialect.as_deref(), Some("postgres"));
+        assert_eq!(
+            config.overrides[1].lint.keyword_case.as_deref(),
+            Some("ignore")
+        );
+    }
+
+    #[test]
+    fn test_settings_walk_up_from_nested_directory() {
+        let dir = TempDir::new().unwrap();
+        write_config(dir.path(), "dialect = \"mysql\"");
+        let file = dir.path().join("a").join("b").join("q.sql");
+
+        let settings = Resolver::new(Overrides::default())
+            .settings_for(&file)
+            .unwrap();
+        assert_eq!(settings.dialect, "mysql");
+    }
+
+    #[test]
+    fn test_format_settings_merge_with_flags() {
+        let dir = TempDir::new().unwrap();
+        write_config(
+            dir.path(),
+            "[format]\nindent-width = 2\ncomma-style = \"leading\"\n",
+        );
+        write_config(&dir.path().join("sub"), "[format]\nmax-line-length = 120\n");
+        let file = dir.path().join("sub").join("q.sql");
+
+        let settings = Resolver::new(Overrides {
+            indent_width: Some(8),
+            ..Default::default()
+        })
+        .settings_for(&file)
+        .unwrap();
+        assert_eq!(settings.format.indent_width, 8);
+        assert_eq!(settings.format.comma_style, CommaStyle::Leading);
+        assert_eq!(settings.format.max_line_length, 120);
+        assert_eq!(settings.format.join_on, JoinOn::SameLine);
+    }
+
+    #[test]
+    fn test_case_settings_resolve_per_role() {
+        let dir = TempDir::new().unwrap();
+        write_config(
+            dir.path(),
+            "[lint]\nkeyword-case = \"lower\"\nidentifier-case = \"consistent\"\n",
+        );
+        let file = dir.path().join("q.sql");
+
+        let settings = Resolver::new(Overrides {
+            function_case: Some("capitalize".to_string()),
+            ..Default::default()
+        })
+        .settings_for(&file)
+        .unwrap();
+        assert_eq!(settings.lint.function_case, KeywordCase::Capitalize);
+        assert_eq!(settings.lint.identifier_case, KeywordCase::Consistent);
+        // Types follow the keywords unless set.
+        assert_eq!(settings.lint.type_case(), KeywordCase::Lower);
+    }
+
+    #[test]
+    fn test_invalid_config_is_reported() {
+        let dir = TempDir::new().unwrap();
+        write_config(dir.path(), "dialect = [");
+        let err = Resolver::new(Overrides::default())
+            .settings_for(&dir.path().join("q.sql"))
+            .unwrap_err();
+        assert!(err.to_string().contains("Invalid config"));
+    }
+
+    #[test]
+    fn test_defaults_without_config() {
+        let dir = TempDir::new().unwrap();
+        let settings = Resolver::new(Overrides::default())
+            .settings_for(&dir.path().join("q.sql"))
+            .unwrap();
+        assert_eq!(settings.dialect, "generic");
+        assert_eq!(settings.lint.keyword_case, KeywordCase::Upper);
+        assert!(settings.lint.no_select_star);
+        assert!(settings.lint.trailing_semicolon);
+    }
+
+    #[test]
+    fn test_cli_overrides_config_values() {
+        let dir = TempDir::new().unwrap();
+        write_config(
+            dir.path(),
+            "dialect = \"mysql\"\n[lint]\nkeyword-case = \"lower\"\nno-select-star = false\n",
+        );
+        let resolver = Resolver::new(Overrides {
+            dialect: Some("sqlite".to_string()),
+            no_select_star: Some(true),
+            ..Default::default()
+        });
+        let settings = resolver.settings_for(&dir.path().join("q.sql")).unwrap();
+        // Flags win where given; the config fills in the rest.
+        assert_eq!(settings.dialect, "sqlite");
+        assert!(settings.lint.no_select_star);
+        assert_eq!(settings.lint.keyword_case, KeywordCase::Lower);
+    }
+
+    #[test]
+    fn test_nested_config_inherits_and_overrides_parent() {
+        let dir = TempDir::new().unwrap();
+        write_config(
+            dir.path(),
+            "dialect = \"mysql\"\n[lint]\nkeyword-case = \"lower\"\n",
+        );
+        write_config(&dir.path().join("warehouse"), "dialect = \"bigquery\"");
+
+        let resolver = Resolver::new(Overrides::default());
+        let top = resolver.settings_for(&dir.path().join("q.sql")).unwrap();
+        let nested = resolver
+            .settings_for(&dir.path().join("warehouse").join("q.sql"))
+            .unwrap();
+        assert_eq!(top.dialect, "mysql");
+        assert_eq!(nested.dialect, "bigquery");
+        // Lint settings not set in the nested file are inherited.
+        assert_eq!(nested.lint.keyword_case, KeywordCase::Lower);
+    }
+
+    #[test]
+    fn test_root_config_stops_inheritance() {
+        let dir = TempDir::new().unwrap();
+        write_config(dir.path(), "[lint]\nkeyword-case = \"lower\"\n");
+        write_config(
+            &dir.path().join("legacy"),
+            "root = true\ndialect = \"mysql\"",
+        );
+
+        let settings = Resolver::new(Overrides::default())
+            .settings_for(&dir.path().join("legacy").join("q.sql"))
+            .unwrap();
+        assert_eq!(settings.dialect, "mysql");
+        assert_eq!(settings.lint.keyword_case, KeywordCase::Upper);
+    }
+
+    #[test]
+    fn test_override_sections_match_relative_paths() {
+        let dir = TempDir::new().unwrap();
+        write_config(
+            dir.path(),
+            r#"
+dialect = "generic"
+
+[[overrides]]
+files = ["app/migrations/**"]
+dialect = "postgres"
+
+[[overrides]]
+files = ["legacy/**"]
+dialect = "mysql"
+lint = { no-select-star = false }
+"#,
+        );
+
+        let resolver = Resolver::new(Overrides::default());
+        let settings = |p: &str| resolver.settings_for(&dir.path().join(p)).unwrap();
+        assert_eq!(settings("app/migrations/001.sql").dialect, "postgres");
+        assert_eq!(settings("app/queries/a.sql").dialect, "generic");
+        let legacy = settings("legacy/old.sql");
+        assert_eq!(legacy.dialect, "mysql");
+        assert!(!legacy.lint.no_select_star);
+    }
+
+    #[test]
+    fn test_target_version_resolves_like_dialect() {
+        let dir = TempDir::new().unwrap();
+        write_config(
+            dir.path(),
+            r#"
+dialect = "mysql"
+target-version = "5.7"
+
+[[overrides]]
+files = ["reports/**"]
+target-version = "8.0"
+"#,
+        );
+
+        let resolver = Resolver::new(Overrides::default());
+        let version = |p: &str| {
+            let settings = resolver.settings_for(&dir.path().join(p)).unwrap();
+            settings.target_version.map(|v| v.to_string())
+        };
+        assert_eq!(version("a.sql").as_deref(), Some("5.7"));
+        assert_eq!(version("reports/daily.sql").as_deref(), Some("8.0"));
+
+        let flags = Resolver::new(Overrides {
+            target_version: Some("8.0.31".to_string()),
+            ..Default::default()
+        });
+        let settings = flags.settings_for(&dir.path().join("a.sql")).unwrap();
+        assert_eq!(settings.target_version.unwrap().to_string(), "8.0.31");
+
+        write_config(dir.path(), "target-version = \"1\"");
+        let err = Resolver::new(Overrides::default())
+            .settings_for(&dir.path().join("a.sql"))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Target versions are not supported for dialect generic (supported: mysql, postgres, sqlite)"
+        );
+
+        write_config(dir.path(), "target-version = \"latest\"");
+        let err = Resolver::new(Overrides::default())
+            .settings_for(&dir.path().join("a.sql"))
+            .unwrap_err();
+        assert!(err.to_string().contains("Invalid target version: latest"));
+    }
+
+    #[test]
+    fn test_include_and_exclude() {
+        let dir = TempDir::new().unwrap();
+        write_config(
+            dir.path(),
+            "include = [\"**/*.sql\", \"*.ddl\"]\nexclude = [\"vendor/**\"]\n",
+        );
+
+        let resolver = Resolver::new(Overrides::default());
+        let selected = |p: &str| resolver.is_selected(&dir.path().join(p)).unwrap();
+        assert!(selected("queries/a.sql"));
+        assert!(selected("schema.ddl"));
+        // `*` does not cross directories.
+        assert!(!selected("sub/schema.ddl"));
+        assert!(!selected("vendor/lib/a.sql"));
+    }
+
+    #[test]
+    fn test_nested_exclude_applies_below_its_directory() {
+        let dir = TempDir::new().unwrap();
+        write_config(&dir.path().join("legacy"), "exclude = [\"generated/**\"]");
+
+        let resolver = Resolver::new(Overrides::default());
+        let selected = |p: &str| resolver.is_selected(&dir.path().join(p)).unwrap();
+        assert!(selected("generated/a.sql"));
+        assert!(!selected("legacy/generated/a.sql"));
+    }
+
+    #[test]
+    fn test_default_selection_uses_sql_extension() {
+        let dir = TempDir::new().unwrap();
+        let resolver = Resolver::new(Overrides::default());
+        assert!(resolver.is_selected(&dir.path().join("a.sql")).unwrap());
+        assert!(!resolver.is_selected(&dir.path().join("a.txt")).unwrap());
+    }
+}
@@ -0,0 +1,332 @@
+//! Dialect detection for `--dialect auto`.
+//!
+//! Each supported dialect is scored from what the file looks like (quoting, casts,
+//! parameters, dialect-only keywords and hints in its path) and from whether the
+//! dialect's parser accepts it. The best score wins; the confidence is its share of
+//! all scores, so SQL every dialect accepts is reported as generic with low confidence.
+
+use crate::cache::Cache;
+use crate::checker::{cached, check_sql, get_dialect, DIALECTS};
+use crate::config::Settings;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlparser::dialect::GenericDialect;
+use sqlparser::tokenizer::{Token, Tokenizer, Whitespace};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Value of the `dialect` setting that asks for detection.
+pub const AUTO: &str = "auto";
+
+/// Weight of a signal found in the SQL.
+const SIGNAL_WEIGHT: f64 = 1.0;
+/// Weight of a dialect named in the file's path.
+const PATH_WEIGHT: f64 = 2.0;
+/// Bonus for a dialect whose parser accepts the whole file.
+const PARSE_WEIGHT: f64 = 3.0;
+/// How sharply the confidence favors higher scores.
+const SHARPNESS: f64 = 2.0;
+
+/// Words in a path that name a dialect.
+const PATH_HINTS: &[(&str, &str)] = &[
+    ("ansi", "ansi"),
+    ("mysql", "mysql"),
+    ("mariadb", "mysql"),
+    ("postgres", "postgres"),
+    ("postgresql", "postgres"),
+    ("pg", "postgres"),
+    ("psql", "postgres"),
+    ("sqlite", "sqlite"),
+    ("bigquery", "bigquery"),
+    ("bq", "bigquery"),
+    ("mssql", "mssql"),
+    ("tsql", "mssql"),
+    ("sqlserver", "mssql"),
+    ("snowflake", "snowflake"),
+    ("redshift", "redshift"),
+    ("clickhouse", "clickhouse"),
+    ("duckdb", "duckdb"),
+    ("hive", "hive"),
+    ("databricks", "databricks"),
+    ("spark", "databricks"),
+];
+
+/// The dialect chosen for a file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Detection {
+    /// Name of the dialect, as accepted by [`get_dialect`].
+    pub dialect: String,
+    /// Share of the total score, from 0 to 1.
+    pub confidence: f64,
+    /// Whether the dialect's parser accepts the whole file. When no dialect does, the
+    /// one with the fewest errors is used.
+    pub parsed: bool,
+}
+
+/// Detect the dialect of `content`, read from `path`.
+pub fn detect_dialect(content: &str, path: &Path) -> Detection {
+    let mut scores: HashMap<&str, f64> = HashMap::new();
+    for dialect in signals(content) {
+        *scores.entry(dialect).or_default() += SIGNAL_WEIGHT;
+    }
+    for dialect in path_hints(path) {
+        *scores.entry(dialect).or_default() += PATH_WEIGHT;
+    }
+
+    let candidates: Vec<(&'static str, f64, bool)> = DIALECTS
+        .iter()
+        .map(|&name| {
+            let dialect = get_dialect(name).expect("DIALECTS are supported");
+            let errors = check_sql(content, dialect.as_ref()).len();
+            let parse_score = if errors == 0 {
+                PARSE_WEIGHT
+            } else {
+                -(errors as f64)
+            };
+            let score = scores.get(name).copied().unwrap_or_default() + parse_score;
+            (name, score, errors == 0)
+        })
+        .collect();
+
+    // The first of equal scores wins, so plain SQL is generic.
+    let (dialect, best, parsed) = candidates
+        .iter()
+        .copied()
+        .reduce(|best, c| if c.1 > best.1 { c } else { best })
+        .expect("DIALECTS is not empty");
+    let total: f64 = candidates
+        .iter()
+        .map(|c| (SHARPNESS * (c.1 - best)).exp())
+        .sum();
+    Detection {
+        dialect: dialect.to_string(),
+        confidence: 1.0 / total,
+        parsed,
+    }
+}
+
+/// Settings for `content` of the file at `path`, with the dialect detected if it is
+/// set to [`AUTO`]. Detection parses the file with every dialect, so like results it
+/// is looked up in `cache` first.
+pub fn resolve<'a>(
+    settings: &'a Settings,
+    path: &Path,
+    content: &str,
+    cache: Option<&Cache>,
+) -> Result<(Cow<'a, Settings>, Option<Detection>)> {
+    if settings.dialect != AUTO {
+        return Ok((Cow::Borrowed(settings), None));
+    }
+    // The path is part of the key, as it hints at the dialect too.
+    let key = ["detect", &path.to_string_lossy()];
+    let detection: Detection = cached(cache, &key, content, || Ok(detect_dialect(content, path)))?;
+    let settings = Settings {
+        dialect: detection.dialect.clone(),
+        ..settings.clone()
+    };
+    Ok((Cow::Owned(settings), Some(detection)))
+}
+
+/// Dialects suggested by the SQL itself, once per kind of signal.
+fn signals(content: &str) -> Vec<&'static str> {
+    let Ok(tokens) = Tokenizer::new(&GenericDialect {}, content).tokenize() else {
+        return Vec::new();
+    };
+    let mut found: Vec<&'static [&'static str]> = Vec::new();
+    let mut add = |dialects: &'static [&'static str]| {
+        if !found.contains(&dialects) {
+            found.push(dialects);
+        }
+    };
+
+    // Positions of the tokens that are not whitespace.
+    let code: Vec<usize> = (0..tokens.len())
+        .filter(|&i| !matches!(tokens[i], Token::Whitespace(_)))
+        .collect();
+    let alone_on_line = |i: usize| {
+        let line_break = |t: Option<&Token>| {
+            t.is_none_or(|t| matches!(t, Token::Whitespace(Whitespace::Newline)))
+        };
+        line_break(i.checked_sub(1).map(|i| &tokens[i])) && line_break(tokens.get(i + 1))
+    };
+
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            Token::Word(w) if w.quote_style == Some('`') => {
+                add(&["mysql", "bigquery", "hive", "databricks", "clickhouse"])
+            }
+            Token::Word(w) if w.quote_style == Some('"') => add(&[
+                "postgres",
+                "ansi",
+                "sqlite",
+                "snowflake",
+                "redshift",
+                "duckdb",
+            ]),
+            // `[name]`, as opposed to a subscript such as `a[1]`.
+            Token::LBracket
+                if matches!(tokens.get(i + 1), Some(Token::Word(_)))
+                    && matches!(tokens.get(i + 2), Some(Token::RBracket))
+                    && (i == 0
+                        || matches!(
+                            tokens[i - 1],
+                            Token::Whitespace(_) | Token::Period | Token::Comma
+                        )) =>
+            {
+                add(&["mssql"])
+            }
+            Token::DoubleColon => {
+                add(&["postgres", "redshift", "snowflake", "duckdb", "databricks"])
+            }
+            Token::Placeholder(p) if p.starts_with('$') => add(&["postgres", "duckdb"]),
+            Token::Placeholder(p) if p == "?" => add(&["mysql", "sqlite"]),
+            _ => {}
+        }
+    }
+
+    for (n, &i) in code.iter().enumerate() {
+        let token = &tokens[i];
+        if word(token, "AUTO_INCREMENT") {
+            add(&["mysql"]);
+        } else if word(token, "AUTOINCREMENT") {
+            add(&["sqlite"]);
+        } else if word(token, "QUALIFY") {
+            add(&["snowflake", "bigquery", "duckdb", "databricks"]);
+        } else if word(token, "ILIKE") {
+            add(&["postgres", "snowflake", "redshift", "duckdb"]);
+        } else if word(token, "SERIAL") || word(token, "BIGSERIAL") {
+            add(&["postgres"]);
+        } else if word(token, "PREWHERE") {
+            add(&["clickhouse"]);
+        } else if word(token, "NOLOCK") || word(token, "NVARCHAR") {
+            add(&["mssql"]);
+        } else if word(token, "TOP") && n > 0 && word(&tokens[code[n - 1]], "SELECT") {
+            add(&["mssql", "redshift"]);
+        } else if word(token, "GO") && alone_on_line(i) {
+            // T-SQL's batch separator.
+            add(&["mssql"]);
+        }
+    }
+    found.into_iter().flatten().copied().collect()
+}
+
+/// Whether `token` is the unquoted word `value`, in any case.
+fn word(token: &Token, value: &str) -> bool {
+    matches!(token, Token::Word(w) if w.quote_style.is_none() && w.value.eq_ignore_ascii_case(value))
+}
+
+/// Dialects named by a directory or a word of the file name in `path`.
+fn path_hints(path: &Path) -> Vec<&'static str> {
+    let path = path.to_string_lossy().to_lowercase();
+    let words: Vec<&str> = path.split(|c: char| !c.is_ascii_alphanumeric()).collect();
+    PATH_HINTS
+        .iter()
+        .filter(|(hint, _)| words.contains(hint))
+        .map(|&(_, dialect)| dialect)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(sql: &str) -> Detection {
+        detect_dialect(sql, Path::new("query.sql"))
+    }
+
+    #[test]
+    fn test_plain_sql_is_generic_with_low_confidence() {
+        let detection = detect("SELECT id FROM users WHERE id = 1;");
+        assert_eq!(detection.dialect, "generic");
+        assert!(detection.parsed);
+        assert!(detection.confidence < 0.5, "{detection:?}");
+    }
+
+    #[test]
+    fn test_signals() {
+        let sql = "CREATE TABLE `users` (id INT AUTO_INCREMENT PRIMARY KEY);";
+        assert_eq!(detect(sql).dialect, "mysql");
+        assert_eq!(
+            detect("SELECT id::text FROM users WHERE id = $1;").dialect,
+            "postgres"
+        );
+        assert_eq!(detect("SELECT [id] FROM [dbo].[users];").dialect, "mssql");
+        let sql =
+            "SELECT id FROM t WHERE name ILIKE 'a%' QUALIFY ROW_NUMBER() OVER (ORDER BY id) = 1;";
+        assert_eq!(detect(sql).dialect, "snowflake");
+    }
+
+    #[test]
+    fn test_only_one_dialect_parses() {
+        let detection = detect("SELECT id FROM t FINAL PREWHERE x = 1 SETTINGS max_threads = 2;");
+        assert_eq!(detection.dialect, "clickhouse");
+        assert!(detection.parsed);
+        assert!(detection.confidence > 0.8, "{detection:?}");
+        assert_eq!(detect("DECLARE @x INT = 1;").dialect, "mssql");
+    }
+
+    #[test]
+    fn test_path_hints() {
+        let sql = "SELECT id FROM users;";
+        let detect_at = |path: &str| detect_dialect(sql, Path::new(path)).dialect;
+        assert_eq!(detect_at("db/postgres/schema.sql"), "postgres");
+        assert_eq!(detect_at("warehouse/daily_bq.sql"), "bigquery");
+        // Only whole words count.
+        assert_eq!(detect_at("db/pgsomething/query.sql"), "generic");
+    }
+
+    #[test]
+    fn test_falls_back_when_nothing_parses() {
+        let detection = detect("SELECT FROM;");
+        assert_eq!(detection.dialect, "generic");
+        assert!(!detection.parsed);
+    }
+
+    #[test]
+    fn test_resolve_only_detects_auto() {
+        let settings = Settings {
+            dialect: "mysql".to_string(),
+            target_version: None,
+            lint: Default::default(),
+            format: Default::default(),
+        };
+        let sql = "SELECT id::text FROM users;";
+        let (resolved, detection) = resolve(&settings, Path::new("a.sql"), sql, None).unwrap();
+        assert_eq!(resolved.dialect, "mysql");
+        assert!(detection.is_none());
+
+        let auto = Settings {
+            dialect: AUTO.to_string(),
+            ..settings
+        };
+        let (resolved, detection) = resolve(&auto, Path::new("a.sql"), sql, None).unwrap();
+        assert_eq!(resolved.dialect, "postgres");
+        assert_eq!(detection.unwrap().dialect, "postgres");
+    }
+
+    #[test]
+    fn test_resolve_caches_detection() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cache = Cache::new(dir.path());
+        let auto = Settings {
+            dialect: AUTO.to_string(),
+            target_version: None,
+            lint: Default::default(),
+            format: Default::default(),
+        };
+        let sql = "SELECT id::text FROM users;";
+        let (_, first) = resolve(&auto, Path::new("a.sql"), sql, Some(&cache)).unwrap();
+        let entries = || {
+            std::fs::read_dir(dir.path().join(env!("CARGO_PKG_VERSION")))
+                .unwrap()
+                .count()
+        };
+        assert_eq!(entries(), 1);
+
+        let (resolved, second) = resolve(&auto, Path::new("a.sql"), sql, Some(&cache)).unwrap();
+        assert_eq!(resolved.dialect, "postgres");
+        assert_eq!(first, second);
+        assert_eq!(entries(), 1);
+    }
+}
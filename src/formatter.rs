@@ -0,0 +1,978 @@
+//! `sqlex format`: reflow queries into a canonical layout.
+//!
+//! Unlike the fixer, which only recases keywords and leaves layout alone, the formatter
+//! rebuilds every query from its tokens: one clause per line, with the items of a
+//! clause (select list, conditions, ...) kept on the clause's line when they fit within
+//! the maximum line length and broken one per line otherwise. Subqueries are indented
+//! on their own lines. Comments are kept where they were relative to the code around
+//! them. Statements other than queries and DML keep their layout and only have their
+//! keywords recased.
+//!
+//! As a safety net the input and the output are both parsed, and the result is only
+//! returned when the statements are identical.
+
+use crate::casing::miscased;
+use crate::checker::{build_line_offsets, location_to_byte_offset};
+use crate::linter::LintConfig;
+use anyhow::{bail, Result};
+use sqlparser::dialect::Dialect;
+use sqlparser::parser::Parser;
+use sqlparser::tokenizer::{Token, Tokenizer, Whitespace};
+use std::collections::HashMap;
+
+/// Where the separating comma goes when list items are broken one per line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CommaStyle {
+    /// `id,` / `name`
+    Trailing,
+    /// `id` / `, name`
+    Leading,
+}
+
+pub fn parse_comma_style(name: &str) -> CommaStyle {
+    match name.to_lowercase().as_str() {
+        "leading" => CommaStyle::Leading,
+        // "trailing" and any unrecognized value default to trailing.
+        _ => CommaStyle::Trailing,
+    }
+}
+
+/// Where the `ON` (or `USING`) of a join goes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JoinOn {
+    /// `JOIN orders o ON o.user_id = u.id`
+    SameLine,
+    /// `ON` on the next line, indented below the `JOIN`.
+    NewLine,
+}
+
+pub fn parse_join_on(name: &str) -> JoinOn {
+    match name.to_lowercase().as_str() {
+        "new-line" => JoinOn::NewLine,
+        // "same-line" and any unrecognized value default to the same line.
+        _ => JoinOn::SameLine,
+    }
+}
+
+/// Layout options for [`format_sql`].
+#[derive(Debug, Clone)]
+pub struct FormatConfig {
+    pub indent_width: usize,
+    pub comma_style: CommaStyle,
+    pub max_line_length: usize,
+    pub join_on: JoinOn,
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        Self {
+            indent_width: 4,
+            comma_style: CommaStyle::Trailing,
+            max_line_length: 80,
+            join_on: JoinOn::SameLine,
+        }
+    }
+}
+
+/// Reformat `content`, recasing words as the case rules of `lint` ask. Every statement
+/// ends with a semicolon and the result ends with a newline.
+///
+/// Fails when `content` does not parse, or (which would be a bug) when the formatted
+/// SQL would not parse to the same statements as the recased input.
+pub fn format_sql(
+    content: &str,
+    dialect: &dyn Dialect,
+    config: &FormatConfig,
+    lint: &LintConfig,
+) -> Result<String> {
+    if let Err(err) = Parser::parse_sql(dialect, content) {
+        bail!("Cannot format SQL with syntax errors: {}", err);
+    }
+
+    let toks = tokenize(content, dialect, lint)?;
+    // Recasing identifiers changes the AST, so compare against the input as recased,
+    // which is as safe as what `fix` does.
+    let before = Parser::parse_sql(dialect, &recased(content, &toks));
+    let formatter = Formatter {
+        source: content,
+        config,
+    };
+    let formatted = formatter.file(&toks);
+
+    match (before, Parser::parse_sql(dialect, &formatted)) {
+        (Ok(before), Ok(after)) if after == before => Ok(formatted),
+        _ => bail!("Formatting would change the meaning of the SQL; left unformatted"),
+    }
+}
+
+/// `content` with each token's text replaced by its (recased) text in `toks`.
+fn recased(content: &str, toks: &[Tok]) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut prev = 0;
+    for tok in toks {
+        out.push_str(&content[prev..tok.start]);
+        out.push_str(&tok.text);
+        prev = tok.end;
+    }
+    out.push_str(&content[prev..]);
+    out
+}
+
+/// A token as it will be written, with what the formatter needs to know about the
+/// original layout around it. Whitespace is not kept; comments are.
+struct Tok {
+    token: Token,
+    /// The source text, recased if a case rule applies to it.
+    text: String,
+    /// Byte range of `text` in the source.
+    start: usize,
+    end: usize,
+    /// Whether whitespace separated this token from the previous one.
+    space_before: bool,
+    /// Whether this is the first token on its line.
+    line_start: bool,
+}
+
+impl Tok {
+    fn is_comment(&self) -> bool {
+        matches!(
+            self.token,
+            Token::Whitespace(
+                Whitespace::SingleLineComment { .. } | Whitespace::MultiLineComment(_)
+            )
+        )
+    }
+
+    fn is_line_comment(&self) -> bool {
+        matches!(
+            self.token,
+            Token::Whitespace(Whitespace::SingleLineComment { .. })
+        )
+    }
+
+    /// Whether this is the unquoted word `keyword` (in any case).
+    fn is(&self, keyword: &str) -> bool {
+        matches!(&self.token, Token::Word(w) if w.quote_style.is_none() && w.value.eq_ignore_ascii_case(keyword))
+    }
+}
+
+fn tokenize(content: &str, dialect: &dyn Dialect, lint: &LintConfig) -> Result<Vec<Tok>> {
+    let tokens = match Tokenizer::new(dialect, content).tokenize_with_location() {
+        Ok(tokens) => tokens,
+        Err(err) => bail!("Cannot format SQL with syntax errors: {}", err),
+    };
+    let mut recased: HashMap<usize, String> = miscased(&tokens, dialect, lint)
+        .into_iter()
+        .map(|word| (word.index, word.expected))
+        .collect();
+    let line_offsets = build_line_offsets(content);
+    let offset = |location: sqlparser::tokenizer::Location| {
+        location_to_byte_offset(content, &line_offsets, location.line, location.column)
+    };
+
+    let mut toks = Vec::new();
+    let mut space_before = false;
+    let mut line_start = true;
+    for (index, token) in tokens.into_iter().enumerate() {
+        match &token.token {
+            Token::Whitespace(Whitespace::Newline) => {
+                space_before = true;
+                line_start = true;
+                continue;
+            }
+            Token::Whitespace(Whitespace::Space | Whitespace::Tab) => {
+                space_before = true;
+                continue;
+            }
+            Token::EOF => continue,
+            _ => {}
+        }
+
+        let start = offset(token.span.start);
+        let end = offset(token.span.end).max(start);
+        // Line comments include their newline.
+        let raw = content[start..end].trim_end();
+        let text = recased.remove(&index).unwrap_or_else(|| raw.to_string());
+        let is_line_comment = matches!(
+            token.token,
+            Token::Whitespace(Whitespace::SingleLineComment { .. })
+        );
+
+        toks.push(Tok {
+            token: token.token,
+            end: start + raw.len(),
+            text,
+            start,
+            space_before,
+            line_start,
+        });
+        space_before = is_line_comment;
+        line_start = is_line_comment;
+    }
+
+    Ok(toks)
+}
+
+/// How a clause lays out its body.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    /// Comma-separated items: `SELECT`, `FROM`, `GROUP BY`, `SET`, ...
+    List,
+    /// Conditions joined by `AND`/`OR`: `WHERE`, `HAVING`, `QUALIFY`.
+    Condition,
+    /// A join's `ON`, which may share the join's line.
+    On,
+    /// A join's `USING`, which may share the join's line.
+    Using,
+    /// `JOIN` and friends.
+    Join,
+    /// `UNION`, `INTERSECT`, `EXCEPT`.
+    SetOp,
+    /// Anything else: the body is written as is after the head.
+    Inline,
+}
+
+/// Keyword sequences that start a clause, longest first where they share a prefix.
+const HEADS: &[(&[&str], Kind)] = &[
+    (&["SELECT", "DISTINCT"], Kind::List),
+    (&["SELECT", "ALL"], Kind::List),
+    (&["SELECT"], Kind::List),
+    (&["FROM"], Kind::List),
+    (&["WHERE"], Kind::Condition),
+    (&["GROUP", "BY"], Kind::List),
+    (&["HAVING"], Kind::Condition),
+    (&["QUALIFY"], Kind::Condition),
+    (&["WINDOW"], Kind::Inline),
+    (&["ORDER", "BY"], Kind::List),
+    (&["LIMIT"], Kind::Inline),
+    (&["OFFSET"], Kind::Inline),
+    (&["FETCH"], Kind::Inline),
+    (&["FOR", "UPDATE"], Kind::Inline),
+    (&["FOR", "SHARE"], Kind::Inline),
+    (&["UNION", "ALL"], Kind::SetOp),
+    (&["UNION", "DISTINCT"], Kind::SetOp),
+    (&["UNION"], Kind::SetOp),
+    (&["INTERSECT", "ALL"], Kind::SetOp),
+    (&["INTERSECT", "DISTINCT"], Kind::SetOp),
+    (&["INTERSECT"], Kind::SetOp),
+    (&["EXCEPT", "ALL"], Kind::SetOp),
+    (&["EXCEPT", "DISTINCT"], Kind::SetOp),
+    (&["EXCEPT"], Kind::SetOp),
+    (&["MINUS"], Kind::SetOp),
+    (&["WITH", "RECURSIVE"], Kind::List),
+    (&["WITH"], Kind::List),
+    (&["INSERT", "INTO"], Kind::Inline),
+    (&["INSERT"], Kind::Inline),
+    (&["REPLACE", "INTO"], Kind::Inline),
+    (&["UPDATE"], Kind::Inline),
+    (&["DELETE", "FROM"], Kind::Inline),
+    (&["DELETE"], Kind::Inline),
+    (&["VALUES"], Kind::List),
+    (&["SET"], Kind::List),
+    (&["RETURNING"], Kind::List),
+    (&["ON", "CONFLICT"], Kind::Inline),
+    (&["ON", "DUPLICATE", "KEY", "UPDATE"], Kind::List),
+    (&["ON"], Kind::On),
+    (&["USING"], Kind::Using),
+];
+
+/// Keywords that may only start a clause at the start of a statement (or after a
+/// `WITH`), as they double as function names or appear inside other clauses.
+const STATEMENT_HEADS: &[&str] = &["WITH", "INSERT", "REPLACE", "UPDATE", "DELETE"];
+
+/// Words that can precede `JOIN` (or `APPLY`) in a join operator.
+const JOIN_MODIFIERS: &[&str] = &[
+    "NATURAL", "LEFT", "RIGHT", "FULL", "INNER", "OUTER", "CROSS", "SEMI", "ANTI",
+];
+
+struct Clause<'t> {
+    kind: Kind,
+    head: Vec<&'t Tok>,
+    body: Vec<&'t Tok>,
+    /// Comments on their own lines just before the head.
+    comments: Vec<&'t Tok>,
+}
+
+impl Clause<'_> {
+    fn starts_with(&self, keyword: &str) -> bool {
+        self.head.first().is_some_and(|t| t.is(keyword))
+    }
+}
+
+/// A list item or condition, with the comments around it.
+struct Item<'t> {
+    /// Comments on their own lines before the item.
+    leading: Vec<&'t Tok>,
+    toks: Vec<&'t Tok>,
+    /// Comments after the item (and its comma) on the same line.
+    trailing: Vec<&'t Tok>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Separator {
+    Comma,
+    AndOr,
+}
+
+struct Formatter<'a> {
+    source: &'a str,
+    config: &'a FormatConfig,
+}
+
+impl Formatter<'_> {
+    fn file(&self, toks: &[Tok]) -> String {
+        // Statements, and whether a blank line preceded each one.
+        let mut statements: Vec<(Vec<&Tok>, bool)> = vec![(Vec::new(), false)];
+        let mut prev_end = 0;
+        for tok in toks {
+            if tok.token == Token::SemiColon {
+                statements.push((Vec::new(), false));
+                prev_end = tok.end;
+                continue;
+            }
+            let len = statements.len();
+            if tok.is_comment() && !tok.line_start && len > 1 && statements[len - 1].0.is_empty() {
+                // A comment after a `;` on the same line belongs to that statement.
+                statements[len - 2].0.push(tok);
+            } else {
+                let (current, blank_before) = &mut statements[len - 1];
+                if current.is_empty() {
+                    *blank_before = self.source[prev_end..tok.start].matches('\n').count() > 1;
+                }
+                current.push(tok);
+            }
+            prev_end = tok.end;
+        }
+
+        let mut out = String::new();
+        for (toks, blank_before) in statements.iter().filter(|(toks, _)| !toks.is_empty()) {
+            if !out.is_empty() {
+                out.push('\n');
+                if *blank_before {
+                    out.push('\n');
+                }
+            }
+            out.push_str(&self.statement(toks));
+        }
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out
+    }
+
+    fn statement(&self, toks: &[&Tok]) -> String {
+        let Some(first) = toks.iter().position(|t| !t.is_comment()) else {
+            return toks
+                .iter()
+                .map(|t| t.text.as_str())
+                .collect::<Vec<_>>()
+                .join("\n");
+        };
+        let last = toks.iter().rposition(|t| !t.is_comment()).unwrap_or(first);
+
+        let mut out = String::new();
+        for comment in &toks[..first] {
+            out.push_str(&comment.text);
+            out.push('\n');
+        }
+        let code = &toks[first..=last];
+        if is_query(code) {
+            out.push_str(&self.query(code, 0));
+        } else {
+            out.push_str(&self.verbatim(code));
+        }
+        out.push(';');
+        self.push_trailing(&mut out, &toks[last + 1..], 0);
+        out
+    }
+
+    /// Tokens with their original spacing, for statements the formatter does not lay out.
+    fn verbatim(&self, toks: &[&Tok]) -> String {
+        let mut out = String::new();
+        for (i, tok) in toks.iter().enumerate() {
+            if i > 0 {
+                let gap = &self.source[toks[i - 1].end..tok.start];
+                match gap.rfind('\n') {
+                    // Keep line breaks and indentation, but not trailing whitespace.
+                    Some(last) => {
+                        out.push_str(&"\n".repeat(gap.matches('\n').count()));
+                        out.push_str(&gap[last + 1..]);
+                    }
+                    None => out.push_str(gap),
+                }
+            }
+            out.push_str(&tok.text);
+        }
+        out
+    }
+
+    /// A query or DML statement. The first line is not indented; later ones are
+    /// indented by `indent` or more.
+    fn query(&self, toks: &[&Tok], indent: usize) -> String {
+        let clauses = split_clauses(toks);
+        let mut out = String::new();
+
+        for (i, clause) in clauses.iter().enumerate() {
+            let mut at = indent;
+            if i > 0 {
+                let follows_join = matches!(clause.kind, Kind::On | Kind::Using)
+                    && clauses[i - 1].kind == Kind::Join;
+                let prev_ends_in_comment = clauses[i - 1]
+                    .body
+                    .last()
+                    .is_some_and(|t| t.is_line_comment());
+                if follows_join
+                    && self.config.join_on == JoinOn::SameLine
+                    && clause.comments.is_empty()
+                    && !prev_ends_in_comment
+                {
+                    out.push(' ');
+                } else {
+                    if follows_join {
+                        at += self.config.indent_width;
+                    }
+                    out.push('\n');
+                    out.push_str(&pad(at));
+                }
+            }
+            for comment in &clause.comments {
+                out.push_str(&comment.text);
+                out.push('\n');
+                out.push_str(&pad(at));
+            }
+            let column = out.len() - out.rfind('\n').map_or(0, |i| i + 1);
+            out.push_str(&self.clause(clause, at, column));
+        }
+
+        out
+    }
+
+    /// One clause, starting at `column` on a line indented by `indent`.
+    fn clause(&self, clause: &Clause, indent: usize, column: usize) -> String {
+        let head = clause
+            .head
+            .iter()
+            .map(|t| t.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let with_body = |body: String| match (head.is_empty(), body.is_empty()) {
+            (true, _) => body,
+            (false, true) => head.clone(),
+            (false, false) => format!("{} {}", head, body),
+        };
+
+        let separator = match clause.kind {
+            Kind::List => Separator::Comma,
+            Kind::Condition | Kind::On => Separator::AndOr,
+            Kind::Using | Kind::Join | Kind::SetOp | Kind::Inline => {
+                return with_body(self.inline(&clause.body, indent));
+            }
+        };
+        let items = split_items(&clause.body, separator);
+
+        // Everything on the clause's line, if it fits and no comment forces a break.
+        if !clause.body.iter().any(|t| t.is_comment()) {
+            let joiner = if separator == Separator::Comma {
+                ", "
+            } else {
+                " "
+            };
+            let line = with_body(
+                items
+                    .iter()
+                    .map(|item| self.inline(&item.toks, indent))
+                    .collect::<Vec<_>>()
+                    .join(joiner),
+            );
+            if !line.contains('\n') && column + line.chars().count() <= self.config.max_line_length
+            {
+                return line;
+            }
+        }
+
+        let item_indent = indent + self.config.indent_width;
+        let mut out = head;
+        for (i, item) in items.iter().enumerate() {
+            // The first condition, or a lone list item, stays on the head's line.
+            let on_head_line = i == 0
+                && item.leading.is_empty()
+                && (separator == Separator::AndOr || items.len() == 1);
+            if on_head_line {
+                if !out.is_empty() {
+                    out.push(' ');
+                }
+            } else {
+                out.push('\n');
+                for comment in &item.leading {
+                    out.push_str(&pad(item_indent));
+                    out.push_str(&comment.text);
+                    out.push('\n');
+                }
+                out.push_str(&pad(item_indent));
+            }
+
+            let text = self.inline(&item.toks, if on_head_line { indent } else { item_indent });
+            let last = i + 1 == items.len();
+            match (separator, self.config.comma_style) {
+                (Separator::Comma, CommaStyle::Trailing) => {
+                    out.push_str(&text);
+                    if !last {
+                        out.push(',');
+                    }
+                }
+                (Separator::Comma, CommaStyle::Leading) => {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    out.push_str(&text);
+                }
+                (Separator::AndOr, _) => out.push_str(&text),
+            }
+            self.push_trailing(&mut out, &item.trailing, item_indent);
+        }
+        out
+    }
+
+    /// Tokens on one line, with subqueries broken out and indented below `indent`.
+    fn inline(&self, toks: &[&Tok], indent: usize) -> String {
+        let mut out = String::new();
+        let mut prev: Option<&Tok> = None;
+        let mut i = 0;
+
+        while i < toks.len() {
+            let tok = toks[i];
+            if prev.is_some_and(|p| !p.is_line_comment() && needs_space(p, tok)) {
+                out.push(' ');
+            }
+
+            if let Some(close) = subquery_end(toks, i) {
+                let inner = indent + self.config.indent_width;
+                out.push_str(&tok.text);
+                out.push('\n');
+                out.push_str(&pad(inner));
+                out.push_str(&self.query(&toks[i + 1..close], inner));
+                out.push('\n');
+                out.push_str(&pad(indent));
+                out.push_str(&toks[close].text);
+                prev = Some(toks[close]);
+                i = close + 1;
+                continue;
+            }
+
+            out.push_str(&tok.text);
+            if tok.is_line_comment() && i + 1 < toks.len() {
+                out.push('\n');
+                out.push_str(&pad(indent + self.config.indent_width));
+            }
+            prev = Some(tok);
+            i += 1;
+        }
+
+        out
+    }
+
+    /// Append comments that follow code on its line. Only the first can share the line;
+    /// anything after a line comment goes on its own line.
+    fn push_trailing(&self, out: &mut String, comments: &[&Tok], indent: usize) {
+        let mut after_line_comment = false;
+        for comment in comments {
+            if after_line_comment || comment.line_start {
+                out.push('\n');
+                out.push_str(&pad(indent));
+            } else {
+                out.push(' ');
+            }
+            out.push_str(&comment.text);
+            after_line_comment = comment.is_line_comment();
+        }
+    }
+}
+
+fn pad(width: usize) -> String {
+    " ".repeat(width)
+}
+
+/// Whether the formatter lays out a statement starting with these tokens.
+fn is_query(toks: &[&Tok]) -> bool {
+    toks.first().is_some_and(|t| {
+        t.token == Token::LParen
+            || [
+                "SELECT", "WITH", "INSERT", "REPLACE", "UPDATE", "DELETE", "VALUES",
+            ]
+            .iter()
+            .any(|k| t.is(k))
+    })
+}
+
+/// Whether to separate two tokens written on the same line. Spaces are dropped inside
+/// parentheses and before commas, added after commas, and otherwise kept as written, so
+/// `count(*)` and `a.b` stay as they are.
+fn needs_space(prev: &Tok, tok: &Tok) -> bool {
+    if matches!(
+        tok.token,
+        Token::Comma | Token::RParen | Token::RBracket | Token::SemiColon
+    ) {
+        return false;
+    }
+    if matches!(prev.token, Token::LParen | Token::LBracket) {
+        return false;
+    }
+    prev.token == Token::Comma || prev.is_comment() || tok.is_comment() || tok.space_before
+}
+
+/// Nesting change of a token: parentheses, brackets and `CASE ... END`.
+fn depth_change(tok: &Tok) -> i32 {
+    match tok.token {
+        Token::LParen | Token::LBracket => 1,
+        Token::RParen | Token::RBracket => -1,
+        _ if tok.is("CASE") => 1,
+        _ if tok.is("END") => -1,
+        _ => 0,
+    }
+}
+
+/// If `toks[i]` opens a parenthesized query, the index of the closing parenthesis.
+fn subquery_end(toks: &[&Tok], i: usize) -> Option<usize> {
+    if toks[i].token != Token::LParen {
+        return None;
+    }
+    let first = toks[i + 1..].iter().find(|t| !t.is_comment())?;
+    if !["SELECT", "WITH", "VALUES"].iter().any(|k| first.is(k)) {
+        return None;
+    }
+
+    let mut depth = 0;
+    for (j, tok) in toks.iter().enumerate().skip(i) {
+        match tok.token {
+            Token::LParen => depth += 1,
+            Token::RParen => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(j);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Split a query into clauses at the top level. Tokens before the first clause (such as
+/// a parenthesized query) form an [`Kind::Inline`] clause without a head.
+fn split_clauses<'t>(toks: &[&'t Tok]) -> Vec<Clause<'t>> {
+    let mut clauses = vec![Clause {
+        kind: Kind::Inline,
+        head: Vec::new(),
+        body: Vec::new(),
+        comments: Vec::new(),
+    }];
+    let mut depth = 0;
+    let mut i = 0;
+
+    while i < toks.len() {
+        if depth == 0 {
+            if let Some((kind, len)) = clause_head(&toks[i..], &clauses) {
+                // Comments on their own lines before the head belong to the new clause.
+                let current = clauses.last_mut().expect("at least one clause");
+                let comments = split_off_own_line_comments(&mut current.body);
+                clauses.push(Clause {
+                    kind,
+                    head: toks[i..i + len].to_vec(),
+                    body: Vec::new(),
+                    comments,
+                });
+                i += len;
+                continue;
+            }
+        }
+        depth += depth_change(toks[i]);
+        clauses
+            .last_mut()
+            .expect("at least one clause")
+            .body
+            .push(toks[i]);
+        i += 1;
+    }
+
+    if clauses[0].body.is_empty() && clauses[0].comments.is_empty() {
+        clauses.remove(0);
+    }
+    clauses
+}
+
+/// Whether a clause starts at `toks[0]`, and if so its kind and how many tokens its
+/// head spans.
+fn clause_head(toks: &[&Tok], clauses: &[Clause]) -> Option<(Kind, usize)> {
+    let current = clauses.last()?;
+    let at_start = clauses.len() == 1 && current.body.iter().all(|t| t.is_comment());
+    let after_with = current.starts_with("WITH");
+    let in_from = clauses
+        .iter()
+        .any(|c| c.starts_with("FROM") || c.starts_with("UPDATE") || c.starts_with("DELETE"));
+
+    if in_from {
+        if let Some(len) = join_len(toks) {
+            return Some((Kind::Join, len));
+        }
+    }
+
+    let (words, kind) = HEADS.iter().find(|(words, _)| {
+        words.len() <= toks.len() && words.iter().zip(toks).all(|(word, tok)| tok.is(word))
+    })?;
+    let first = words[0];
+
+    let allowed = match first {
+        _ if STATEMENT_HEADS.contains(&first) => at_start || after_with,
+        "VALUES" => at_start || current.starts_with("INSERT") || current.starts_with("REPLACE"),
+        "SET" => current.starts_with("UPDATE") || current.starts_with("ON"),
+        // `IS DISTINCT FROM`
+        "FROM" => !current.body.last().is_some_and(|t| t.is("DISTINCT")),
+        // BigQuery's `SELECT * EXCEPT (column)`
+        "EXCEPT" => toks
+            .get(words.len())
+            .is_none_or(|t| t.token != Token::LParen),
+        _ if *kind == Kind::On || *kind == Kind::Using => current.kind == Kind::Join,
+        _ => true,
+    };
+    allowed.then_some((*kind, words.len()))
+}
+
+/// The length of a join operator such as `LEFT OUTER JOIN` or `CROSS APPLY` at the
+/// start of `toks`.
+fn join_len(toks: &[&Tok]) -> Option<usize> {
+    let modifiers = toks
+        .iter()
+        .take_while(|t| JOIN_MODIFIERS.iter().any(|m| t.is(m)))
+        .count();
+    let last = toks.get(modifiers)?;
+    if last.is("JOIN") || (modifiers > 0 && last.is("APPLY")) {
+        Some(modifiers + 1)
+    } else {
+        None
+    }
+}
+
+/// Remove and return the comments at the end of `toks` from the first one on its own
+/// line onward. Comments sharing a line with the code before them stay.
+fn split_off_own_line_comments<'t>(toks: &mut Vec<&'t Tok>) -> Vec<&'t Tok> {
+    let run = toks.iter().rev().take_while(|t| t.is_comment()).count();
+    let start = toks.len() - run;
+    match toks[start..].iter().position(|t| t.line_start) {
+        Some(own_line) => toks.split_off(start + own_line),
+        None => Vec::new(),
+    }
+}
+
+/// Split a clause body into comma-separated items, or into conditions at top-level
+/// `AND`/`OR` (kept at the start of the condition they introduce).
+fn split_items<'t>(toks: &[&'t Tok], separator: Separator) -> Vec<Item<'t>> {
+    let mut parts: Vec<Vec<&Tok>> = vec![Vec::new()];
+    let mut depth = 0;
+    let mut in_between = false;
+
+    for &tok in toks {
+        if depth == 0 {
+            match separator {
+                Separator::Comma if tok.token == Token::Comma => {
+                    parts.push(Vec::new());
+                    continue;
+                }
+                // The `AND` of `BETWEEN x AND y` does not separate conditions.
+                Separator::AndOr if tok.is("AND") && in_between => in_between = false,
+                Separator::AndOr if tok.is("AND") || tok.is("OR") => {
+                    parts.push(vec![tok]);
+                    continue;
+                }
+                Separator::AndOr if tok.is("BETWEEN") => in_between = true,
+                _ => {}
+            }
+        }
+        depth += depth_change(tok);
+        parts.last_mut().expect("at least one part").push(tok);
+    }
+
+    let mut items: Vec<Item> = Vec::new();
+    // Comments on their own lines at the end of an item, which lead the next one.
+    let mut pending = Vec::new();
+    for mut toks in parts {
+        // Comments right after a separator, on its line, trail the previous item.
+        let same_line = toks
+            .iter()
+            .take_while(|t| t.is_comment() && !t.line_start)
+            .count();
+        match items.last_mut() {
+            Some(prev) if pending.is_empty() => prev.trailing.extend(toks.drain(..same_line)),
+            _ => pending.extend(toks.drain(..same_line)),
+        }
+
+        let mut leading = std::mem::take(&mut pending);
+        let own_line = toks.iter().take_while(|t| t.is_comment()).count();
+        leading.extend(toks.drain(..own_line));
+        pending = split_off_own_line_comments(&mut toks);
+        let run = toks.iter().rev().take_while(|t| t.is_comment()).count();
+        let trailing = toks.split_off(toks.len() - run);
+
+        items.push(Item {
+            leading,
+            toks,
+            trailing,
+        });
+    }
+    if let Some(last) = items.last_mut() {
+        last.trailing.extend(pending);
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlparser::dialect::{GenericDialect, PostgreSqlDialect};
+
+    fn format(sql: &str) -> String {
+        format_with(sql, &FormatConfig::default())
+    }
+
+    fn format_with(sql: &str, config: &FormatConfig) -> String {
+        format_sql(sql, &GenericDialect {}, config, &LintConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn test_short_clauses_stay_on_one_line() {
+        assert_eq!(
+            format("select id,name from users where id=1"),
+            "SELECT id, name\nFROM users\nWHERE id=1;\n"
+        );
+    }
+
+    #[test]
+    fn test_long_lists_break_one_item_per_line() {
+        let config = FormatConfig {
+            max_line_length: 20,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_with("SELECT id, first_name, last_name FROM users", &config),
+            "SELECT\n    id,\n    first_name,\n    last_name\nFROM users;\n"
+        );
+
+        let config = FormatConfig {
+            max_line_length: 20,
+            comma_style: CommaStyle::Leading,
+            indent_width: 2,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_with("SELECT id, first_name, last_name FROM users", &config),
+            "SELECT\n  id\n  , first_name\n  , last_name\nFROM users;\n"
+        );
+    }
+
+    #[test]
+    fn test_long_conditions_break_before_and_or() {
+        let config = FormatConfig {
+            max_line_length: 30,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_with(
+                "SELECT id FROM t WHERE a BETWEEN 1 AND 2 AND b = 'x' OR c IS NULL",
+                &config
+            ),
+            "SELECT id\nFROM t\nWHERE a BETWEEN 1 AND 2\n    AND b = 'x'\n    OR c IS NULL;\n"
+        );
+    }
+
+    #[test]
+    fn test_join_on_placement() {
+        let sql = "SELECT u.id FROM users u LEFT OUTER JOIN orders o ON o.user_id = u.id";
+        assert_eq!(
+            format(sql),
+            "SELECT u.id\nFROM users u\nLEFT OUTER JOIN orders o ON o.user_id = u.id;\n"
+        );
+
+        let config = FormatConfig {
+            join_on: JoinOn::NewLine,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_with(sql, &config),
+            "SELECT u.id\nFROM users u\nLEFT OUTER JOIN orders o\n    ON o.user_id = u.id;\n"
+        );
+    }
+
+    #[test]
+    fn test_subqueries_are_indented() {
+        assert_eq!(
+            format("select id from users where id in (select user_id from orders)"),
+            "SELECT id\nFROM users\nWHERE id IN (\n    SELECT user_id\n    FROM orders\n);\n"
+        );
+    }
+
+    #[test]
+    fn test_comments_are_preserved() {
+        let sql = "-- header\nselect id, -- the key\n  name\n  -- filter\nfrom users /* all */;\n";
+        let config = FormatConfig {
+            max_line_length: 20,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_with(sql, &config),
+            "-- header\nSELECT\n    id, -- the key\n    name\n-- filter\nFROM users; /* all */\n"
+        );
+    }
+
+    #[test]
+    fn test_statements_keep_blank_lines_between_them() {
+        assert_eq!(
+            format("select 1; -- one\nselect 2;\n\n\nselect 3"),
+            "SELECT 1; -- one\nSELECT 2;\n\nSELECT 3;\n"
+        );
+    }
+
+    #[test]
+    fn test_other_statements_keep_their_layout() {
+        let sql = "create table t (\n  id int primary key,\n  name text\n);\n";
+        assert_eq!(
+            format_sql(
+                sql,
+                &PostgreSqlDialect {},
+                &FormatConfig::default(),
+                &LintConfig::default()
+            )
+            .unwrap(),
+            "CREATE TABLE t (\n  id INT PRIMARY KEY,\n  name TEXT\n);\n"
+        );
+    }
+
+    #[test]
+    fn test_formatting_is_idempotent() {
+        let sql =
+            "with recent as (select * from orders where created_at > now() - interval '1 day') \
+                   select r.id, case when r.total > 100 then 'big' else 'small' end as size \
+                   from recent r join users u on u.id = r.user_id and u.active \
+                   group by r.id order by r.id desc limit 10";
+        let once = format(sql);
+        assert_eq!(format(&once), once);
+    }
+
+    #[test]
+    fn test_syntax_errors_are_rejected() {
+        let err = format_sql(
+            "SELECT * FROM users WHERE",
+            &GenericDialect {},
+            &FormatConfig::default(),
+            &LintConfig::default(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("syntax errors"));
+    }
+
+    #[test]
+    fn test_parse_options() {
+        assert_eq!(parse_comma_style("Leading"), CommaStyle::Leading);
+        assert_eq!(parse_comma_style("bogus"), CommaStyle::Trailing);
+        assert_eq!(parse_join_on("new-line"), JoinOn::NewLine);
+        assert_eq!(parse_join_on("same-line"), JoinOn::SameLine);
+    }
+}
@@ -0,0 +1,271 @@
+//! `--diff-base`: restrict a run to what changed relative to a git revision.
+//!
+//! Changes are read from the local repository by running `git`: the working tree
+//! (staged or not, plus untracked files) is compared to the merge base of the revision
+//! and `HEAD`, the same range a pull request shows.
+
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Lines of a file that changed, as inclusive 1-based ranges.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum FileChange {
+    /// The file is new to git, so all of it counts as changed.
+    All,
+    Lines(Vec<(usize, usize)>),
+}
+
+/// The files and lines that changed since a revision, keyed by canonical path.
+#[derive(Debug, Clone, Default)]
+pub struct Changes {
+    files: HashMap<PathBuf, FileChange>,
+}
+
+impl Changes {
+    /// Collect the changes between the merge base of `base` and `HEAD`, and the working
+    /// tree of the repository the current directory is in.
+    pub fn since(base: &str) -> Result<Self> {
+        let root = PathBuf::from(git(&["rev-parse", "--show-toplevel"])?.trim());
+        let merge_base = git(&["merge-base", base, "HEAD"])
+            .with_context(|| format!("Invalid diff base: {}", base))?;
+        let diff = git(&[
+            "-c",
+            "core.quotePath=false",
+            "diff",
+            "--no-ext-diff",
+            "--no-color",
+            "--no-renames",
+            "--unified=0",
+            merge_base.trim(),
+        ])?;
+        let untracked = git(&[
+            "-C",
+            &root.to_string_lossy(),
+            "-c",
+            "core.quotePath=false",
+            "ls-files",
+            "-z",
+            "--others",
+            "--exclude-standard",
+        ])?;
+
+        let mut files: HashMap<PathBuf, FileChange> = parse_diff(&diff)
+            .into_iter()
+            .map(|(path, lines)| (path, FileChange::Lines(lines)))
+            .collect();
+        files.extend(
+            untracked
+                .split_terminator('\0')
+                .map(|path| (PathBuf::from(path), FileChange::All)),
+        );
+
+        // Canonicalize so that paths match however they are spelled on the command line.
+        // Files that no longer exist cannot be processed anyway.
+        let files = files
+            .into_iter()
+            .filter_map(|(path, change)| Some((root.join(path).canonicalize().ok()?, change)))
+            .collect();
+        Ok(Self { files })
+    }
+
+    /// Whether `path` changed at all.
+    pub fn contains_file(&self, path: &Path) -> bool {
+        self.lookup(path).is_some()
+    }
+
+    /// Whether any of the lines `start..=end` of `path` changed.
+    pub fn touches(&self, path: &Path, start: usize, end: usize) -> bool {
+        match self.lookup(path) {
+            Some(FileChange::All) => true,
+            Some(FileChange::Lines(ranges)) => {
+                ranges.iter().any(|&(from, to)| from <= end && start <= to)
+            }
+            None => false,
+        }
+    }
+
+    fn lookup(&self, path: &Path) -> Option<&FileChange> {
+        self.files.get(&path.canonicalize().ok()?)
+    }
+}
+
+/// Run git in the current directory and return its stdout.
+fn git(args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The added or modified line ranges of each file in a `git diff --unified=0`, with
+/// paths relative to the repository root. A file whose changes are all deletions is
+/// listed without ranges; deleted files are left out.
+fn parse_diff(diff: &str) -> HashMap<PathBuf, Vec<(usize, usize)>> {
+    let mut files: HashMap<PathBuf, Vec<(usize, usize)>> = HashMap::new();
+    let mut current: Option<PathBuf> = None;
+
+    for line in diff.lines() {
+        if let Some(path) = line.strip_prefix("+++ ") {
+            current = unquote(path.trim_end_matches('\t'))
+                .strip_prefix("b/")
+                .map(PathBuf::from);
+            if let Some(path) = &current {
+                files.entry(path.clone()).or_default();
+            }
+        } else if let Some(hunk) = line.strip_prefix("@@ ") {
+            let (Some(path), Some(range)) = (&current, hunk_range(hunk)) else {
+                continue;
+            };
+            if let Some(range) = range {
+                files.entry(path.clone()).or_default().push(range);
+            }
+        }
+    }
+
+    files
+}
+
+/// A path as git prints it in a diff header. Paths with quotes, backslashes or control
+/// characters are written as C string literals even with `core.quotePath=false`, which
+/// only stops non-ASCII bytes from being escaped too.
+fn unquote(path: &str) -> String {
+    let Some(quoted) = path
+        .strip_prefix('"')
+        .and_then(|path| path.strip_suffix('"'))
+    else {
+        return path.to_string();
+    };
+    let mut bytes = Vec::new();
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        let byte = match chars.next() {
+            Some('a') => 0x07,
+            Some('b') => 0x08,
+            Some('t') => b'\t',
+            Some('n') => b'\n',
+            Some('v') => 0x0b,
+            Some('f') => 0x0c,
+            Some('r') => b'\r',
+            // Any other byte is escaped as three octal digits.
+            Some(digit @ '0'..='3') => {
+                let octal: String = std::iter::once(digit)
+                    .chain(chars.by_ref().take(2))
+                    .collect();
+                u8::from_str_radix(&octal, 8).unwrap_or(b'?')
+            }
+            Some(other) => other as u8,
+            None => break,
+        };
+        bytes.push(byte);
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// The new-file side of a hunk header such as `-3,2 +4,3 @@ ...`: `Some(None)` when
+/// the hunk only removes lines.
+fn hunk_range(hunk: &str) -> Option<Option<(usize, usize)>> {
+    let new = hunk.split_whitespace().find_map(|s| s.strip_prefix('+'))?;
+    let (start, count) = match new.split_once(',') {
+        Some((start, count)) => (start.parse::<usize>().ok()?, count.parse::<usize>().ok()?),
+        None => (new.parse::<usize>().ok()?, 1),
+    };
+    Some((count > 0).then(|| (start, start + count - 1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "\
+diff --git a/sql/a.sql b/sql/a.sql
+index 1111111..2222222 100644
+--- a/sql/a.sql
++++ b/sql/a.sql
+@@ -2 +2 @@ SELECT 1;
+-select 2;
++SELECT 2;
+@@ -10,0 +11,3 @@ SELECT 3;
++SELECT 4;
++SELECT 5;
++SELECT 6;
+diff --git a/b.sql b/b.sql
+index 3333333..4444444 100644
+--- a/b.sql
++++ b/b.sql
+@@ -4,2 +3,0 @@ SELECT 1;
+-SELECT 2;
+-SELECT 3;
+diff --git a/gone.sql b/gone.sql
+deleted file mode 100644
+--- a/gone.sql
++++ /dev/null
+@@ -1 +0,0 @@
+-SELECT 1;
+";
+
+    #[test]
+    fn test_parse_diff_collects_added_ranges() {
+        let files = parse_diff(DIFF);
+        assert_eq!(files[Path::new("sql/a.sql")], vec![(2, 2), (11, 13)]);
+        assert_eq!(files[Path::new("b.sql")], vec![]);
+        assert!(!files.contains_key(Path::new("gone.sql")));
+    }
+
+    #[test]
+    fn test_parse_diff_unquotes_paths() {
+        let diff = "\
++++ \"b/caf\\303\\251 \\\"bar\\\".sql\"
+@@ -1 +1 @@
++++ \"b/back\\\\slash.sql\"
+@@ -1 +1 @@
++++ b/with space.sql\t
+@@ -1 +1 @@
+";
+        let files = parse_diff(diff);
+        assert_eq!(files[Path::new("café \"bar\".sql")], vec![(1, 1)]);
+        assert_eq!(files[Path::new("back\\slash.sql")], vec![(1, 1)]);
+        assert_eq!(files[Path::new("with space.sql")], vec![(1, 1)]);
+    }
+
+    #[test]
+    fn test_hunk_range() {
+        assert_eq!(hunk_range("-1 +1 @@"), Some(Some((1, 1))));
+        assert_eq!(hunk_range("-0,0 +1,5 @@"), Some(Some((1, 5))));
+        assert_eq!(hunk_range("-3,2 +2,0 @@"), Some(None));
+        assert_eq!(hunk_range("garbage"), None);
+    }
+
+    #[test]
+    fn test_touches_overlapping_lines() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("a.sql");
+        std::fs::write(&path, "").unwrap();
+        let changes = Changes {
+            files: HashMap::from([(
+                path.canonicalize().unwrap(),
+                FileChange::Lines(vec![(3, 4)]),
+            )]),
+        };
+
+        assert!(changes.contains_file(&path));
+        assert!(changes.touches(&path, 4, 4));
+        assert!(changes.touches(&path, 1, 3));
+        assert!(!changes.touches(&path, 5, 9));
+        assert!(!changes.touches(&dir.path().join("b.sql"), 3, 3));
+    }
+}
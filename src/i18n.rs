@@ -1,3 +1,4 @@
+use crate::detect::Detection;
 use sys_locale::get_locale;
 
 pub fn is_japanese_locale() -> bool {
@@ -32,6 +33,14 @@ impl Messages {
         }
     }
 
+    /// Code of the language messages are written in (`"en"` or `"ja"`).
+    pub fn lang(&self) -> &'static str {
+        match self.lang {
+            Lang::En => "en",
+            Lang::Ja => "ja",
+        }
+    }
+
     pub fn syntax_error(&self, line: usize, col: usize, msg: &str) -> String {
         match self.lang {
             Lang::Ja => format!("構文エラー ({}行目, {}列目): {}", line, col, msg),
@@ -39,6 +48,32 @@ impl Messages {
         }
     }
 
+    pub fn version_error(&self, line: usize, col: usize, msg: &str) -> String {
+        match self.lang {
+            Lang::Ja => format!("バージョンエラー ({}行目, {}列目): {}", line, col, msg),
+            Lang::En => format!("Version error (line {}, col {}): {}", line, col, msg),
+        }
+    }
+
+    pub fn unsupported_feature(
+        &self,
+        feature: &str,
+        database: &str,
+        since: &str,
+        target: &str,
+    ) -> String {
+        match self.lang {
+            Lang::Ja => format!(
+                "{} は {} {} 以降でのみ使用できます (対象バージョン: {})",
+                feature, database, since, target
+            ),
+            Lang::En => format!(
+                "{} requires {} {} or later (target: {})",
+                feature, database, since, target
+            ),
+        }
+    }
+
     pub fn file_ok(&self, path: &str) -> String {
         match self.lang {
             Lang::Ja => format!("✓ {} - 問題なし", path),
@@ -60,6 +95,55 @@ impl Messages {
         }
     }
 
+    // Portability (`check --dialects`)
+    pub fn file_not_portable(&self, path: &str, statements: usize) -> String {
+        match self.lang {
+            Lang::Ja => format!("✗ {} - {}件の文が一部の方言で構文エラー", path, statements),
+            Lang::En => format!("✗ {} - {} statement(s) not portable", path, statements),
+        }
+    }
+
+    pub fn statement_at(&self, line: usize, excerpt: &str) -> String {
+        match self.lang {
+            Lang::Ja => format!("{}行目の文: {}", line, excerpt),
+            Lang::En => format!("Statement at line {}: {}", line, excerpt),
+        }
+    }
+
+    pub fn syntax_error_in(&self, dialect: &str, line: usize, col: usize, msg: &str) -> String {
+        match self.lang {
+            Lang::Ja => format!("{} ({}行目, {}列目): {}", dialect, line, col, msg),
+            Lang::En => format!("{} (line {}, col {}): {}", dialect, line, col, msg),
+        }
+    }
+
+    pub fn rejected_by(&self, dialect: &str, msg: &str) -> String {
+        match self.lang {
+            Lang::Ja => format!("{} では構文エラー: {}", dialect, msg),
+            Lang::En => format!("Rejected by {}: {}", dialect, msg),
+        }
+    }
+
+    pub fn portability_matrix(&self) -> String {
+        match self.lang {
+            Lang::Ja => "方言ごとの構文エラーになる文の数:".to_string(),
+            Lang::En => "Statements rejected per dialect:".to_string(),
+        }
+    }
+
+    pub fn portability_summary(&self, files: usize, statements: usize, rejected: usize) -> String {
+        match self.lang {
+            Lang::Ja => format!(
+                "\n合計: {}ファイル, {}件の文, うち{}件が移植不可",
+                files, statements, rejected
+            ),
+            Lang::En => format!(
+                "\nTotal: {} file(s), {} statement(s), {} not portable",
+                files, statements, rejected
+            ),
+        }
+    }
+
     pub fn would_fix(&self, path: &str) -> String {
         match self.lang {
             Lang::Ja => format!("修正予定: {}", path),
@@ -67,6 +151,13 @@ impl Messages {
         }
     }
 
+    pub fn would_fix_count(&self, files: usize) -> String {
+        match self.lang {
+            Lang::Ja => format!("\n修正が必要なファイル: {}件", files),
+            Lang::En => format!("\n{} file(s) would be fixed", files),
+        }
+    }
+
     pub fn fixed(&self, path: &str) -> String {
         match self.lang {
             Lang::Ja => format!("修正完了: {}", path),
@@ -82,6 +173,27 @@ impl Messages {
         }
     }
 
+    pub fn function_case_error(&self, actual: &str, expected: &str) -> String {
+        match self.lang {
+            Lang::Ja => format!("関数名 '{}' は '{}' であるべきです", actual, expected),
+            Lang::En => format!("Function name '{}' should be '{}'", actual, expected),
+        }
+    }
+
+    pub fn type_case_error(&self, actual: &str, expected: &str) -> String {
+        match self.lang {
+            Lang::Ja => format!("データ型 '{}' は '{}' であるべきです", actual, expected),
+            Lang::En => format!("Data type '{}' should be '{}'", actual, expected),
+        }
+    }
+
+    pub fn identifier_case_error(&self, actual: &str, expected: &str) -> String {
+        match self.lang {
+            Lang::Ja => format!("識別子 '{}' は '{}' であるべきです", actual, expected),
+            Lang::En => format!("Identifier '{}' should be '{}'", actual, expected),
+        }
+    }
+
     pub fn no_select_star_error(&self) -> String {
         match self.lang {
             Lang::Ja => {
@@ -105,6 +217,13 @@ impl Messages {
         }
     }
 
+    pub fn unused_suppression_error(&self, directive: &str) -> String {
+        match self.lang {
+            Lang::Ja => format!("抑制コメント '{}' は何も抑制していません", directive),
+            Lang::En => format!("Suppression '{}' does not suppress anything", directive),
+        }
+    }
+
     pub fn lint_warning(&self, rule: &str, line: usize, col: usize, msg: &str) -> String {
         match self.lang {
             Lang::Ja => format!("  [{}] {}行目:{}列目 - {}", rule, line, col, msg),
@@ -119,6 +238,237 @@ impl Messages {
         }
     }
 
+    pub fn cache_cleaned(&self, path: &str) -> String {
+        match self.lang {
+            Lang::Ja => format!("キャッシュ {} を削除しました", path),
+            Lang::En => format!("Removed cache {}", path),
+        }
+    }
+
+    // Transpilation
+    pub fn transpiled(&self, path: &str, out: &str) -> String {
+        match self.lang {
+            Lang::Ja => format!("変換完了: {} -> {}", path, out),
+            Lang::En => format!("Transpiled: {} -> {}", path, out),
+        }
+    }
+
+    pub fn file_untranslated(&self, path: &str, count: usize) -> String {
+        match self.lang {
+            Lang::Ja => format!("⚠ {} - {}件の構文を変換できませんでした", path, count),
+            Lang::En => format!(
+                "⚠ {} - {} construct(s) could not be translated",
+                path, count
+            ),
+        }
+    }
+
+    pub fn untranslated(&self, line: usize, col: usize, msg: &str) -> String {
+        match self.lang {
+            Lang::Ja => format!("未変換 ({}行目, {}列目): {}", line, col, msg),
+            Lang::En => format!("Not translated (line {}, col {}): {}", line, col, msg),
+        }
+    }
+
+    pub fn no_equivalent(&self, construct: &str, database: &str) -> String {
+        match self.lang {
+            Lang::Ja => format!(
+                "{} に相当する構文が {} にないため、そのまま残しました",
+                construct, database
+            ),
+            Lang::En => format!("{} has no {} equivalent; left as is", construct, database),
+        }
+    }
+
+    pub fn translation_does_not_parse(&self, database: &str, error: &str) -> String {
+        match self.lang {
+            Lang::Ja => format!("変換後の文を {} として解析できません: {}", database, error),
+            Lang::En => format!(
+                "The translated statement does not parse as {}: {}",
+                database, error
+            ),
+        }
+    }
+
+    pub fn pipes_as_concat(&self, database: &str) -> String {
+        match self.lang {
+            Lang::Ja => format!(
+                "MySQL の || は sql_mode に PIPES_AS_CONCAT がなければ OR ですが、{} では文字列連結として残しました",
+                database
+            ),
+            Lang::En => format!(
+                "|| is OR in MySQL unless sql_mode has PIPES_AS_CONCAT; left as {} string concatenation",
+                database
+            ),
+        }
+    }
+
+    pub fn formatted(&self, path: &str) -> String {
+        match self.lang {
+            Lang::Ja => format!("整形完了: {}", path),
+            Lang::En => format!("Formatted: {}", path),
+        }
+    }
+
+    pub fn dialect_detected(&self, path: &str, detection: &Detection) -> String {
+        let (dialect, confidence) = (&detection.dialect, detection.confidence * 100.0);
+        match (self.lang, detection.parsed) {
+            (Lang::Ja, true) => format!(
+                "{}: 方言を {} と判定しました (確信度 {:.0}%)",
+                path, dialect, confidence
+            ),
+            (Lang::Ja, false) => format!(
+                "{}: どの方言でも構文エラーになるため {} を使用します (確信度 {:.0}%)",
+                path, dialect, confidence
+            ),
+            (Lang::En, true) => format!(
+                "{}: detected dialect {} ({:.0}% confidence)",
+                path, dialect, confidence
+            ),
+            (Lang::En, false) => format!(
+                "{}: no dialect parses this file cleanly, using {} ({:.0}% confidence)",
+                path, dialect, confidence
+            ),
+        }
+    }
+
+    pub fn baseline_written(&self, path: &str, warnings: usize) -> String {
+        match self.lang {
+            Lang::Ja => format!(
+                "{} 件の警告をベースライン {} に記録しました",
+                warnings, path
+            ),
+            Lang::En => format!("Recorded {} warning(s) in baseline {}", warnings, path),
+        }
+    }
+
+    pub fn watching(&self) -> String {
+        match self.lang {
+            Lang::Ja => "変更を監視しています... (Ctrl+C で終了)".to_string(),
+            Lang::En => "Watching for changes... (press Ctrl+C to stop)".to_string(),
+        }
+    }
+
+    // Editor integration
+    pub fn fix_action_title(&self, rule: &str, original: &str, replacement: &str) -> String {
+        match (self.lang, rule) {
+            (Lang::Ja, "trailing-semicolon") => "文末にセミコロンを追加".to_string(),
+            (Lang::En, "trailing-semicolon") => "Add trailing semicolon".to_string(),
+            (Lang::Ja, _) => format!("'{}' を '{}' に変更", original, replacement),
+            (Lang::En, _) => format!("Change '{}' to '{}'", original, replacement),
+        }
+    }
+
+    pub fn fix_all_action_title(&self) -> String {
+        match self.lang {
+            Lang::Ja => "自動修正可能な問題をすべて修正".to_string(),
+            Lang::En => "Fix all auto-fixable problems".to_string(),
+        }
+    }
+
+    // Rule metadata
+    pub fn rule_description(&self, rule: &str) -> String {
+        match (self.lang, rule) {
+            (Lang::Ja, "syntax-error") => "SQLの構文エラー".to_string(),
+            (Lang::En, "syntax-error") => "SQL syntax error".to_string(),
+            (Lang::Ja, "target-version") => "対象バージョンで使えない構文".to_string(),
+            (Lang::En, "target-version") => {
+                "Construct the target database version does not support".to_string()
+            }
+            (Lang::Ja, "keyword-case") => "キーワードの大文字・小文字を統一する".to_string(),
+            (Lang::En, "keyword-case") => "Enforce consistent keyword case".to_string(),
+            (Lang::Ja, "function-case") => "関数名の大文字・小文字を統一する".to_string(),
+            (Lang::En, "function-case") => "Enforce consistent function name case".to_string(),
+            (Lang::Ja, "type-case") => "データ型の大文字・小文字を統一する".to_string(),
+            (Lang::En, "type-case") => "Enforce consistent data type case".to_string(),
+            (Lang::Ja, "identifier-case") => "識別子の大文字・小文字を統一する".to_string(),
+            (Lang::En, "identifier-case") => "Enforce consistent identifier case".to_string(),
+            (Lang::Ja, "no-select-star") => "SELECT * を禁止する".to_string(),
+            (Lang::En, "no-select-star") => "Disallow SELECT *".to_string(),
+            (Lang::Ja, "require-table-alias") => "テーブルにエイリアスを必須とする".to_string(),
+            (Lang::En, "require-table-alias") => "Require table aliases".to_string(),
+            (Lang::Ja, "trailing-semicolon") => "文末のセミコロンを必須とする".to_string(),
+            (Lang::En, "trailing-semicolon") => "Require a trailing semicolon".to_string(),
+            (Lang::Ja, "unused-suppression") => "使われていない抑制コメントを報告する".to_string(),
+            (Lang::En, "unused-suppression") => "Report unused suppression comments".to_string(),
+            (_, other) => other.to_string(),
+        }
+    }
+
+    pub fn rule_help(&self, rule: &str) -> String {
+        match (self.lang, rule) {
+            (Lang::Ja, "syntax-error") => {
+                "指定したダイアレクトでSQLを解析できません。エラー位置付近を確認してください"
+                    .to_string()
+            }
+            (Lang::En, "syntax-error") => {
+                "The SQL could not be parsed with the selected dialect. Check the reported position"
+                    .to_string()
+            }
+            (Lang::Ja, "target-version") => {
+                "対象バージョンより新しいデータベースが必要です。構文を書き換えるか対象バージョンを見直してください"
+                    .to_string()
+            }
+            (Lang::En, "target-version") => {
+                "The construct needs a newer database than the target version. Rewrite it or raise the target"
+                    .to_string()
+            }
+            (Lang::Ja, "keyword-case") => {
+                "キーワードを設定された大文字・小文字で記述してください (sqlex fix で自動修正できます)"
+                    .to_string()
+            }
+            (Lang::En, "keyword-case") => {
+                "Write keywords in the configured case (auto-fixable with sqlex fix)".to_string()
+            }
+            (Lang::Ja, "function-case") => {
+                "関数名を設定された大文字・小文字で記述してください (sqlex fix で自動修正できます)"
+                    .to_string()
+            }
+            (Lang::En, "function-case") => {
+                "Write function names in the configured case (auto-fixable with sqlex fix)"
+                    .to_string()
+            }
+            (Lang::Ja, "type-case") => {
+                "データ型を設定された大文字・小文字で記述してください (sqlex fix で自動修正できます)"
+                    .to_string()
+            }
+            (Lang::En, "type-case") => {
+                "Write data types in the configured case (auto-fixable with sqlex fix)".to_string()
+            }
+            (Lang::Ja, "identifier-case") => {
+                "引用符なしの識別子を設定された大文字・小文字で記述してください (sqlex fix で自動修正できます)"
+                    .to_string()
+            }
+            (Lang::En, "identifier-case") => {
+                "Write unquoted identifiers in the configured case (auto-fixable with sqlex fix)"
+                    .to_string()
+            }
+            (Lang::Ja, "no-select-star") => {
+                "取得するカラムを明示的に列挙してください".to_string()
+            }
+            (Lang::En, "no-select-star") => "List the selected columns explicitly".to_string(),
+            (Lang::Ja, "require-table-alias") => {
+                "FROM 句や JOIN 句のテーブルにエイリアスを付けてください".to_string()
+            }
+            (Lang::En, "require-table-alias") => {
+                "Give every table in FROM and JOIN clauses an alias".to_string()
+            }
+            (Lang::Ja, "trailing-semicolon") => {
+                "最後の文をセミコロンで終えてください (sqlex fix で自動修正できます)".to_string()
+            }
+            (Lang::En, "trailing-semicolon") => {
+                "End the last statement with a semicolon (auto-fixable with sqlex fix)".to_string()
+            }
+            (Lang::Ja, "unused-suppression") => {
+                "該当する警告がなくなった sqlex-disable コメントを削除してください".to_string()
+            }
+            (Lang::En, "unused-suppression") => {
+                "Remove sqlex-disable comments that no longer match any warning".to_string()
+            }
+            (_, other) => other.to_string(),
+        }
+    }
+
     // Hint messages
     pub fn hint_trailing_comma(&self, line: usize) -> String {
         match self.lang {
@@ -196,6 +546,14 @@ mod tests {
             Messages::new("ja").syntax_error(3, 5, "boom"),
             "構文エラー (3行目, 5列目): boom"
         );
+        assert_eq!(
+            Messages::new("en").version_error(3, 5, "boom"),
+            "Version error (line 3, col 5): boom"
+        );
+        assert_eq!(
+            Messages::new("ja").version_error(3, 5, "boom"),
+            "バージョンエラー (3行目, 5列目): boom"
+        );
     }
 
     #[test]
@@ -226,6 +584,8 @@ mod tests {
         assert_eq!(ja.would_fix("q.sql"), "修正予定: q.sql");
         assert_eq!(en.fixed("q.sql"), "Fixed: q.sql");
         assert_eq!(ja.fixed("q.sql"), "修正完了: q.sql");
+        assert_eq!(en.would_fix_count(2), "\n2 file(s) would be fixed");
+        assert_eq!(ja.would_fix_count(2), "\n修正が必要なファイル: 2件");
     }
 
     #[test]
@@ -240,6 +600,18 @@ mod tests {
             ja.keyword_case_error("select", "SELECT"),
             "キーワード 'select' は 'SELECT' であるべきです"
         );
+        assert_eq!(
+            en.function_case_error("COUNT", "count"),
+            "Function name 'COUNT' should be 'count'"
+        );
+        assert_eq!(
+            ja.type_case_error("int", "INT"),
+            "データ型 'int' は 'INT' であるべきです"
+        );
+        assert_eq!(
+            en.identifier_case_error("Users", "users"),
+            "Identifier 'Users' should be 'users'"
+        );
         assert_eq!(
             en.no_select_star_error(),
             "Avoid SELECT *. Specify columns explicitly"
@@ -250,6 +622,13 @@ mod tests {
             "Table 'users' should have an alias"
         );
         assert!(ja.require_table_alias_error("users").contains("users"));
+        assert_eq!(
+            en.unused_suppression_error("sqlex-disable-next-line no-select-star"),
+            "Suppression 'sqlex-disable-next-line no-select-star' does not suppress anything"
+        );
+        assert!(ja
+            .unused_suppression_error("sqlex-disable-file")
+            .contains("sqlex-disable-file"));
     }
 
     #[test]
@@ -264,6 +643,122 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_unsupported_feature_both_langs() {
+        assert_eq!(
+            Messages::new("en").unsupported_feature("WITH", "MySQL", "8.0", "5.7"),
+            "WITH requires MySQL 8.0 or later (target: 5.7)"
+        );
+        assert!(Messages::new("ja")
+            .unsupported_feature("WITH", "MySQL", "8.0", "5.7")
+            .contains("MySQL 8.0"));
+    }
+
+    #[test]
+    fn test_portability_messages_both_langs() {
+        let en = Messages::new("en");
+        let ja = Messages::new("ja");
+        assert_eq!(
+            en.file_not_portable("a.sql", 2),
+            "✗ a.sql - 2 statement(s) not portable"
+        );
+        assert_eq!(
+            en.syntax_error_in("sqlite", 3, 7, "msg"),
+            "sqlite (line 3, col 7): msg"
+        );
+        assert_eq!(en.rejected_by("sqlite", "msg"), "Rejected by sqlite: msg");
+        assert_eq!(
+            en.portability_summary(1, 4, 2),
+            "\nTotal: 1 file(s), 4 statement(s), 2 not portable"
+        );
+        assert!(ja.statement_at(3, "SELECT 1;").contains("3行目"));
+        assert!(ja.rejected_by("sqlite", "msg").contains("sqlite"));
+    }
+
+    #[test]
+    fn test_transpile_messages_both_langs() {
+        let en = Messages::new("en");
+        let ja = Messages::new("ja");
+        assert_eq!(
+            en.file_untranslated("a.sql", 2),
+            "⚠ a.sql - 2 construct(s) could not be translated"
+        );
+        assert_eq!(
+            en.untranslated(3, 7, &en.no_equivalent("GROUP_CONCAT", "PostgreSQL")),
+            "Not translated (line 3, col 7): GROUP_CONCAT has no PostgreSQL equivalent; left as is"
+        );
+        assert_eq!(
+            en.translation_does_not_parse("PostgreSQL", "msg"),
+            "The translated statement does not parse as PostgreSQL: msg"
+        );
+        assert_eq!(
+            en.pipes_as_concat("PostgreSQL"),
+            "|| is OR in MySQL unless sql_mode has PIPES_AS_CONCAT; left as PostgreSQL string concatenation"
+        );
+        assert_eq!(
+            en.transpiled("a.sql", "out/a.sql"),
+            "Transpiled: a.sql -> out/a.sql"
+        );
+        assert!(ja.untranslated(3, 7, "msg").contains("3行目"));
+        assert!(ja
+            .no_equivalent("GROUP_CONCAT", "PostgreSQL")
+            .contains("GROUP_CONCAT"));
+        assert!(ja.pipes_as_concat("PostgreSQL").contains("PIPES_AS_CONCAT"));
+    }
+
+    #[test]
+    fn test_dialect_detected_both_langs() {
+        let detection = Detection {
+            dialect: "mysql".to_string(),
+            confidence: 0.914,
+            parsed: true,
+        };
+        assert_eq!(
+            Messages::new("en").dialect_detected("a.sql", &detection),
+            "a.sql: detected dialect mysql (91% confidence)"
+        );
+        assert!(Messages::new("ja")
+            .dialect_detected("a.sql", &detection)
+            .contains("mysql"));
+        let fallback = Detection {
+            parsed: false,
+            ..detection
+        };
+        assert!(Messages::new("en")
+            .dialect_detected("a.sql", &fallback)
+            .contains("no dialect parses"));
+    }
+
+    #[test]
+    fn test_fix_action_titles_both_langs() {
+        let en = Messages::new("en");
+        let ja = Messages::new("ja");
+        assert_eq!(
+            en.fix_action_title("keyword-case", "select", "SELECT"),
+            "Change 'select' to 'SELECT'"
+        );
+        assert_eq!(
+            en.fix_action_title("trailing-semicolon", "", ";\n"),
+            "Add trailing semicolon"
+        );
+        assert!(ja
+            .fix_action_title("keyword-case", "select", "SELECT")
+            .contains("SELECT"));
+        assert_eq!(ja.fix_all_action_title(), "自動修正可能な問題をすべて修正");
+    }
+
+    #[test]
+    fn test_rule_metadata_both_langs() {
+        let en = Messages::new("en");
+        let ja = Messages::new("ja");
+        assert_eq!(en.rule_description("no-select-star"), "Disallow SELECT *");
+        assert_eq!(ja.rule_description("no-select-star"), "SELECT * を禁止する");
+        assert!(en.rule_help("keyword-case").contains("sqlex fix"));
+        assert!(ja.rule_help("trailing-semicolon").contains("セミコロン"));
+        // Unknown rules fall back to the rule id.
+        assert_eq!(en.rule_description("custom"), "custom");
+    }
+
     #[test]
     fn test_hint_messages_both_langs() {
         let en = Messages::new("en");
@@ -0,0 +1,56 @@
+//! SQL syntax checker and linter.
+//!
+//! The `sqlex` binary is built on this crate, and the same checks are available for
+//! embedding in other tools without shelling out:
+//!
+//! ```
+//! use sqlex::{check_sql, fix_content, get_dialect, LintConfig, Linter, Messages};
+//!
+//! let dialect = get_dialect("postgres").unwrap();
+//!
+//! // Syntax errors, with 1-based line/column positions.
+//! let errors = check_sql("SELECT id FROM users WHERE;", dialect.as_ref());
+//! assert_eq!(errors.len(), 1);
+//!
+//! // Style warnings, identified by rule name.
+//! let linter = Linter::new(LintConfig::default());
+//! let warnings = linter.lint("select id from users", dialect.as_ref(), &Messages::new("en"));
+//! assert!(warnings.iter().any(|w| w.rule == "keyword-case"));
+//!
+//! // Auto-fix: keyword case and trailing semicolon, preserving layout.
+//! let fixed = fix_content("select id from users", dialect.as_ref(), &LintConfig::default()).unwrap();
+//! assert_eq!(fixed, "SELECT id FROM users;\n");
+//! ```
+
+mod baseline;
+mod cache;
+mod casing;
+mod checker;
+mod cli;
+mod config;
+mod detect;
+mod error;
+mod formatter;
+mod git;
+mod highlight;
+mod hints;
+mod i18n;
+mod linter;
+mod lsp;
+mod portability;
+mod report;
+mod suppression;
+mod target;
+mod transpile;
+mod watch;
+
+pub use checker::{
+    apply_edits, check_sql, fix_content, fix_edits, get_dialect, Edit, SyntaxError, DIALECTS,
+};
+pub use cli::run;
+pub use error::SqlexError;
+pub use formatter::{format_sql, CommaStyle, FormatConfig, JoinOn};
+pub use i18n::Messages;
+pub use linter::{is_sql_keyword, KeywordCase, LintConfig, LintError, Linter};
+pub use report::{Diagnostic, Severity};
+pub use transpile::{transpile_sql, Reason, Transpiled, Untranslated, TRANSLATIONS};
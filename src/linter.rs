@@ -1,43 +1,104 @@
+use crate::casing::{miscased, Role};
 use crate::i18n::Messages;
+use crate::suppression::{Suppressions, UNUSED_SUPPRESSION};
+use serde::{Deserialize, Serialize};
 use sqlparser::ast::{SelectItem, SetExpr, Spanned, Statement, TableFactor, TableWithJoins};
 use sqlparser::dialect::Dialect;
+use sqlparser::keywords::ALL_KEYWORDS;
 use sqlparser::parser::Parser;
-use sqlparser::tokenizer::{Span, Token, Tokenizer};
-
-#[derive(Debug, Clone)]
+use sqlparser::tokenizer::{Span, TokenWithSpan, Tokenizer};
+
+/// Ids of every rule the linter can report.
+pub const RULES: &[&str] = &[
+    "keyword-case",
+    "function-case",
+    "type-case",
+    "identifier-case",
+    "no-select-star",
+    "require-table-alias",
+    "trailing-semicolon",
+    UNUSED_SUPPRESSION,
+];
+
+/// A style warning reported by [`Linter::lint`].
+///
+/// Positions are 1-based; the end position is exclusive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LintError {
     pub rule: String,
     pub line: usize,
     pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
     pub message: String,
 }
 
 #[derive(Debug, Clone)]
 pub struct LintConfig {
     pub keyword_case: KeywordCase,
+    /// Case of function names in calls.
+    pub function_case: KeywordCase,
+    /// Case of data type names; `None` follows `keyword_case`. See [`LintConfig::type_case`].
+    pub type_case: Option<KeywordCase>,
+    /// Case of unquoted identifiers: tables, columns, aliases and so on.
+    pub identifier_case: KeywordCase,
     pub no_select_star: bool,
     pub require_table_alias: bool,
     pub trailing_semicolon: bool,
+    /// Words to treat as keywords even though sqlparser does not know them.
+    pub extra_keywords: Vec<String>,
+    /// Words never to treat as keywords, e.g. because they are used as names.
+    pub exclude_keywords: Vec<String>,
 }
 
+/// How a case rule wants words written. Used by `keyword-case`, `function-case`,
+/// `type-case` and `identifier-case` alike.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum KeywordCase {
     Upper,
     Lower,
+    /// First letter upper, the rest lower.
+    Capitalize,
+    /// Whichever of the above the first word of the kind in the file is written in.
+    Consistent,
     Ignore,
 }
 
+pub fn parse_keyword_case(name: &str) -> KeywordCase {
+    match name.to_lowercase().as_str() {
+        "lower" => KeywordCase::Lower,
+        "capitalize" => KeywordCase::Capitalize,
+        "consistent" => KeywordCase::Consistent,
+        "ignore" => KeywordCase::Ignore,
+        // "upper" and any unrecognized value default to upper.
+        _ => KeywordCase::Upper,
+    }
+}
+
 impl Default for LintConfig {
     fn default() -> Self {
         Self {
             keyword_case: KeywordCase::Upper,
+            function_case: KeywordCase::Ignore,
+            type_case: None,
+            identifier_case: KeywordCase::Ignore,
             no_select_star: true,
             require_table_alias: false,
             trailing_semicolon: true,
+            extra_keywords: Vec::new(),
+            exclude_keywords: Vec::new(),
         }
     }
 }
 
+impl LintConfig {
+    /// The case data type names should be written in: `type_case` if set, otherwise
+    /// the keyword case, since data types are written like keywords.
+    pub fn type_case(&self) -> KeywordCase {
+        self.type_case.unwrap_or(self.keyword_case)
+    }
+}
+
 pub struct Linter {
     config: LintConfig,
 }
@@ -47,13 +108,17 @@ impl Linter {
         Self { config }
     }
 
+    /// Lint `sql`, leaving out warnings silenced by `sqlex-disable` comments (see
+    /// [`crate::suppression`]) and reporting the comments that silenced nothing.
     pub fn lint(&self, sql: &str, dialect: &dyn Dialect, messages: &Messages) -> Vec<LintError> {
         let mut errors = Vec::new();
+        // Tokenize with location so keywords and suppression comments carry exact positions.
+        let tokens = Tokenizer::new(dialect, sql)
+            .tokenize_with_location()
+            .unwrap_or_default();
 
-        // Keyword case check using tokenizer
-        if self.config.keyword_case != KeywordCase::Ignore {
-            errors.extend(self.check_keyword_case(sql, dialect, messages));
-        }
+        // Case checks, by the role each word plays in the parsed statements
+        errors.extend(self.check_case(&tokens, dialect, messages));
 
         // AST-based checks
         if let Ok(statements) = Parser::parse_sql(dialect, sql) {
@@ -72,58 +137,41 @@ impl Linter {
             errors.extend(self.check_trailing_semicolon(sql, messages));
         }
 
+        let mut suppressions = Suppressions::parse(&tokens);
+        errors.retain(|e| !suppressions.is_suppressed(&e.rule, e.line, e.column));
+        errors.extend(suppressions.unused(messages));
         errors
     }
 
-    fn check_keyword_case(
+    fn check_case(
         &self,
-        sql: &str,
+        tokens: &[TokenWithSpan],
         dialect: &dyn Dialect,
         messages: &Messages,
     ) -> Vec<LintError> {
-        let mut errors = Vec::new();
-        let mut tokenizer = Tokenizer::new(dialect, sql);
-
-        // Tokenize with location so each keyword carries an exact (line, column),
-        // matching the fix command and avoiding the previous approximate scan.
-        if let Ok(tokens) = tokenizer.tokenize_with_location() {
-            for token_with_span in tokens {
-                let Token::Word(word) = &token_with_span.token else {
-                    continue;
-                };
-                // Quoted identifiers that happen to match a keyword are not keywords.
-                if word.quote_style.is_some() || !is_sql_keyword(&word.value) {
-                    continue;
-                }
-
-                // This method is only invoked for Upper/Lower (the caller skips
-                // Ignore), so treat anything that is not Upper as Lower.
-                let want_upper = self.config.keyword_case == KeywordCase::Upper;
-                let conforms = if want_upper {
-                    word.value.chars().all(|c| c.is_uppercase())
-                } else {
-                    word.value.chars().all(|c| c.is_lowercase())
+        miscased(tokens, dialect, &self.config)
+            .into_iter()
+            .map(|word| {
+                let token = &tokens[word.index];
+                let actual = token.token.to_string();
+                let (line, column) = span_start(&token.span);
+                let (end_line, end_column) = span_end(&token.span);
+                let message = match word.role {
+                    Role::Keyword => messages.keyword_case_error(&actual, &word.expected),
+                    Role::Function => messages.function_case_error(&actual, &word.expected),
+                    Role::Type => messages.type_case_error(&actual, &word.expected),
+                    Role::Identifier => messages.identifier_case_error(&actual, &word.expected),
                 };
-                if conforms {
-                    continue;
+                LintError {
+                    rule: word.role.rule().to_string(),
+                    line,
+                    column,
+                    end_line,
+                    end_column,
+                    message,
                 }
-
-                let expected = if want_upper {
-                    word.value.to_uppercase()
-                } else {
-                    word.value.to_lowercase()
-                };
-                let start = token_with_span.span.start;
-                errors.push(LintError {
-                    rule: "keyword-case".to_string(),
-                    line: (start.line as usize).max(1),
-                    column: (start.column as usize).max(1),
-                    message: messages.keyword_case_error(&word.value, &expected),
-                });
-            }
-        }
-
-        errors
+            })
+            .collect()
     }
 
     fn check_select_star(&self, stmt: &Statement, messages: &Messages) -> Vec<LintError> {
@@ -138,10 +186,13 @@ impl Linter {
                         SelectItem::Wildcard(_) | SelectItem::QualifiedWildcard(_, _)
                     ) {
                         let (line, column) = span_start(&item.span());
+                        let (end_line, end_column) = span_end(&item.span());
                         errors.push(LintError {
                             rule: "no-select-star".to_string(),
                             line,
                             column,
+                            end_line,
+                            end_column,
                             message: messages.no_select_star_error(),
                         });
                     }
@@ -187,10 +238,13 @@ impl Linter {
         if let TableFactor::Table { name, alias, .. } = relation {
             if alias.is_none() {
                 let (line, column) = span_start(&relation.span());
+                let (end_line, end_column) = span_end(&relation.span());
                 errors.push(LintError {
                     rule: "require-table-alias".to_string(),
                     line,
                     column,
+                    end_line,
+                    end_column,
                     message: messages.require_table_alias_error(&name.to_string()),
                 });
             }
@@ -201,10 +255,14 @@ impl Linter {
         let trimmed = sql.trim();
         if !trimmed.is_empty() && !trimmed.ends_with(';') {
             let lines: Vec<&str> = sql.lines().collect();
+            let line = lines.len();
+            let column = lines.last().map(|l| l.len()).unwrap_or(1);
             vec![LintError {
                 rule: "trailing-semicolon".to_string(),
-                line: lines.len(),
-                column: lines.last().map(|l| l.len()).unwrap_or(1),
+                line,
+                column,
+                end_line: line,
+                end_column: column,
                 message: messages.trailing_semicolon_error(),
             }]
         } else {
@@ -224,102 +282,19 @@ fn span_start(span: &Span) -> (usize, usize) {
     (line, column)
 }
 
+/// Convert the end of a [`Span`] into a 1-based (line, column) pair, with the same
+/// fallback as [`span_start`].
+fn span_end(span: &Span) -> (usize, usize) {
+    let line = span.end.line.max(1) as usize;
+    let column = span.end.column.max(1) as usize;
+    (line, column)
+}
+
+/// Whether `word` is a keyword in any dialect sqlparser supports, regardless of how it
+/// is used. The linter, fixer and formatter also look at how a word is used, so that
+/// names like a column called `status` are left alone.
 pub fn is_sql_keyword(word: &str) -> bool {
-    const KEYWORDS: &[&str] = &[
-        "SELECT",
-        "FROM",
-        "WHERE",
-        "AND",
-        "OR",
-        "NOT",
-        "IN",
-        "IS",
-        "NULL",
-        "LIKE",
-        "BETWEEN",
-        "JOIN",
-        "LEFT",
-        "RIGHT",
-        "INNER",
-        "OUTER",
-        "FULL",
-        "CROSS",
-        "ON",
-        "AS",
-        "ORDER",
-        "BY",
-        "ASC",
-        "DESC",
-        "GROUP",
-        "HAVING",
-        "LIMIT",
-        "OFFSET",
-        "UNION",
-        "ALL",
-        "DISTINCT",
-        "INSERT",
-        "INTO",
-        "VALUES",
-        "UPDATE",
-        "SET",
-        "DELETE",
-        "CREATE",
-        "TABLE",
-        "INDEX",
-        "VIEW",
-        "DROP",
-        "ALTER",
-        "ADD",
-        "COLUMN",
-        "PRIMARY",
-        "KEY",
-        "FOREIGN",
-        "REFERENCES",
-        "CONSTRAINT",
-        "DEFAULT",
-        "UNIQUE",
-        "CHECK",
-        "CASCADE",
-        "RESTRICT",
-        "IF",
-        "EXISTS",
-        "CASE",
-        "WHEN",
-        "THEN",
-        "ELSE",
-        "END",
-        "CAST",
-        "COALESCE",
-        "NULLIF",
-        "TRUE",
-        "FALSE",
-        "WITH",
-        "RECURSIVE",
-        "WINDOW",
-        "OVER",
-        "PARTITION",
-        "ROWS",
-        "RANGE",
-        "UNBOUNDED",
-        "PRECEDING",
-        "FOLLOWING",
-        "CURRENT",
-        "ROW",
-        "EXCEPT",
-        "INTERSECT",
-        "FETCH",
-        "FIRST",
-        "NEXT",
-        "ONLY",
-        "PERCENT",
-        "TIES",
-        "FOR",
-        "SHARE",
-        "NOWAIT",
-        "SKIP",
-        "LOCKED",
-    ];
-    KEYWORDS.contains(&word.to_uppercase().as_str())
+    ALL_KEYWORDS.contains(&word.to_uppercase().as_str())
 }
 
 #[cfg(test)]
@@ -358,6 +333,23 @@ mod tests {
         assert!(!errors.iter().any(|e| e.rule == "no-select-star"));
     }
 
+    #[test]
+    fn test_suppression_comments() {
+        let linter = Linter::new(LintConfig::default());
+        let messages = Messages::new("en");
+        let dialect = GenericDialect {};
+
+        let sql = "-- sqlex-disable-next-line no-select-star\nSELECT * FROM users;\nSELECT * FROM orders;";
+        let errors = linter.lint(sql, &dialect, &messages);
+        let positions: Vec<_> = errors.iter().map(|e| (e.rule.as_str(), e.line)).collect();
+        assert_eq!(positions, vec![("no-select-star", 3)]);
+
+        let sql = "-- sqlex-disable-file keyword-case\nSELECT id FROM users;";
+        let errors = linter.lint(sql, &dialect, &messages);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].rule, "unused-suppression");
+    }
+
     #[test]
     fn test_trailing_semicolon() {
         let linter = Linter::new(LintConfig {
@@ -382,6 +374,7 @@ mod tests {
             no_select_star: false,
             require_table_alias: false,
             trailing_semicolon: false,
+            ..Default::default()
         })
     }
 
@@ -392,6 +385,7 @@ mod tests {
             no_select_star: false,
             require_table_alias: false,
             trailing_semicolon: false,
+            ..Default::default()
         });
         let messages = Messages::new("en");
         let dialect = GenericDialect {};
@@ -421,6 +415,7 @@ mod tests {
             no_select_star: false,
             require_table_alias: false,
             trailing_semicolon: false,
+            ..Default::default()
         });
         let messages = Messages::new("en");
         let dialect = GenericDialect {};
@@ -445,6 +440,7 @@ mod tests {
             no_select_star: false,
             require_table_alias: true,
             trailing_semicolon: false,
+            ..Default::default()
         });
         let messages = Messages::new("en");
         let dialect = GenericDialect {};
@@ -465,6 +461,7 @@ mod tests {
             no_select_star: false,
             require_table_alias: true,
             trailing_semicolon: false,
+            ..Default::default()
         });
         let messages = Messages::new("en");
         let dialect = GenericDialect {};
@@ -490,6 +487,7 @@ mod tests {
             no_select_star: true,
             require_table_alias: false,
             trailing_semicolon: false,
+            ..Default::default()
         });
         let messages = Messages::new("en");
         let dialect = GenericDialect {};
@@ -506,6 +504,7 @@ mod tests {
             no_select_star: true,
             require_table_alias: false,
             trailing_semicolon: false,
+            ..Default::default()
         });
         let messages = Messages::new("en");
         let dialect = GenericDialect {};
@@ -523,6 +522,7 @@ mod tests {
             no_select_star: false,
             require_table_alias: true,
             trailing_semicolon: false,
+            ..Default::default()
         });
         let messages = Messages::new("en");
         let dialect = GenericDialect {};
@@ -569,6 +569,16 @@ mod tests {
         assert_eq!((kw.line, kw.column), (2, 1));
     }
 
+    #[test]
+    fn test_keyword_case_reports_exclusive_end() {
+        let linter = upper_only_linter();
+        let messages = Messages::new("en");
+        let dialect = GenericDialect {};
+        let errors = linter.lint("SELECT id\nfrom users", &dialect, &messages);
+        let kw = errors.iter().find(|e| e.rule == "keyword-case").unwrap();
+        assert_eq!((kw.end_line, kw.end_column), (2, 5));
+    }
+
     #[test]
     fn test_keyword_case_ignores_quoted_identifiers() {
         let linter = upper_only_linter();
@@ -0,0 +1,530 @@
+//! `sqlex lsp`: a language server speaking LSP over stdio.
+//!
+//! Open documents are checked on every open and change, publishing syntax errors and
+//! lint warnings as diagnostics. The fixes for the case rules and `trailing-semicolon`
+//! are offered as code actions. Whole-document formatting lays documents out as
+//! `sqlex format` does, or applies all the fixes to those it cannot format.
+//! Settings come from the `.sqlex.toml` files that apply to each document's path, read
+//! afresh on every check so config edits take effect without a restart.
+
+use crate::checker::{apply_edits, check_content, fix_edits, get_dialect, Edit};
+use crate::config::{Overrides, Resolver, Settings};
+use crate::detect;
+use crate::formatter::format_sql;
+use crate::i18n::Messages;
+use crate::linter::Linter;
+use anyhow::Result;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as _,
+    PublishDiagnostics,
+};
+use lsp_types::request::{CodeActionRequest, Formatting, Request as _};
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams,
+    CodeActionProviderCapability, Diagnostic, DiagnosticSeverity, DocumentFormattingParams,
+    NumberOrString, OneOf, Position, PublishDiagnosticsParams, Range, ServerCapabilities,
+    TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Uri, WorkspaceEdit,
+};
+use sqlparser::dialect::Dialect;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Serve LSP requests on stdin/stdout until the client shuts the server down.
+pub fn run(messages: &Messages) -> Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+        document_formatting_provider: Some(OneOf::Left(true)),
+        ..Default::default()
+    };
+    connection.initialize(serde_json::to_value(capabilities)?)?;
+
+    let mut server = Server::new(messages);
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    break;
+                }
+                let response = server.handle_request(request);
+                connection.sender.send(Message::Response(response))?;
+            }
+            Message::Notification(notification) => {
+                if let Some(uri) = server.handle_notification(notification) {
+                    let params = PublishDiagnosticsParams {
+                        diagnostics: server.diagnostics(&uri),
+                        uri,
+                        version: None,
+                    };
+                    connection
+                        .sender
+                        .send(Message::Notification(Notification::new(
+                            PublishDiagnostics::METHOD.to_string(),
+                            params,
+                        )))?;
+                }
+            }
+            Message::Response(_) => {}
+        }
+    }
+
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
+
+/// The open documents and what the server knows how to do with them.
+struct Server<'a> {
+    messages: &'a Messages,
+    documents: HashMap<Uri, String>,
+}
+
+impl<'a> Server<'a> {
+    fn new(messages: &'a Messages) -> Self {
+        Self {
+            messages,
+            documents: HashMap::new(),
+        }
+    }
+
+    fn handle_request(&self, request: Request) -> Response {
+        match request.method.as_str() {
+            CodeActionRequest::METHOD => {
+                respond::<CodeActionRequest>(request, |params| Some(self.code_actions(params)))
+            }
+            Formatting::METHOD => {
+                respond::<Formatting>(request, |params| Some(self.format(params)))
+            }
+            _ => Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("Unsupported request: {}", request.method),
+            ),
+        }
+    }
+
+    /// Track document contents. Returns the document whose diagnostics need publishing.
+    fn handle_notification(&mut self, notification: Notification) -> Option<Uri> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: lsp_types::DidOpenTextDocumentParams =
+                    serde_json::from_value(notification.params).ok()?;
+                let document = params.text_document;
+                self.documents.insert(document.uri.clone(), document.text);
+                Some(document.uri)
+            }
+            DidChangeTextDocument::METHOD => {
+                let params: lsp_types::DidChangeTextDocumentParams =
+                    serde_json::from_value(notification.params).ok()?;
+                // Full sync: the last change holds the whole new text.
+                let text = params.content_changes.into_iter().last()?.text;
+                let uri = params.text_document.uri;
+                self.documents.insert(uri.clone(), text);
+                Some(uri)
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: lsp_types::DidCloseTextDocumentParams =
+                    serde_json::from_value(notification.params).ok()?;
+                let uri = params.text_document.uri;
+                self.documents.remove(&uri);
+                // Publishing for a closed document clears its diagnostics.
+                Some(uri)
+            }
+            _ => None,
+        }
+    }
+
+    fn settings(&self, uri: &Uri, content: &str) -> Result<Settings> {
+        // Documents without a file path (e.g. unsaved buffers) use the config of the
+        // server's working directory.
+        let path = uri_to_path(uri).unwrap_or_else(|| PathBuf::from("untitled.sql"));
+        let settings = Resolver::new(Overrides::default()).settings_for(&path)?;
+        Ok(detect::resolve(&settings, &path, content, None)?
+            .0
+            .into_owned())
+    }
+
+    fn diagnostics(&self, uri: &Uri) -> Vec<Diagnostic> {
+        let Some(content) = self.documents.get(uri) else {
+            return Vec::new();
+        };
+        let settings = match self.settings(uri, content) {
+            Ok(settings) => settings,
+            Err(err) => return vec![config_error(format!("{:#}", err))],
+        };
+        let dialect = match get_dialect(&settings.dialect) {
+            Ok(dialect) => dialect,
+            Err(err) => return vec![config_error(err.to_string())],
+        };
+
+        let errors = check_content(content, dialect.as_ref(), &settings, self.messages);
+        let mut diagnostics: Vec<Diagnostic> = errors
+            .iter()
+            .map(|error| Diagnostic {
+                range: range(
+                    content,
+                    (error.line, error.column),
+                    (error.end_line, error.end_column),
+                ),
+                severity: Some(DiagnosticSeverity::ERROR),
+                code: Some(NumberOrString::String(error.rule.clone())),
+                source: Some("sqlex".to_string()),
+                message: error.message.clone(),
+                ..Default::default()
+            })
+            .collect();
+
+        let warnings = Linter::new(settings.lint).lint(content, dialect.as_ref(), self.messages);
+        diagnostics.extend(warnings.into_iter().map(|warning| Diagnostic {
+            range: range(
+                content,
+                (warning.line, warning.column),
+                (warning.end_line, warning.end_column),
+            ),
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String(warning.rule)),
+            source: Some("sqlex".to_string()),
+            message: warning.message,
+            ..Default::default()
+        }));
+        diagnostics
+    }
+
+    /// A document with its settings and dialect, or `None` if it is unknown or
+    /// misconfigured.
+    fn document(&self, uri: &Uri) -> Option<(&str, Settings, Box<dyn Dialect>)> {
+        let content = self.documents.get(uri)?;
+        let settings = self.settings(uri, content).ok()?;
+        let dialect = get_dialect(&settings.dialect).ok()?;
+        Some((content, settings, dialect))
+    }
+
+    /// The fixer's edits for a document, or `None` if it is unknown or misconfigured.
+    fn edits(&self, uri: &Uri) -> Option<(&str, Vec<Edit>)> {
+        let (content, settings, dialect) = self.document(uri)?;
+        let edits = fix_edits(content, dialect.as_ref(), &settings.lint);
+        Some((content, edits))
+    }
+
+    fn code_actions(&self, params: CodeActionParams) -> Vec<CodeActionOrCommand> {
+        let uri = params.text_document.uri;
+        let Some((content, edits)) = self.edits(&uri) else {
+            return Vec::new();
+        };
+        let wanted = |kind: &CodeActionKind| {
+            params.context.only.as_ref().is_none_or(|only| {
+                only.iter()
+                    .any(|prefix| kind.as_str().starts_with(prefix.as_str()))
+            })
+        };
+        let workspace_edit = |edits: &[&Edit]| WorkspaceEdit {
+            changes: Some(HashMap::from([(
+                uri.clone(),
+                edits.iter().map(|edit| text_edit(content, edit)).collect(),
+            )])),
+            ..Default::default()
+        };
+
+        let mut actions = Vec::new();
+        if wanted(&CodeActionKind::QUICKFIX) {
+            for edit in &edits {
+                let edit_range = range(
+                    content,
+                    (edit.line, edit.column),
+                    (edit.end_line, edit.end_column),
+                );
+                if edit_range.end < params.range.start || params.range.end < edit_range.start {
+                    continue;
+                }
+                let diagnostics: Vec<Diagnostic> = params
+                    .context
+                    .diagnostics
+                    .iter()
+                    .filter(|d| {
+                        d.code == Some(NumberOrString::String(edit.rule.clone()))
+                            && d.range.start.line == edit_range.start.line
+                    })
+                    .cloned()
+                    .collect();
+                actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                    title: self.messages.fix_action_title(
+                        &edit.rule,
+                        &edit.original,
+                        &edit.replacement,
+                    ),
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    diagnostics: (!diagnostics.is_empty()).then_some(diagnostics),
+                    edit: Some(workspace_edit(&[edit])),
+                    is_preferred: Some(true),
+                    ..Default::default()
+                }));
+            }
+        }
+        if !edits.is_empty() && wanted(&CodeActionKind::SOURCE_FIX_ALL) {
+            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title: self.messages.fix_all_action_title(),
+                kind: Some(CodeActionKind::SOURCE_FIX_ALL),
+                edit: Some(workspace_edit(&edits.iter().collect::<Vec<_>>())),
+                ..Default::default()
+            }));
+        }
+        actions
+    }
+
+    fn format(&self, params: DocumentFormattingParams) -> Vec<TextEdit> {
+        let Some((content, settings, dialect)) = self.document(&params.text_document.uri) else {
+            return Vec::new();
+        };
+        let formatted = format_sql(content, dialect.as_ref(), &settings.format, &settings.lint)
+            .unwrap_or_else(|_| {
+                apply_edits(
+                    content,
+                    &fix_edits(content, dialect.as_ref(), &settings.lint),
+                )
+            });
+        if formatted == content {
+            return Vec::new();
+        }
+        vec![TextEdit::new(
+            Range::new(Position::new(0, 0), end_position(content)),
+            formatted,
+        )]
+    }
+}
+
+/// Answer an `R` request with what `handle` makes of its params. Every request needs a
+/// response, so malformed params are answered with an error.
+fn respond<R: lsp_types::request::Request>(
+    request: Request,
+    handle: impl FnOnce(R::Params) -> R::Result,
+) -> Response {
+    match serde_json::from_value(request.params) {
+        Ok(params) => Response::new_ok(request.id, handle(params)),
+        Err(err) => Response::new_err(
+            request.id,
+            ErrorCode::InvalidParams as i32,
+            format!("Invalid params for {}: {}", R::METHOD, err),
+        ),
+    }
+}
+
+fn config_error(message: String) -> Diagnostic {
+    Diagnostic {
+        severity: Some(DiagnosticSeverity::ERROR),
+        source: Some("sqlex".to_string()),
+        message,
+        ..Default::default()
+    }
+}
+
+fn text_edit(content: &str, edit: &Edit) -> TextEdit {
+    TextEdit::new(
+        range(
+            content,
+            (edit.line, edit.column),
+            (edit.end_line, edit.end_column),
+        ),
+        edit.replacement.clone(),
+    )
+}
+
+fn range(content: &str, start: (usize, usize), end: (usize, usize)) -> Range {
+    Range::new(
+        position(content, start.0, start.1),
+        position(content, end.0, end.1),
+    )
+}
+
+/// Convert a 1-based (line, column) pair, with the column counted in chars, into an
+/// LSP position, whose character offset counts UTF-16 code units.
+fn position(content: &str, line: usize, column: usize) -> Position {
+    let text = content
+        .split('\n')
+        .nth(line.saturating_sub(1))
+        .unwrap_or("");
+    let character: usize = text
+        .chars()
+        .take(column.saturating_sub(1))
+        .map(char::len_utf16)
+        .sum();
+    Position::new(line.saturating_sub(1) as u32, character as u32)
+}
+
+fn end_position(content: &str) -> Position {
+    let line = content.matches('\n').count();
+    let last = content.rsplit('\n').next().unwrap_or("");
+    Position::new(line as u32, last.encode_utf16().count() as u32)
+}
+
+/// The local path of a `file:` URI.
+fn uri_to_path(uri: &Uri) -> Option<PathBuf> {
+    let rest = uri.as_str().strip_prefix("file://")?;
+    // Skip an authority such as `localhost`.
+    let path = &rest[rest.find('/')?..];
+    let path = percent_decode(path)?;
+    // `file:///C:/dir` names the Windows path `C:/dir`.
+    let path = match path.as_bytes() {
+        [b'/', drive, b':', ..] if drive.is_ascii_alphabetic() => path[1..].to_string(),
+        _ => path,
+    };
+    Some(PathBuf::from(path))
+}
+
+fn percent_decode(text: &str) -> Option<String> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = text.get(i + 1..i + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn uri(text: &str) -> Uri {
+        Uri::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn test_uri_to_path() {
+        assert_eq!(
+            uri_to_path(&uri("file:///home/me/my%20queries/a.sql")),
+            Some(PathBuf::from("/home/me/my queries/a.sql"))
+        );
+        assert_eq!(
+            uri_to_path(&uri("file:///C:/sql/a.sql")),
+            Some(PathBuf::from("C:/sql/a.sql"))
+        );
+        assert_eq!(uri_to_path(&uri("untitled:Untitled-1")), None);
+    }
+
+    #[test]
+    fn test_position_counts_utf16_units() {
+        let content = "SELECT '😀', x\nFROM t";
+        // Column 13 is `x`, after a surrogate pair.
+        assert_eq!(position(content, 1, 13), Position::new(0, 13));
+        assert_eq!(position(content, 2, 1), Position::new(1, 0));
+        assert_eq!(end_position(content), Position::new(1, 6));
+        assert_eq!(end_position("SELECT 1;\n"), Position::new(1, 0));
+    }
+
+    #[test]
+    fn test_diagnostics_and_formatting() {
+        let messages = Messages::new("en");
+        let mut server = Server::new(&messages);
+        let uri = uri("untitled:Untitled-1");
+        server
+            .documents
+            .insert(uri.clone(), "select id from users".to_string());
+
+        let codes: Vec<_> = server
+            .diagnostics(&uri)
+            .into_iter()
+            .filter_map(|d| d.code)
+            .collect();
+        assert_eq!(codes.len(), 3);
+
+        let edits = server.format(DocumentFormattingParams {
+            text_document: lsp_types::TextDocumentIdentifier { uri: uri.clone() },
+            options: Default::default(),
+            work_done_progress_params: Default::default(),
+        });
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].new_text, "SELECT id\nFROM users;\n");
+    }
+
+    #[test]
+    fn test_formatting_matches_format_command() {
+        let messages = Messages::new("en");
+        let mut server = Server::new(&messages);
+        let uri = uri("untitled:Untitled-1");
+        let params = || DocumentFormattingParams {
+            text_document: lsp_types::TextDocumentIdentifier { uri: uri.clone() },
+            options: Default::default(),
+            work_done_progress_params: Default::default(),
+        };
+
+        let sql = "select id,name from users where id=1";
+        server.documents.insert(uri.clone(), sql.to_string());
+        let (_, settings, dialect) = server.document(&uri).unwrap();
+        let expected = format_sql(sql, dialect.as_ref(), &settings.format, &settings.lint).unwrap();
+        assert_eq!(server.format(params())[0].new_text, expected);
+
+        // SQL that cannot be formatted still gets the fixes.
+        server
+            .documents
+            .insert(uri.clone(), "select id from".to_string());
+        assert_eq!(server.format(params())[0].new_text, "SELECT id FROM;\n");
+    }
+
+    #[test]
+    fn test_code_actions_cover_requested_range() {
+        let messages = Messages::new("en");
+        let mut server = Server::new(&messages);
+        let uri = uri("untitled:Untitled-1");
+        server
+            .documents
+            .insert(uri.clone(), "select id\nfrom users;".to_string());
+
+        let params = |range| CodeActionParams {
+            text_document: lsp_types::TextDocumentIdentifier { uri: uri.clone() },
+            range,
+            context: Default::default(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+        let titles = |actions: Vec<CodeActionOrCommand>| -> Vec<String> {
+            actions
+                .into_iter()
+                .map(|action| match action {
+                    CodeActionOrCommand::CodeAction(action) => action.title,
+                    CodeActionOrCommand::Command(command) => command.title,
+                })
+                .collect()
+        };
+
+        let actions =
+            server.code_actions(params(Range::new(Position::new(1, 0), Position::new(1, 0))));
+        assert_eq!(
+            titles(actions),
+            vec!["Change 'from' to 'FROM'", "Fix all auto-fixable problems"]
+        );
+    }
+
+    #[test]
+    fn test_every_request_gets_a_response() {
+        let messages = Messages::new("en");
+        let server = Server::new(&messages);
+        let request = |method: &str, params| Request::new(1.into(), method.to_string(), params);
+
+        let response = server.handle_request(request(
+            Formatting::METHOD,
+            serde_json::json!({"textDocument": 42}),
+        ));
+        assert_eq!(response.id, 1.into());
+        assert_eq!(
+            response.error.map(|error| error.code),
+            Some(ErrorCode::InvalidParams as i32)
+        );
+
+        let response = server.handle_request(request("workspace/symbol", serde_json::json!({})));
+        assert_eq!(
+            response.error.map(|error| error.code),
+            Some(ErrorCode::MethodNotFound as i32)
+        );
+    }
+}
@@ -0,0 +1,309 @@
+//! `check --dialects`: which statements parse in some of the listed dialects but not
+//! in others.
+
+use crate::cache::Cache;
+use crate::checker::{
+    cached, check_sql, collect_sources, get_dialect, split_statements, Inputs, SyntaxError,
+};
+use crate::config::Resolver;
+use crate::i18n::Messages;
+use crate::report::{Diagnostic, DiagnosticReporter, FileReport, OutputFormat};
+use anyhow::Result;
+use colored::Colorize;
+use rayon::prelude::*;
+use sqlparser::dialect::GenericDialect;
+use sqlparser::tokenizer::{Token, Tokenizer};
+use std::io;
+
+/// Longest statement excerpt shown in the text report, in characters.
+const EXCERPT_LENGTH: usize = 60;
+
+/// A statement that some of the dialects reject.
+#[derive(Debug)]
+struct Statement {
+    line: usize,
+    column: usize,
+    excerpt: String,
+    /// Each rejecting dialect with its error, in the order the dialects were listed.
+    rejections: Vec<(String, SyntaxError)>,
+}
+
+/// A file's statements, of which only those some dialect rejects are kept.
+#[derive(Debug)]
+struct Portability {
+    statements: usize,
+    rejected: Vec<Statement>,
+}
+
+impl Portability {
+    /// Parse `content` with every one of `dialects` and sort their errors by statement.
+    fn analyze(content: &str, dialects: &[String], cache: Option<&Cache>) -> Result<Self> {
+        // When the generic tokenizer cannot split the file, it is one statement.
+        let starts = statement_starts(content).unwrap_or_else(|| vec![(1, 1, excerpt(content))]);
+        let mut statements = starts
+            .into_iter()
+            .map(|(line, column, excerpt)| Statement {
+                line,
+                column,
+                excerpt,
+                rejections: Vec::new(),
+            })
+            .collect::<Vec<_>>();
+
+        for name in dialects {
+            let dialect = get_dialect(name)?;
+            let errors = cached(cache, &["check", name], content, || {
+                Ok(check_sql(content, dialect.as_ref()))
+            })?;
+            for error in errors {
+                // The error belongs to the last statement starting at or before it.
+                let position = (error.line, error.column);
+                let index = statements
+                    .iter()
+                    .rposition(|s| (s.line, s.column) <= position)
+                    .unwrap_or(0);
+                statements[index].rejections.push((name.clone(), error));
+            }
+        }
+
+        let count = statements.len();
+        statements.retain(|s| !s.rejections.is_empty());
+        Ok(Portability {
+            statements: count,
+            rejected: statements,
+        })
+    }
+
+    /// Number of statements `dialect` rejects.
+    fn rejected_by(&self, dialect: &str) -> usize {
+        self.rejected
+            .iter()
+            .filter(|s| s.rejections.iter().any(|(d, _)| d == dialect))
+            .count()
+    }
+}
+
+/// Position and excerpt of each statement in `content`, as split by the generic
+/// tokenizer.
+fn statement_starts(content: &str) -> Option<Vec<(usize, usize, String)>> {
+    let tokens = Tokenizer::new(&GenericDialect {}, content)
+        .tokenize_with_location()
+        .ok()?;
+    let starts = split_statements(&tokens)
+        .into_iter()
+        .filter_map(|statement| {
+            let first = statement
+                .iter()
+                .find(|t| !matches!(t.token, Token::Whitespace(_)))?;
+            let text: String = statement
+                .iter()
+                .skip_while(|t| matches!(t.token, Token::Whitespace(_)))
+                .map(|t| t.token.to_string())
+                .collect();
+            Some((
+                first.span.start.line as usize,
+                first.span.start.column as usize,
+                excerpt(&text),
+            ))
+        })
+        .collect();
+    Some(starts)
+}
+
+/// The first line of `text`, shortened to [`EXCERPT_LENGTH`] characters.
+fn excerpt(text: &str) -> String {
+    let line = text.trim().lines().next().unwrap_or_default();
+    if line.chars().count() > EXCERPT_LENGTH {
+        let short: String = line.chars().take(EXCERPT_LENGTH).collect();
+        format!("{}...", short)
+    } else {
+        line.to_string()
+    }
+}
+
+/// Check files and directories against each of `dialects`, printing the statements
+/// that only some of them accept and a matrix of rejected statements per file and
+/// dialect.
+///
+/// Returns the number of statements at least one dialect rejects.
+pub fn check_portability(
+    inputs: &Inputs,
+    resolver: &Resolver,
+    dialects: &[String],
+    jobs: usize,
+    cache: Option<&Cache>,
+    format: OutputFormat,
+    messages: &Messages,
+) -> Result<usize> {
+    // Fail on a misspelled dialect before reading anything.
+    for name in dialects {
+        get_dialect(name)?;
+    }
+    let sources = collect_sources(inputs, resolver)?;
+    if sources.is_empty() {
+        eprintln!("{}", "No SQL files found".yellow());
+        if format == OutputFormat::Text {
+            return Ok(0);
+        }
+    }
+
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(jobs).build()?;
+    let results = pool.install(|| {
+        sources
+            .par_iter()
+            .map(|source| Portability::analyze(&source.read()?, dialects, cache))
+            .collect::<Result<Vec<_>>>()
+    })?;
+    let files: Vec<(&str, Portability)> = sources.iter().map(|s| s.name()).zip(results).collect();
+    let total: usize = files.iter().map(|(_, p)| p.rejected.len()).sum();
+
+    if format != OutputFormat::Text {
+        let mut reporter = DiagnosticReporter::new(format, "check", messages, io::stdout().lock());
+        for (file, portability) in &files {
+            let diagnostics = portability
+                .rejected
+                .iter()
+                .flat_map(|s| &s.rejections)
+                .map(|(dialect, error)| {
+                    let error = SyntaxError {
+                        message: messages.rejected_by(dialect, &error.message),
+                        ..error.clone()
+                    };
+                    Diagnostic::syntax(file, &error, None)
+                })
+                .collect();
+            reporter.file(FileReport {
+                path: file.to_string(),
+                diagnostics,
+            })?;
+        }
+        reporter.finish()?;
+        return Ok(total);
+    }
+
+    for (file, portability) in &files {
+        print!("{}", render(file, portability, messages));
+    }
+    print!("{}", render_matrix(&files, dialects, messages));
+    let statements = files.iter().map(|(_, p)| p.statements).sum();
+    println!(
+        "{}",
+        messages.portability_summary(files.len(), statements, total)
+    );
+    Ok(total)
+}
+
+/// Text report for one file: each statement some dialect rejects, with the errors.
+fn render(file: &str, portability: &Portability, messages: &Messages) -> String {
+    if portability.rejected.is_empty() {
+        return format!("{}\n", messages.file_ok(file).green());
+    }
+    let mut out = format!(
+        "{}\n",
+        messages
+            .file_not_portable(file, portability.rejected.len())
+            .red()
+    );
+    for statement in &portability.rejected {
+        out += &format!(
+            "  {}\n",
+            messages.statement_at(statement.line, &statement.excerpt)
+        );
+        for (dialect, error) in &statement.rejections {
+            out += &format!(
+                "    {} {}\n",
+                "✗".red(),
+                messages.syntax_error_in(dialect, error.line, error.column, &error.message)
+            );
+        }
+    }
+    out
+}
+
+/// Matrix of the number of rejected statements per file (rows) and dialect (columns).
+fn render_matrix(
+    files: &[(&str, Portability)],
+    dialects: &[String],
+    messages: &Messages,
+) -> String {
+    let width = files
+        .iter()
+        .map(|(file, _)| file.chars().count())
+        .max()
+        .unwrap_or_default();
+    let mut out = format!("\n{}\n{:width$}", messages.portability_matrix(), "");
+    for dialect in dialects {
+        out += &format!("  {}", dialect);
+    }
+    out += "\n";
+    for (file, portability) in files {
+        out += &format!("{:width$}", file);
+        for dialect in dialects {
+            let cell = match portability.rejected_by(dialect) {
+                0 => "✓".green().to_string(),
+                n => n.to_string().red().to_string(),
+            };
+            // Pad by hand: the colored cell's escape codes would throw off `{:width$}`.
+            let padding = dialect.chars().count().saturating_sub(1);
+            out += &format!("  {}{}", cell, " ".repeat(padding));
+        }
+        out.truncate(out.trim_end().len());
+        out += "\n";
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn analyze(sql: &str, dialects: &[&str]) -> Portability {
+        let dialects: Vec<String> = dialects.iter().map(|d| d.to_string()).collect();
+        Portability::analyze(sql, &dialects, None).unwrap()
+    }
+
+    #[test]
+    fn test_portable_statements_are_dropped() {
+        let portability = analyze("SELECT 1;\nSELECT id FROM t;\n", &["postgres", "sqlite"]);
+        assert_eq!(portability.statements, 2);
+        assert!(portability.rejected.is_empty());
+    }
+
+    #[test]
+    fn test_errors_are_sorted_by_statement() {
+        let sql = "SELECT 1;\nSELECT * EXCLUDE (a) FROM t;\nSELECT id FROM t LIMIT 1 BY id;\n";
+        let portability = analyze(sql, &["duckdb", "clickhouse", "postgres"]);
+        assert_eq!(portability.statements, 3);
+
+        let rejecting = |s: &Statement| {
+            s.rejections
+                .iter()
+                .map(|(d, _)| d.clone())
+                .collect::<Vec<_>>()
+        };
+        let [exclude, limit_by] = portability.rejected.as_slice() else {
+            panic!("{:?}", portability.rejected);
+        };
+        assert_eq!((exclude.line, exclude.column), (2, 1));
+        assert_eq!(exclude.excerpt, "SELECT * EXCLUDE (a) FROM t;");
+        assert_eq!(rejecting(exclude), ["clickhouse", "postgres"]);
+        assert_eq!(limit_by.line, 3);
+        assert_eq!(rejecting(limit_by), ["duckdb", "postgres"]);
+
+        assert_eq!(portability.rejected_by("postgres"), 2);
+        assert_eq!(portability.rejected_by("clickhouse"), 1);
+    }
+
+    #[test]
+    fn test_unknown_dialect_errors() {
+        let dialects = vec!["oracle".to_string()];
+        assert!(Portability::analyze("SELECT 1;", &dialects, None).is_err());
+    }
+
+    #[test]
+    fn test_excerpt() {
+        assert_eq!(excerpt("  SELECT 1\n  FROM t;"), "SELECT 1");
+        let long = format!("SELECT {} FROM t;", "a, ".repeat(30));
+        assert_eq!(excerpt(&long).chars().count(), EXCERPT_LENGTH + 3);
+    }
+}
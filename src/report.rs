@@ -0,0 +1,474 @@
+//! Machine-readable output for `check`, `lint` and `fix`.
+//!
+//! # JSON schema (version 1)
+//!
+//! `--format json` prints one document once every file has been processed:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "command": "lint",
+//!   "files": [
+//!     { "path": "q.sql", "diagnostics": [ <diagnostic>, ... ] }
+//!   ],
+//!   "summary": { "files": 1, "errors": 0, "warnings": 1 }
+//! }
+//! ```
+//!
+//! where each diagnostic is
+//!
+//! ```json
+//! {
+//!   "file": "q.sql", "rule": "keyword-case", "severity": "warning",
+//!   "line": 1, "column": 1, "end_line": 1, "end_column": 7,
+//!   "message": "Keyword 'select' should be 'SELECT'", "hint": null
+//! }
+//! ```
+//!
+//! Syntax errors use the rule `syntax-error` and severity `error`. Positions are
+//! 1-based and the end position is exclusive.
+//!
+//! `--format ndjson` writes one diagnostic per line, each with an added `"version"`
+//! field. Lines are written in path order once every file has been processed.
+//!
+//! For `fix`, files are listed with the edits made (or, with `--dry-run`, the edits
+//! that would be made) instead of diagnostics:
+//!
+//! ```json
+//! {
+//!   "version": 1, "command": "fix", "dry_run": true,
+//!   "files": [
+//!     { "path": "q.sql", "edits": [
+//!       { "rule": "keyword-case", "line": 1, "column": 1, "end_line": 1,
+//!         "end_column": 7, "original": "select", "replacement": "SELECT" }
+//!     ] }
+//!   ]
+//! }
+//! ```
+//!
+//! NDJSON fix output is one edit per line with added `"version"` and `"file"` fields.
+//! Only files with at least one edit are listed.
+//!
+//! `--format sarif` writes a SARIF 2.1.0 log instead; see [`sarif`]. `--format github`
+//! writes GitHub Actions workflow commands; see [`github`]. `--format junit` and
+//! `--format checkstyle` write XML reports; see [`junit`] and [`checkstyle`].
+
+use crate::checker::{Edit, SyntaxError};
+use crate::i18n::Messages;
+use crate::linter::LintError;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::io::{self, Write};
+
+pub mod checkstyle;
+pub mod github;
+pub mod junit;
+pub mod sarif;
+
+/// Version of the JSON/NDJSON output schema, bumped on incompatible changes.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Output format for `check` and `lint`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable colored text (default)
+    #[default]
+    Text,
+    /// A single JSON document
+    Json,
+    /// One JSON object per line, in path order
+    Ndjson,
+    /// SARIF 2.1.0 log for code-scanning tools
+    Sarif,
+    /// GitHub Actions annotations (::error / ::warning workflow commands)
+    Github,
+    /// JUnit XML, one test case per file
+    Junit,
+    /// Checkstyle XML
+    Checkstyle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A syntax error or lint warning tied to a file.
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    pub file: String,
+    pub rule: String,
+    pub severity: Severity,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub message: String,
+    pub hint: Option<String>,
+    /// Edit that resolves the finding, for auto-fixable rules. Only used by SARIF.
+    #[serde(skip)]
+    pub fix: Option<Edit>,
+}
+
+impl Diagnostic {
+    pub fn syntax(file: &str, error: &SyntaxError, hint: Option<String>) -> Self {
+        Self {
+            file: file.to_string(),
+            rule: error.rule.clone(),
+            severity: Severity::Error,
+            line: error.line,
+            column: error.column,
+            end_line: error.end_line,
+            end_column: error.end_column,
+            message: error.message.clone(),
+            hint,
+            fix: None,
+        }
+    }
+
+    pub fn lint(file: &str, error: &LintError) -> Self {
+        Self {
+            file: file.to_string(),
+            rule: error.rule.clone(),
+            severity: Severity::Warning,
+            line: error.line,
+            column: error.column,
+            end_line: error.end_line,
+            end_column: error.end_column,
+            message: error.message.clone(),
+            hint: None,
+            fix: None,
+        }
+    }
+
+    pub fn with_fix(mut self, fix: Option<Edit>) -> Self {
+        self.fix = fix;
+        self
+    }
+}
+
+/// Diagnostics for one processed file (empty when the file is clean).
+#[derive(Debug, Clone, Serialize)]
+pub struct FileReport {
+    pub path: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Edits for one fixed file.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileEdits {
+    pub path: String,
+    pub edits: Vec<Edit>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Summary {
+    pub files: usize,
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+#[derive(Serialize)]
+struct Versioned<'a, T> {
+    version: u32,
+    #[serde(flatten)]
+    record: &'a T,
+}
+
+#[derive(Serialize)]
+struct FileEdit<'a> {
+    version: u32,
+    file: &'a str,
+    #[serde(flatten)]
+    edit: &'a Edit,
+}
+
+#[derive(Serialize)]
+struct DiagnosticDocument<'a> {
+    version: u32,
+    command: &'a str,
+    files: &'a [FileReport],
+    summary: &'a Summary,
+}
+
+#[derive(Serialize)]
+struct FixDocument<'a> {
+    version: u32,
+    command: &'a str,
+    dry_run: bool,
+    files: &'a [FileEdits],
+}
+
+fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters other than tab/newline are not allowed in XML 1.0.
+            c if c.is_control() && c != '\t' && c != '\n' && c != '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn write_line<W: Write, T: Serialize>(out: &mut W, value: &T) -> io::Result<()> {
+    serde_json::to_writer(&mut *out, value)?;
+    writeln!(out)
+}
+
+/// Writes `check`/`lint` results in a machine-readable format.
+pub struct DiagnosticReporter<'a, W: Write> {
+    format: OutputFormat,
+    command: &'static str,
+    messages: &'a Messages,
+    out: W,
+    files: Vec<FileReport>,
+    summary: Summary,
+}
+
+impl<'a, W: Write> DiagnosticReporter<'a, W> {
+    pub fn new(
+        format: OutputFormat,
+        command: &'static str,
+        messages: &'a Messages,
+        out: W,
+    ) -> Self {
+        Self {
+            format,
+            command,
+            messages,
+            out,
+            files: Vec::new(),
+            summary: Summary::default(),
+        }
+    }
+
+    pub fn file(&mut self, report: FileReport) -> io::Result<()> {
+        self.summary.files += 1;
+        for diagnostic in &report.diagnostics {
+            match diagnostic.severity {
+                Severity::Error => self.summary.errors += 1,
+                Severity::Warning => self.summary.warnings += 1,
+            }
+        }
+
+        match self.format {
+            OutputFormat::Ndjson => {
+                for diagnostic in &report.diagnostics {
+                    write_line(
+                        &mut self.out,
+                        &Versioned {
+                            version: SCHEMA_VERSION,
+                            record: diagnostic,
+                        },
+                    )?;
+                }
+                self.out.flush()
+            }
+            OutputFormat::Github => {
+                for diagnostic in &report.diagnostics {
+                    writeln!(self.out, "{}", github::annotation(diagnostic))?;
+                }
+                self.out.flush()
+            }
+            _ => {
+                self.files.push(report);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        match self.format {
+            OutputFormat::Json => {
+                let document = DiagnosticDocument {
+                    version: SCHEMA_VERSION,
+                    command: self.command,
+                    files: &self.files,
+                    summary: &self.summary,
+                };
+                serde_json::to_writer_pretty(&mut self.out, &document)?;
+                writeln!(self.out)?;
+            }
+            OutputFormat::Sarif => {
+                let log = sarif::document(self.command, &self.files, self.messages);
+                serde_json::to_writer_pretty(&mut self.out, &log)?;
+                writeln!(self.out)?;
+            }
+            OutputFormat::Junit => {
+                let xml = junit::document(self.command, &self.files, self.messages);
+                self.out.write_all(xml.as_bytes())?;
+            }
+            OutputFormat::Checkstyle => {
+                self.out
+                    .write_all(checkstyle::document(&self.files).as_bytes())?;
+            }
+            OutputFormat::Text | OutputFormat::Ndjson | OutputFormat::Github => {}
+        }
+        self.out.flush()
+    }
+}
+
+/// Writes `fix` edits as JSON or NDJSON.
+pub struct EditReporter<W: Write> {
+    format: OutputFormat,
+    dry_run: bool,
+    out: W,
+    files: Vec<FileEdits>,
+}
+
+impl<W: Write> EditReporter<W> {
+    pub fn new(format: OutputFormat, dry_run: bool, out: W) -> Self {
+        Self {
+            format,
+            dry_run,
+            out,
+            files: Vec::new(),
+        }
+    }
+
+    pub fn file(&mut self, edits: FileEdits) -> io::Result<()> {
+        if self.format == OutputFormat::Ndjson {
+            for edit in &edits.edits {
+                write_line(
+                    &mut self.out,
+                    &FileEdit {
+                        version: SCHEMA_VERSION,
+                        file: &edits.path,
+                        edit,
+                    },
+                )?;
+            }
+            self.out.flush()
+        } else {
+            self.files.push(edits);
+            Ok(())
+        }
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        if self.format == OutputFormat::Json {
+            let document = FixDocument {
+                version: SCHEMA_VERSION,
+                command: "fix",
+                dry_run: self.dry_run,
+                files: &self.files,
+            };
+            serde_json::to_writer_pretty(&mut self.out, &document)?;
+            writeln!(self.out)?;
+        }
+        self.out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn warning(file: &str) -> Diagnostic {
+        Diagnostic {
+            file: file.to_string(),
+            rule: "keyword-case".to_string(),
+            severity: Severity::Warning,
+            line: 1,
+            column: 1,
+            end_line: 1,
+            end_column: 7,
+            message: "Keyword 'select' should be 'SELECT'".to_string(),
+            hint: None,
+            fix: None,
+        }
+    }
+
+    #[test]
+    fn test_json_document_shape() {
+        let messages = Messages::new("en");
+        let mut out = Vec::new();
+        let mut reporter = DiagnosticReporter::new(OutputFormat::Json, "lint", &messages, &mut out);
+        reporter
+            .file(FileReport {
+                path: "a.sql".to_string(),
+                diagnostics: vec![warning("a.sql")],
+            })
+            .unwrap();
+        reporter
+            .file(FileReport {
+                path: "b.sql".to_string(),
+                diagnostics: vec![],
+            })
+            .unwrap();
+        reporter.finish().unwrap();
+
+        let doc: Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(doc["version"], SCHEMA_VERSION);
+        assert_eq!(doc["command"], "lint");
+        assert_eq!(doc["files"].as_array().unwrap().len(), 2);
+        let d = &doc["files"][0]["diagnostics"][0];
+        assert_eq!(d["rule"], "keyword-case");
+        assert_eq!(d["severity"], "warning");
+        assert_eq!(d["end_column"], 7);
+        assert_eq!(doc["summary"]["warnings"], 1);
+        assert_eq!(doc["summary"]["errors"], 0);
+    }
+
+    #[test]
+    fn test_ndjson_emits_one_versioned_line_per_diagnostic() {
+        let messages = Messages::new("en");
+        let mut out = Vec::new();
+        let mut reporter =
+            DiagnosticReporter::new(OutputFormat::Ndjson, "lint", &messages, &mut out);
+        reporter
+            .file(FileReport {
+                path: "a.sql".to_string(),
+                diagnostics: vec![warning("a.sql"), warning("a.sql")],
+            })
+            .unwrap();
+        reporter.finish().unwrap();
+
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let first: Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(first["version"], SCHEMA_VERSION);
+        assert_eq!(first["file"], "a.sql");
+    }
+
+    #[test]
+    fn test_fix_json_lists_edits() {
+        let mut out = Vec::new();
+        let mut reporter = EditReporter::new(OutputFormat::Json, true, &mut out);
+        reporter
+            .file(FileEdits {
+                path: "a.sql".to_string(),
+                edits: vec![Edit {
+                    rule: "keyword-case".to_string(),
+                    start: 0,
+                    end: 6,
+                    line: 1,
+                    column: 1,
+                    end_line: 1,
+                    end_column: 7,
+                    original: "select".to_string(),
+                    replacement: "SELECT".to_string(),
+                }],
+            })
+            .unwrap();
+        reporter.finish().unwrap();
+
+        let doc: Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(doc["command"], "fix");
+        assert_eq!(doc["dry_run"], true);
+        let edit = &doc["files"][0]["edits"][0];
+        assert_eq!(edit["replacement"], "SELECT");
+        // Byte offsets are internal and not part of the schema.
+        assert!(edit.get("start").is_none());
+    }
+}
@@ -0,0 +1,64 @@
+//! Checkstyle XML output: one `<file>` per processed file and one `<error>` per diagnostic.
+
+use super::{escape_xml, FileReport, Severity};
+use std::fmt::Write;
+
+pub fn document(files: &[FileReport]) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<checkstyle version=\"4.3\">\n");
+
+    for file in files {
+        let _ = writeln!(xml, "  <file name=\"{}\">", escape_xml(&file.path));
+        for d in &file.diagnostics {
+            let severity = match d.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            };
+            let _ = writeln!(
+                xml,
+                "    <error line=\"{}\" column=\"{}\" severity=\"{}\" message=\"{}\" source=\"sqlex.{}\"/>",
+                d.line,
+                d.column,
+                severity,
+                escape_xml(&d.message),
+                escape_xml(&d.rule)
+            );
+        }
+        xml.push_str("  </file>\n");
+    }
+
+    xml.push_str("</checkstyle>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::Diagnostic;
+
+    #[test]
+    fn test_errors_carry_position_severity_and_source() {
+        let files = vec![FileReport {
+            path: "q.sql".to_string(),
+            diagnostics: vec![Diagnostic {
+                file: "q.sql".to_string(),
+                rule: "syntax-error".to_string(),
+                severity: Severity::Error,
+                line: 3,
+                column: 4,
+                end_line: 3,
+                end_column: 4,
+                message: "Expected: <identifier>, found: \"x\"".to_string(),
+                hint: None,
+                fix: None,
+            }],
+        }];
+        let xml = document(&files);
+        assert!(xml.contains("<file name=\"q.sql\">"));
+        assert!(xml.contains(
+            "<error line=\"3\" column=\"4\" severity=\"error\" \
+             message=\"Expected: &lt;identifier&gt;, found: &quot;x&quot;\" \
+             source=\"sqlex.syntax-error\"/>"
+        ));
+    }
+}
@@ -0,0 +1,99 @@
+//! GitHub Actions workflow commands, which show up as inline annotations on pull requests.
+
+use super::{Diagnostic, Severity};
+
+/// Format one diagnostic as an `::error` / `::warning` workflow command.
+pub fn annotation(diagnostic: &Diagnostic) -> String {
+    let command = match diagnostic.severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+    };
+
+    let mut properties = vec![
+        format!("file={}", escape_property(&diagnostic.file)),
+        format!("line={}", diagnostic.line),
+        format!("col={}", diagnostic.column),
+    ];
+    // GitHub only honors endColumn on single-line annotations.
+    if (diagnostic.end_line, diagnostic.end_column) != (diagnostic.line, diagnostic.column) {
+        properties.push(format!("endLine={}", diagnostic.end_line));
+        if diagnostic.end_line == diagnostic.line {
+            properties.push(format!("endColumn={}", diagnostic.end_column));
+        }
+    }
+    properties.push(format!(
+        "title={}",
+        escape_property(&format!("sqlex ({})", diagnostic.rule))
+    ));
+
+    let mut message = diagnostic.message.clone();
+    if let Some(hint) = &diagnostic.hint {
+        message = format!("{}\n{}", message, hint);
+    }
+
+    format!(
+        "::{} {}::{}",
+        command,
+        properties.join(","),
+        escape_data(&message)
+    )
+}
+
+fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostic(severity: Severity) -> Diagnostic {
+        Diagnostic {
+            file: "sql/q.sql".to_string(),
+            rule: "keyword-case".to_string(),
+            severity,
+            line: 2,
+            column: 1,
+            end_line: 2,
+            end_column: 5,
+            message: "Keyword 'from' should be 'FROM'".to_string(),
+            hint: None,
+            fix: None,
+        }
+    }
+
+    #[test]
+    fn test_warning_annotation() {
+        assert_eq!(
+            annotation(&diagnostic(Severity::Warning)),
+            "::warning file=sql/q.sql,line=2,col=1,endLine=2,endColumn=5,\
+             title=sqlex (keyword-case)::Keyword 'from' should be 'FROM'"
+        );
+    }
+
+    #[test]
+    fn test_error_annotation_with_point_location_and_hint() {
+        let mut d = diagnostic(Severity::Error);
+        d.rule = "syntax-error".to_string();
+        d.end_column = d.column;
+        d.message = "Expected: identifier, found: ; at Line: 2, Column: 1".to_string();
+        d.hint = Some("100% sure".to_string());
+        assert_eq!(
+            annotation(&d),
+            "::error file=sql/q.sql,line=2,col=1,title=sqlex (syntax-error)::\
+             Expected: identifier, found: ; at Line: 2, Column: 1%0A100%25 sure"
+        );
+    }
+
+    #[test]
+    fn test_properties_are_escaped() {
+        assert_eq!(escape_property("a,b:c"), "a%2Cb%3Ac");
+    }
+}
@@ -0,0 +1,102 @@
+//! JUnit XML output: one test case per file, failing when the file has diagnostics.
+
+use super::{escape_xml, FileReport, Severity};
+use crate::i18n::Messages;
+use std::fmt::Write;
+
+pub fn document(command: &str, files: &[FileReport], messages: &Messages) -> String {
+    let failures = files.iter().filter(|f| !f.diagnostics.is_empty()).count();
+    let suite = format!("sqlex {}", command);
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<testsuites name=\"sqlex\" tests=\"{}\" failures=\"{}\" errors=\"0\">",
+        files.len(),
+        failures
+    );
+    let _ = writeln!(
+        xml,
+        "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\">",
+        escape_xml(&suite),
+        files.len(),
+        failures
+    );
+
+    for file in files {
+        let _ = write!(
+            xml,
+            "    <testcase name=\"{}\" classname=\"sqlex.{}\"",
+            escape_xml(&file.path),
+            command
+        );
+        let Some(first) = file.diagnostics.first() else {
+            xml.push_str("/>\n");
+            continue;
+        };
+        xml.push_str(">\n");
+
+        let body: Vec<String> = file
+            .diagnostics
+            .iter()
+            .map(|d| match d.severity {
+                Severity::Error => messages.syntax_error(d.line, d.column, &d.message),
+                Severity::Warning => messages
+                    .lint_warning(&d.rule, d.line, d.column, &d.message)
+                    .trim_start()
+                    .to_string(),
+            })
+            .collect();
+        let _ = writeln!(
+            xml,
+            "      <failure message=\"{}\" type=\"{}\">{}</failure>",
+            escape_xml(&first.message),
+            escape_xml(&first.rule),
+            escape_xml(&body.join("\n"))
+        );
+        xml.push_str("    </testcase>\n");
+    }
+
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::Diagnostic;
+
+    fn files() -> Vec<FileReport> {
+        vec![
+            FileReport {
+                path: "ok.sql".to_string(),
+                diagnostics: vec![],
+            },
+            FileReport {
+                path: "a&b.sql".to_string(),
+                diagnostics: vec![Diagnostic {
+                    file: "a&b.sql".to_string(),
+                    rule: "no-select-star".to_string(),
+                    severity: Severity::Warning,
+                    line: 1,
+                    column: 8,
+                    end_line: 1,
+                    end_column: 9,
+                    message: "Avoid SELECT *. Specify columns explicitly".to_string(),
+                    hint: None,
+                    fix: None,
+                }],
+            },
+        ]
+    }
+
+    #[test]
+    fn test_one_testcase_per_file() {
+        let xml = document("lint", &files(), &Messages::new("en"));
+        assert!(xml.contains("<testsuites name=\"sqlex\" tests=\"2\" failures=\"1\""));
+        assert!(xml.contains("<testcase name=\"ok.sql\" classname=\"sqlex.lint\"/>"));
+        assert!(xml.contains("<testcase name=\"a&amp;b.sql\" classname=\"sqlex.lint\">"));
+        assert!(xml.contains("type=\"no-select-star\""));
+        assert!(xml.contains(">[no-select-star] line 1:8 - Avoid SELECT *."));
+    }
+}
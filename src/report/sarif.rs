@@ -0,0 +1,223 @@
+//! SARIF 2.1.0 output for code-scanning dashboards.
+
+use super::{Diagnostic, FileReport, Severity};
+use crate::checker::Edit;
+use crate::i18n::Messages;
+use crate::linter::RULES;
+use serde_json::{json, Value};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Build a SARIF log with a single run covering every processed file.
+pub fn document(command: &str, files: &[FileReport], messages: &Messages) -> Value {
+    let mut rule_ids: Vec<&str> = match command {
+        "check" => vec!["syntax-error"],
+        _ => RULES.to_vec(),
+    };
+    for diagnostic in files.iter().flat_map(|f| &f.diagnostics) {
+        if !rule_ids.contains(&diagnostic.rule.as_str()) {
+            rule_ids.push(&diagnostic.rule);
+        }
+    }
+
+    let rules: Vec<Value> = rule_ids
+        .iter()
+        .map(|id| {
+            json!({
+                "id": id,
+                "shortDescription": { "text": messages.rule_description(id) },
+                "help": { "text": messages.rule_help(id) },
+            })
+        })
+        .collect();
+
+    let results: Vec<Value> = files
+        .iter()
+        .flat_map(|f| &f.diagnostics)
+        .map(|d| result(d, &rule_ids))
+        .collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "sqlex",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }]
+    })
+}
+
+fn result(diagnostic: &Diagnostic, rule_ids: &[&str]) -> Value {
+    let uri = artifact_uri(&diagnostic.file);
+    let mut message = diagnostic.message.clone();
+    if let Some(hint) = &diagnostic.hint {
+        message = format!("{} ({})", message, hint);
+    }
+
+    let mut result = json!({
+        "ruleId": diagnostic.rule,
+        "level": match diagnostic.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        },
+        "message": { "text": message },
+        "locations": [{
+            "physicalLocation": {
+                "artifactLocation": { "uri": uri },
+                "region": region(
+                    diagnostic.line,
+                    diagnostic.column,
+                    diagnostic.end_line,
+                    diagnostic.end_column,
+                ),
+            }
+        }],
+    });
+    if let Some(index) = rule_ids.iter().position(|id| *id == diagnostic.rule) {
+        result["ruleIndex"] = json!(index);
+    }
+    if let Some(edit) = &diagnostic.fix {
+        result["fixes"] = json!([fix(&uri, &diagnostic.message, edit)]);
+    }
+    result
+}
+
+fn fix(uri: &str, description: &str, edit: &Edit) -> Value {
+    // Deleted regions always carry an explicit end so insertions stay zero-width.
+    let deleted_region = json!({
+        "startLine": edit.line,
+        "startColumn": edit.column,
+        "endLine": edit.end_line,
+        "endColumn": edit.end_column,
+    });
+    json!({
+        "description": { "text": description },
+        "artifactChanges": [{
+            "artifactLocation": { "uri": uri },
+            "replacements": [{
+                "deletedRegion": deleted_region,
+                "insertedContent": { "text": edit.replacement },
+            }],
+        }],
+    })
+}
+
+/// SARIF region; the end is omitted when sqlex only knows the start position.
+fn region(line: usize, column: usize, end_line: usize, end_column: usize) -> Value {
+    if (end_line, end_column) == (line, column) {
+        json!({ "startLine": line, "startColumn": column })
+    } else {
+        json!({
+            "startLine": line,
+            "startColumn": column,
+            "endLine": end_line,
+            "endColumn": end_column,
+        })
+    }
+}
+
+/// Turn a file path into a relative URI reference: forward slashes, with characters
+/// outside the unreserved set percent-encoded.
+fn artifact_uri(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let path = path.strip_prefix("./").unwrap_or(&path);
+    let mut uri = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyword_warning() -> Diagnostic {
+        Diagnostic {
+            file: "sql/q.sql".to_string(),
+            rule: "keyword-case".to_string(),
+            severity: Severity::Warning,
+            line: 1,
+            column: 1,
+            end_line: 1,
+            end_column: 7,
+            message: "Keyword 'select' should be 'SELECT'".to_string(),
+            hint: None,
+            fix: Some(Edit {
+                rule: "keyword-case".to_string(),
+                start: 0,
+                end: 6,
+                line: 1,
+                column: 1,
+                end_line: 1,
+                end_column: 7,
+                original: "select".to_string(),
+                replacement: "SELECT".to_string(),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_document_has_one_run_with_rule_metadata() {
+        let files = vec![FileReport {
+            path: "sql/q.sql".to_string(),
+            diagnostics: vec![keyword_warning()],
+        }];
+        let log = document("lint", &files, &Messages::new("en"));
+
+        assert_eq!(log["version"], "2.1.0");
+        assert_eq!(log["runs"].as_array().unwrap().len(), 1);
+        let driver = &log["runs"][0]["tool"]["driver"];
+        assert_eq!(driver["name"], "sqlex");
+        assert_eq!(driver["rules"][0]["id"], "keyword-case");
+        assert_eq!(
+            driver["rules"][0]["shortDescription"]["text"],
+            "Enforce consistent keyword case"
+        );
+    }
+
+    #[test]
+    fn test_result_region_and_fix() {
+        let files = vec![FileReport {
+            path: "sql/q.sql".to_string(),
+            diagnostics: vec![keyword_warning()],
+        }];
+        let log = document("lint", &files, &Messages::new("en"));
+        let result = &log["runs"][0]["results"][0];
+
+        assert_eq!(result["ruleId"], "keyword-case");
+        assert_eq!(result["ruleIndex"], 0);
+        assert_eq!(result["level"], "warning");
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "sql/q.sql");
+        assert_eq!(location["region"]["endColumn"], 7);
+        let replacement = &result["fixes"][0]["artifactChanges"][0]["replacements"][0];
+        assert_eq!(replacement["insertedContent"]["text"], "SELECT");
+        assert_eq!(replacement["deletedRegion"]["startColumn"], 1);
+    }
+
+    #[test]
+    fn test_point_region_omits_end() {
+        let r = region(3, 5, 3, 5);
+        assert!(r.get("endLine").is_none());
+        assert_eq!(r["startColumn"], 5);
+    }
+
+    #[test]
+    fn test_artifact_uri_encoding() {
+        assert_eq!(artifact_uri("./a b/q.sql"), "a%20b/q.sql");
+        assert_eq!(artifact_uri("dir\\q.sql"), "dir/q.sql");
+    }
+}
@@ -0,0 +1,276 @@
+//! Inline suppression comments.
+//!
+//! Lint warnings can be silenced from the SQL itself:
+//!
+//! - `-- sqlex-disable-next-line rule1,rule2` silences the line after the comment
+//! - `-- sqlex-disable rule1,rule2` silences everything up to a matching `-- sqlex-enable`
+//!   (or the end of the file); `-- sqlex-enable` without rules ends every open region
+//! - `-- sqlex-disable-file rule1,rule2` silences the whole file
+//!
+//! Leaving out the rule list applies a directive to every rule. Block comments work
+//! too. A directive that ends up silencing nothing is reported as `unused-suppression`
+//! so stale comments get cleaned up.
+
+use crate::i18n::Messages;
+use crate::linter::LintError;
+use sqlparser::tokenizer::{Token, TokenWithSpan, Whitespace};
+
+/// Rule id reported for directives that did not suppress anything.
+pub const UNUSED_SUPPRESSION: &str = "unused-suppression";
+
+/// A suppression comment, as written.
+struct Directive {
+    text: String,
+    line: usize,
+    column: usize,
+    end_line: usize,
+    end_column: usize,
+    used: bool,
+}
+
+enum Scope {
+    NextLine(usize),
+    /// From a position up to, but excluding, an end position (`None`: end of file).
+    Region {
+        start: (usize, usize),
+        end: Option<(usize, usize)>,
+    },
+    File,
+}
+
+/// What a directive silences. One directive may produce several of these when an
+/// `sqlex-enable` re-enables only some of its rules.
+struct Suppression {
+    directive: usize,
+    /// Empty means every rule.
+    rules: Vec<String>,
+    scope: Scope,
+}
+
+impl Suppression {
+    fn covers(&self, rule: &str, line: usize, column: usize) -> bool {
+        if !self.rules.is_empty() && !self.rules.iter().any(|r| r == rule) {
+            return false;
+        }
+        match self.scope {
+            Scope::NextLine(target) => line == target,
+            Scope::Region { start, end } => {
+                (line, column) >= start && end.is_none_or(|end| (line, column) < end)
+            }
+            Scope::File => true,
+        }
+    }
+}
+
+/// The suppression directives found in one file.
+pub(crate) struct Suppressions {
+    directives: Vec<Directive>,
+    suppressions: Vec<Suppression>,
+}
+
+impl Suppressions {
+    /// Collect the directives from the comments in a token stream.
+    pub(crate) fn parse(tokens: &[TokenWithSpan]) -> Self {
+        let mut directives = Vec::new();
+        let mut suppressions: Vec<Suppression> = Vec::new();
+
+        for token in tokens {
+            let line = token.span.start.line as usize;
+            let column = token.span.start.column as usize;
+            let (text, end_line, end_column) = match &token.token {
+                Token::Whitespace(Whitespace::SingleLineComment { comment, prefix }) => {
+                    // The comment token includes its newline, so measure the end by hand.
+                    let width = prefix.chars().count() + comment.trim_end().chars().count();
+                    (comment.trim(), line, column + width)
+                }
+                Token::Whitespace(Whitespace::MultiLineComment(comment)) => (
+                    comment.trim(),
+                    token.span.end.line as usize,
+                    token.span.end.column as usize,
+                ),
+                _ => continue,
+            };
+            let mut words = text.split([',', ' ', '\t']).filter(|w| !w.is_empty());
+            let Some(name) = words.next() else {
+                continue;
+            };
+            let rules: Vec<String> = words.map(str::to_string).collect();
+
+            let scope = match name {
+                "sqlex-disable-next-line" => Scope::NextLine(line + 1),
+                "sqlex-disable" => Scope::Region {
+                    start: (line, column),
+                    end: None,
+                },
+                "sqlex-disable-file" => Scope::File,
+                "sqlex-enable" => {
+                    enable(&mut suppressions, &rules, (line, column));
+                    continue;
+                }
+                _ => continue,
+            };
+
+            suppressions.push(Suppression {
+                directive: directives.len(),
+                rules,
+                scope,
+            });
+            directives.push(Directive {
+                text: text.to_string(),
+                line,
+                column,
+                end_line,
+                end_column,
+                used: false,
+            });
+        }
+
+        Self {
+            directives,
+            suppressions,
+        }
+    }
+
+    /// Whether a warning for `rule` at (`line`, `column`) is suppressed. Every directive
+    /// that covers it is marked as used.
+    pub(crate) fn is_suppressed(&mut self, rule: &str, line: usize, column: usize) -> bool {
+        let mut suppressed = false;
+        for suppression in &self.suppressions {
+            if suppression.covers(rule, line, column) {
+                self.directives[suppression.directive].used = true;
+                suppressed = true;
+            }
+        }
+        suppressed
+    }
+
+    /// A warning for every directive that has not suppressed anything so far.
+    pub(crate) fn unused(&self, messages: &Messages) -> Vec<LintError> {
+        self.directives
+            .iter()
+            .filter(|d| !d.used)
+            .map(|d| LintError {
+                rule: UNUSED_SUPPRESSION.to_string(),
+                line: d.line,
+                column: d.column,
+                end_line: d.end_line,
+                end_column: d.end_column,
+                message: messages.unused_suppression_error(&d.text),
+            })
+            .collect()
+    }
+}
+
+/// End open regions at `at`: all of them when `rules` is empty, otherwise just the
+/// listed rules. A region that disables every rule can only be ended as a whole.
+fn enable(suppressions: &mut Vec<Suppression>, rules: &[String], at: (usize, usize)) {
+    let mut reopened = Vec::new();
+
+    for suppression in suppressions.iter_mut() {
+        let Scope::Region { start, end } = &mut suppression.scope else {
+            continue;
+        };
+        if end.is_some() {
+            continue;
+        }
+        if rules.is_empty() {
+            *end = Some(at);
+            continue;
+        }
+        if suppression.rules.is_empty() {
+            continue;
+        }
+
+        let (ended, still_open): (Vec<String>, Vec<String>) = suppression
+            .rules
+            .iter()
+            .cloned()
+            .partition(|r| rules.contains(r));
+        if ended.is_empty() {
+            continue;
+        }
+        if !still_open.is_empty() {
+            reopened.push(Suppression {
+                directive: suppression.directive,
+                rules: still_open,
+                scope: Scope::Region {
+                    start: *start,
+                    end: None,
+                },
+            });
+        }
+        suppression.rules = ended;
+        *end = Some(at);
+    }
+
+    suppressions.extend(reopened);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlparser::dialect::GenericDialect;
+    use sqlparser::tokenizer::Tokenizer;
+
+    fn parse(sql: &str) -> Suppressions {
+        let tokens = Tokenizer::new(&GenericDialect {}, sql)
+            .tokenize_with_location()
+            .unwrap();
+        Suppressions::parse(&tokens)
+    }
+
+    #[test]
+    fn test_next_line_only_covers_following_line() {
+        let mut s = parse("-- sqlex-disable-next-line no-select-star\nSELECT *\nFROM t;");
+        assert!(s.is_suppressed("no-select-star", 2, 8));
+        assert!(!s.is_suppressed("no-select-star", 3, 1));
+        assert!(!s.is_suppressed("keyword-case", 2, 1));
+    }
+
+    #[test]
+    fn test_region_ends_at_enable() {
+        let mut s = parse("-- sqlex-disable\nselect 1;\n-- sqlex-enable\nselect 2;");
+        assert!(s.is_suppressed("keyword-case", 2, 1));
+        assert!(!s.is_suppressed("keyword-case", 4, 1));
+    }
+
+    #[test]
+    fn test_partial_enable_keeps_other_rules_disabled() {
+        let mut s = parse(
+            "/* sqlex-disable keyword-case, no-select-star */\n\
+             select * from t;\n\
+             -- sqlex-enable keyword-case\n\
+             select * from t;",
+        );
+        assert!(s.is_suppressed("keyword-case", 2, 1));
+        assert!(!s.is_suppressed("keyword-case", 4, 1));
+        assert!(s.is_suppressed("no-select-star", 4, 8));
+    }
+
+    #[test]
+    fn test_file_directive_covers_everything() {
+        let mut s = parse("SELECT 1;\n-- sqlex-disable-file\nselect 2");
+        assert!(s.is_suppressed("keyword-case", 1, 1));
+        assert!(s.is_suppressed("trailing-semicolon", 3, 8));
+    }
+
+    #[test]
+    fn test_unused_directives_are_reported() {
+        let mut s = parse("-- sqlex-disable-next-line no-select-star\nSELECT id FROM t;");
+        assert!(!s.is_suppressed("keyword-case", 2, 1));
+
+        let unused = s.unused(&Messages::new("en"));
+        assert_eq!(unused.len(), 1);
+        assert_eq!(unused[0].rule, UNUSED_SUPPRESSION);
+        assert_eq!(
+            (unused[0].line, unused[0].column, unused[0].end_column),
+            (1, 1, 42)
+        );
+    }
+
+    #[test]
+    fn test_ordinary_comments_are_ignored() {
+        let s = parse("-- sqlex is great\n/* disable nothing */ SELECT 1;");
+        assert!(s.directives.is_empty());
+    }
+}
@@ -0,0 +1,620 @@
+//! Target database versions (`--target mysql@5.7`, `target-version` in config).
+//!
+//! A dialect's parser accepts everything any version of the database supports, so the
+//! parsed statements are searched for constructs that the target version predates.
+
+use crate::checker::{get_dialect, split_statements};
+use anyhow::{anyhow, bail, Result};
+use sqlparser::ast::{
+    AlterTableOperation, ColumnDef, ColumnOption, DataType, Delete, Expr, Fetch, Function, Insert,
+    OnInsert, OrderBy, OrderByExpr, OrderByKind, Query, SetExpr, SetOperator, Spanned, Statement,
+    TableFactor, Update, Visit, Visitor, WindowType,
+};
+use sqlparser::dialect::{Dialect, MySqlDialect, PostgreSqlDialect, SQLiteDialect};
+use sqlparser::parser::Parser;
+use sqlparser::tokenizer::{Location, Span, Tokenizer};
+use std::fmt;
+use std::ops::ControlFlow;
+
+/// A database version such as `5.7` or `3.35.0`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version {
+    parts: Vec<u32>,
+}
+
+impl Version {
+    pub fn parse(text: &str) -> Result<Self> {
+        let parts = text
+            .split('.')
+            .map(|part| part.parse::<u32>())
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|_| anyhow!("Invalid target version: {}", text))?;
+        Ok(Self { parts })
+    }
+
+    /// Whether this version is `since` or later. Missing parts count as zero, so
+    /// `8.0` is at least `8.0.0`.
+    fn at_least(&self, since: &[u32]) -> bool {
+        let len = self.parts.len().max(since.len());
+        let padded = |parts: &[u32]| {
+            (0..len)
+                .map(|i| parts.get(i).copied().unwrap_or(0))
+                .collect::<Vec<_>>()
+        };
+        padded(&self.parts) >= padded(since)
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", display_version(&self.parts))
+    }
+}
+
+fn display_version(parts: &[u32]) -> String {
+    parts
+        .iter()
+        .map(|p| p.to_string())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// A construct that only some versions of a database support.
+struct Feature {
+    /// How the construct is written, e.g. `WITH`.
+    name: &'static str,
+    /// First version supporting it.
+    since: &'static [u32],
+    /// Where the construct is in an AST node, if the node has it.
+    found: fn(Node) -> Option<Span>,
+}
+
+const fn feature(
+    name: &'static str,
+    since: &'static [u32],
+    found: fn(Node) -> Option<Span>,
+) -> Feature {
+    Feature { name, since, found }
+}
+
+/// The AST nodes features are looked for in.
+#[derive(Clone, Copy)]
+enum Node<'a> {
+    Query(&'a Query),
+    /// A `SetExpr::SetOperation` in a query's body.
+    SetOperation(&'a SetExpr),
+    TableFactor(&'a TableFactor),
+    Expr(&'a Expr),
+    Statement(&'a Statement),
+    Column(&'a ColumnDef),
+}
+
+impl Node<'_> {
+    fn span(self) -> Span {
+        match self {
+            Node::Query(query) => query.span(),
+            Node::SetOperation(set_expr) => set_expr.span(),
+            Node::TableFactor(table_factor) => table_factor.span(),
+            Node::Expr(expr) => expr.span(),
+            Node::Statement(statement) => statement.span(),
+            Node::Column(column) => column.span(),
+        }
+    }
+}
+
+fn with(node: Node) -> Option<Span> {
+    match node {
+        Node::Query(query) => Some(query.with.as_ref()?.with_token.0.span),
+        _ => None,
+    }
+}
+
+fn over(node: Node) -> Option<Span> {
+    match node {
+        Node::Expr(expr @ Expr::Function(Function { over: Some(_), .. })) => Some(expr.span()),
+        _ => None,
+    }
+}
+
+fn lateral(node: Node) -> Option<Span> {
+    match node {
+        Node::TableFactor(
+            table_factor @ (TableFactor::Derived { lateral: true, .. }
+            | TableFactor::Function { lateral: true, .. }),
+        ) => Some(table_factor.span()),
+        _ => None,
+    }
+}
+
+fn json_table(node: Node) -> Option<Span> {
+    match node {
+        Node::TableFactor(table_factor @ TableFactor::JsonTable { .. }) => {
+            Some(table_factor.span())
+        }
+        _ => None,
+    }
+}
+
+fn set_operation(node: Node, operator: SetOperator) -> Option<Span> {
+    match node {
+        Node::SetOperation(set_expr @ SetExpr::SetOperation { op, .. }) if *op == operator => {
+            Some(set_expr.span())
+        }
+        _ => None,
+    }
+}
+
+fn intersect(node: Node) -> Option<Span> {
+    set_operation(node, SetOperator::Intersect)
+}
+
+fn except(node: Node) -> Option<Span> {
+    set_operation(node, SetOperator::Except)
+}
+
+fn json_type(node: Node) -> Option<Span> {
+    match node {
+        Node::Column(column) if column.data_type == DataType::JSON => Some(column.span()),
+        Node::Expr(
+            expr @ Expr::Cast {
+                data_type: DataType::JSON,
+                ..
+            },
+        ) => Some(expr.span()),
+        _ => None,
+    }
+}
+
+/// A column generated from an expression, or as an identity column when `expression`
+/// is false.
+fn generated(node: Node, expression: bool) -> Option<Span> {
+    let Node::Column(column) = node else {
+        return None;
+    };
+    column
+        .options
+        .iter()
+        .find_map(|option| match &option.option {
+            ColumnOption::Generated {
+                generation_expr, ..
+            } if generation_expr.is_some() == expression => {
+                Some(generation_expr.as_ref().map_or(Span::empty(), |e| e.span()))
+            }
+            _ => None,
+        })
+}
+
+fn generated_column(node: Node) -> Option<Span> {
+    generated(node, true)
+}
+
+fn identity_column(node: Node) -> Option<Span> {
+    generated(node, false)
+}
+
+fn alter_table(node: Node, operation: fn(&AlterTableOperation) -> Option<Span>) -> Option<Span> {
+    match node {
+        Node::Statement(Statement::AlterTable(alter)) => {
+            alter.operations.iter().find_map(operation)
+        }
+        _ => None,
+    }
+}
+
+fn rename_column(node: Node) -> Option<Span> {
+    alter_table(node, |operation| match operation {
+        AlterTableOperation::RenameColumn {
+            old_column_name, ..
+        } => Some(old_column_name.span),
+        _ => None,
+    })
+}
+
+fn drop_column(node: Node) -> Option<Span> {
+    alter_table(node, |operation| match operation {
+        AlterTableOperation::DropColumn { column_names, .. } => {
+            Some(Span::union_iter(column_names.iter().map(|c| c.span)))
+        }
+        _ => None,
+    })
+}
+
+fn merge(node: Node) -> Option<Span> {
+    match node {
+        Node::Statement(Statement::Merge(merge)) => Some(merge.merge_token.0.span),
+        _ => None,
+    }
+}
+
+fn on_conflict(node: Node) -> Option<Span> {
+    match node {
+        Node::Statement(Statement::Insert(Insert {
+            on: Some(OnInsert::OnConflict(on_conflict)),
+            ..
+        })) => Some(on_conflict.span()),
+        _ => None,
+    }
+}
+
+fn returning(node: Node) -> Option<Span> {
+    let returning = match node {
+        Node::Statement(Statement::Insert(Insert { returning, .. }))
+        | Node::Statement(Statement::Update(Update { returning, .. }))
+        | Node::Statement(Statement::Delete(Delete { returning, .. })) => returning.as_ref()?,
+        _ => return None,
+    };
+    Some(Span::union_iter(returning.iter().map(|item| item.span())))
+}
+
+fn filter(node: Node) -> Option<Span> {
+    match node {
+        Node::Expr(
+            expr @ Expr::Function(Function {
+                filter: Some(_), ..
+            }),
+        ) => Some(expr.span()),
+        _ => None,
+    }
+}
+
+fn grouping_sets(node: Node) -> Option<Span> {
+    match node {
+        Node::Expr(expr @ (Expr::GroupingSets(_) | Expr::Rollup(_) | Expr::Cube(_))) => {
+            Some(expr.span())
+        }
+        _ => None,
+    }
+}
+
+fn with_ties(node: Node) -> Option<Span> {
+    match node {
+        Node::Query(Query {
+            fetch:
+                Some(Fetch {
+                    with_ties: true,
+                    quantity,
+                    ..
+                }),
+            ..
+        }) => Some(quantity.as_ref().map_or(Span::empty(), |q| q.span())),
+        _ => None,
+    }
+}
+
+fn materialized(node: Node) -> Option<Span> {
+    let Node::Query(query) = node else {
+        return None;
+    };
+    query
+        .with
+        .iter()
+        .flat_map(|with| &with.cte_tables)
+        .find(|cte| cte.materialized.is_some())
+        .map(|cte| cte.span())
+}
+
+fn nulls_order(node: Node) -> Option<Span> {
+    let order_by: &[OrderByExpr] = match node {
+        Node::Query(Query {
+            order_by:
+                Some(OrderBy {
+                    kind: OrderByKind::Expressions(order_by),
+                    ..
+                }),
+            ..
+        }) => order_by,
+        Node::Expr(Expr::Function(Function {
+            over: Some(WindowType::WindowSpec(window)),
+            ..
+        })) => &window.order_by,
+        _ => return None,
+    };
+    order_by
+        .iter()
+        .find(|expr| expr.options.nulls_first.is_some())
+        .map(|expr| expr.span())
+}
+
+const MYSQL: &[Feature] = &[
+    feature("GENERATED ALWAYS AS", &[5, 7, 6], generated_column),
+    feature("JSON", &[5, 7, 8], json_type),
+    feature("WITH", &[8, 0], with),
+    feature("OVER", &[8, 0], over),
+    feature("RENAME COLUMN", &[8, 0, 3], rename_column),
+    feature("JSON_TABLE", &[8, 0, 4], json_table),
+    feature("LATERAL", &[8, 0, 14], lateral),
+    feature("INTERSECT", &[8, 0, 31], intersect),
+    feature("EXCEPT", &[8, 0, 31], except),
+];
+
+const POSTGRES: &[Feature] = &[
+    feature("WITH", &[8, 4], with),
+    feature("OVER", &[8, 4], over),
+    feature("LATERAL", &[9, 3], lateral),
+    feature("FILTER", &[9, 4], filter),
+    feature("ON CONFLICT", &[9, 5], on_conflict),
+    feature("GROUPING SETS, ROLLUP and CUBE", &[9, 5], grouping_sets),
+    feature("GENERATED AS IDENTITY", &[10], identity_column),
+    feature("GENERATED ALWAYS AS ... STORED", &[12], generated_column),
+    feature("AS MATERIALIZED", &[12], materialized),
+    feature("WITH TIES", &[13], with_ties),
+    feature("MERGE", &[15], merge),
+    feature("JSON_TABLE", &[17], json_table),
+];
+
+const SQLITE: &[Feature] = &[
+    feature("WITH", &[3, 8, 3], with),
+    feature("ON CONFLICT", &[3, 24, 0], on_conflict),
+    feature("OVER", &[3, 25, 0], over),
+    feature("RENAME COLUMN", &[3, 25, 0], rename_column),
+    feature("FILTER", &[3, 30, 0], filter),
+    feature("NULLS FIRST/LAST", &[3, 30, 0], nulls_order),
+    feature("GENERATED ALWAYS AS", &[3, 31, 0], generated_column),
+    feature("RETURNING", &[3, 35, 0], returning),
+    feature("DROP COLUMN", &[3, 35, 0], drop_column),
+    feature("AS MATERIALIZED", &[3, 35, 0], materialized),
+];
+
+/// The database `dialect` stands for and its feature table, for the databases whose
+/// versions are known.
+fn features(dialect: &dyn Dialect) -> Option<(&'static str, &'static [Feature])> {
+    if dialect.is::<MySqlDialect>() {
+        Some(("MySQL", MYSQL))
+    } else if dialect.is::<PostgreSqlDialect>() {
+        Some(("PostgreSQL", POSTGRES))
+    } else if dialect.is::<SQLiteDialect>() {
+        Some(("SQLite", SQLITE))
+    } else {
+        None
+    }
+}
+
+/// Dialects with a feature table, as accepted by [`get_dialect`].
+pub const VERSIONED_DIALECTS: &[&str] = &["mysql", "postgres", "sqlite"];
+
+/// Fail unless `dialect` has a feature table, so that a target version is never
+/// silently ignored.
+pub fn ensure_versioned(dialect: &str) -> Result<()> {
+    let versioned = get_dialect(dialect)
+        .ok()
+        .and_then(|dialect| database_name(dialect.as_ref()))
+        .is_some();
+    if !versioned {
+        bail!(
+            "Target versions are not supported for dialect {} (supported: {})",
+            dialect,
+            VERSIONED_DIALECTS.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Name of the database `dialect` stands for, e.g. `PostgreSQL`, when it has a feature
+/// table.
+pub(crate) fn database_name(dialect: &dyn Dialect) -> Option<&'static str> {
+    features(dialect).map(|(name, _)| name)
+}
+
+/// A construct the target version does not support yet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unsupported {
+    pub start: Location,
+    pub end: Location,
+    /// How the construct is written, e.g. `WITH`.
+    pub feature: &'static str,
+    /// Name of the database, e.g. `MySQL`.
+    pub database: &'static str,
+    /// First version supporting the construct.
+    pub since: String,
+}
+
+/// Find the constructs in `content` that `version` of the database `dialect` stands
+/// for does not support. Statements that do not parse are skipped; they are syntax
+/// errors already. Dialects without a feature table, which [`ensure_versioned`] keeps
+/// out of settings, have nothing to report.
+pub fn unsupported(content: &str, dialect: &dyn Dialect, version: &Version) -> Vec<Unsupported> {
+    let Some((database, features)) = features(dialect) else {
+        return Vec::new();
+    };
+    let missing: Vec<&Feature> = features
+        .iter()
+        .filter(|f| !version.at_least(f.since))
+        .collect();
+    if missing.is_empty() {
+        return Vec::new();
+    }
+
+    let mut finder = Finder {
+        missing: &missing,
+        found: Vec::new(),
+    };
+    for statements in parse(content, dialect) {
+        let _ = statements.visit(&mut finder);
+    }
+    let mut found = finder.found;
+    found.sort_by_key(|(_, span)| (span.start.line, span.start.column));
+    found
+        .into_iter()
+        .map(|(feature, span)| Unsupported {
+            start: span.start,
+            end: span.end,
+            feature: feature.name,
+            database,
+            since: display_version(feature.since),
+        })
+        .collect()
+}
+
+/// Statements of `content`: the whole file when it parses, otherwise each statement
+/// that does on its own.
+fn parse(content: &str, dialect: &dyn Dialect) -> Vec<Vec<Statement>> {
+    if let Ok(statements) = Parser::parse_sql(dialect, content) {
+        return vec![statements];
+    }
+    let Ok(tokens) = Tokenizer::new(dialect, content).tokenize_with_location() else {
+        return Vec::new();
+    };
+    split_statements(&tokens)
+        .into_iter()
+        .filter_map(|statement| {
+            Parser::new(dialect)
+                .with_tokens_with_locations(statement.to_vec())
+                .parse_statements()
+                .ok()
+        })
+        .collect()
+}
+
+/// Collects where the `missing` features are found, in walk order.
+struct Finder<'f> {
+    missing: &'f [&'f Feature],
+    found: Vec<(&'f Feature, Span)>,
+}
+
+impl Finder<'_> {
+    /// Look for the missing features in `node`. A construct sqlparser does not locate
+    /// is reported at the node holding it.
+    fn check(&mut self, node: Node) {
+        for &feature in self.missing {
+            let Some(mut span) = (feature.found)(node) else {
+                continue;
+            };
+            if span.start.line == 0 {
+                span = node.span();
+            }
+            if span.start.line > 0 {
+                self.found.push((feature, span));
+            }
+        }
+    }
+
+    /// Check the set operations in a query's body; nested queries are visited on
+    /// their own.
+    fn check_set_operations(&mut self, set_expr: &SetExpr) {
+        if let SetExpr::SetOperation { left, right, .. } = set_expr {
+            self.check(Node::SetOperation(set_expr));
+            self.check_set_operations(left);
+            self.check_set_operations(right);
+        }
+    }
+}
+
+impl Visitor for Finder<'_> {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<()> {
+        self.check(Node::Query(query));
+        self.check_set_operations(&query.body);
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_table_factor(&mut self, table_factor: &TableFactor) -> ControlFlow<()> {
+        self.check(Node::TableFactor(table_factor));
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expr(&mut self, expr: &Expr) -> ControlFlow<()> {
+        self.check(Node::Expr(expr));
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_statement(&mut self, statement: &Statement) -> ControlFlow<()> {
+        self.check(Node::Statement(statement));
+        let columns: Vec<&ColumnDef> = match statement {
+            Statement::CreateTable(create) => create.columns.iter().collect(),
+            Statement::AlterTable(alter) => alter
+                .operations
+                .iter()
+                .filter_map(|operation| match operation {
+                    AlterTableOperation::AddColumn { column_def, .. } => Some(column_def),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        for column in columns {
+            self.check(Node::Column(column));
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlparser::dialect::GenericDialect;
+
+    fn found(sql: &str, dialect: &dyn Dialect, version: &str) -> Vec<(&'static str, String)> {
+        unsupported(sql, dialect, &Version::parse(version).unwrap())
+            .into_iter()
+            .map(|u| (u.feature, u.since))
+            .collect()
+    }
+
+    #[test]
+    fn test_version() {
+        let version = Version::parse("8.0").unwrap();
+        assert!(version.at_least(&[8, 0, 0]));
+        assert!(version.at_least(&[5, 7, 8]));
+        assert!(!version.at_least(&[8, 0, 14]));
+        assert_eq!(version.to_string(), "8.0");
+        assert!(Version::parse("8.x").is_err());
+        assert!(Version::parse("").is_err());
+    }
+
+    #[test]
+    fn test_mysql_5_7() {
+        let sql = "WITH a AS (SELECT 1 AS x) SELECT ROW_NUMBER() OVER (ORDER BY x) FROM a;";
+        assert_eq!(
+            found(sql, &MySqlDialect {}, "5.7"),
+            [("WITH", "8.0".to_string()), ("OVER", "8.0".to_string())]
+        );
+        assert!(found(sql, &MySqlDialect {}, "8.0").is_empty());
+    }
+
+    #[test]
+    fn test_location() {
+        let sql = "SELECT 1;\nWITH a AS (SELECT 1) SELECT * FROM a;";
+        let version = Version::parse("5.7").unwrap();
+        let found = unsupported(sql, &MySqlDialect {}, &version);
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].start.line, found[0].start.column), (2, 1));
+        assert_eq!((found[0].end.line, found[0].end.column), (2, 5));
+    }
+
+    #[test]
+    fn test_postgres_and_sqlite() {
+        let merge = "MERGE INTO t USING s ON t.id = s.id WHEN MATCHED THEN DELETE;";
+        assert_eq!(
+            found(merge, &PostgreSqlDialect {}, "12"),
+            [("MERGE", "15".to_string())]
+        );
+        assert!(found(merge, &PostgreSqlDialect {}, "15").is_empty());
+
+        let upsert = "INSERT INTO t (a) VALUES (1) ON CONFLICT (a) DO NOTHING RETURNING a;";
+        assert_eq!(
+            found(upsert, &SQLiteDialect {}, "3.24"),
+            [("RETURNING", "3.35.0".to_string())]
+        );
+        assert_eq!(found(upsert, &SQLiteDialect {}, "3.22").len(), 2);
+    }
+
+    #[test]
+    fn test_generated_columns() {
+        let sql = "CREATE TABLE t (a INT GENERATED ALWAYS AS (b + 1) STORED, c INT GENERATED ALWAYS AS IDENTITY);";
+        assert_eq!(
+            found(sql, &PostgreSqlDialect {}, "9.6"),
+            [
+                ("GENERATED ALWAYS AS ... STORED", "12".to_string()),
+                ("GENERATED AS IDENTITY", "10".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_broken_statements_and_other_dialects_are_skipped() {
+        let sql = "SELECT FROM;\nWITH a AS (SELECT 1) SELECT * FROM a;";
+        assert_eq!(found(sql, &MySqlDialect {}, "5.7").len(), 1);
+        assert!(found(sql, &GenericDialect {}, "1").is_empty());
+    }
+}
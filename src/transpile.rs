@@ -0,0 +1,1489 @@
+//! `sqlex transpile`: translate SQL written for one dialect into another.
+//!
+//! Names are respelled in the source's tokens, using the roles the case rules give
+//! words (see [`crate::casing`]), and the SQL is parsed with the source dialect. The
+//! AST is then visited: nodes the target spells differently are rewritten in place,
+//! or replaced by the target's spelling parsed with the target dialect. The rewritten
+//! statements are printed and laid out by the formatter, and the comments of the
+//! source are put back around them from the token stream.
+//!
+//! Constructs without an equivalent are left as they are and reported, as are
+//! statements the target dialect cannot parse once translated. Only MySQL to
+//! PostgreSQL is supported so far.
+
+use crate::casing::{token_roles, Role};
+use crate::checker::{collect_sources, get_dialect, process, Inputs, Source};
+use crate::config::Resolver;
+use crate::formatter::{format_sql, FormatConfig};
+use crate::highlight::SourceHighlighter;
+use crate::i18n::Messages;
+use crate::linter::LintConfig;
+use crate::target::database_name;
+use anyhow::{anyhow, bail, Context, Result};
+use colored::Colorize;
+use sqlparser::ast::{
+    AlterColumnOperation, AlterTableOperation, BinaryOperator, CastKind, ColumnDef, ColumnOption,
+    CreateTableOptions, DataType, ExactNumberInfo, Expr, Function, FunctionArg, FunctionArgExpr,
+    FunctionArguments, GeneratedAs, Insert, Interval, KeyOrIndexDisplay, LimitClause,
+    ObjectNamePart, Offset, OffsetRows, OnInsert, Query, Select, Spanned, SqlOption, Statement,
+    TableConstraint, TableFactor, TimezoneInfo, Value, ValueWithSpan, Visit, VisitMut, Visitor,
+    VisitorMut,
+};
+use sqlparser::dialect::{Dialect, MySqlDialect, PostgreSqlDialect};
+use sqlparser::keywords::Keyword;
+use sqlparser::parser::Parser;
+use sqlparser::tokenizer::{Location, Span, Token, TokenWithSpan, Tokenizer, Whitespace, Word};
+use std::fs;
+use std::ops::ControlFlow;
+use std::path::{Component, Path, PathBuf};
+
+/// Dialects `transpile` translates between, as `(from, to)`.
+pub const TRANSLATIONS: &[(&str, &str)] = &[("mysql", "postgres")];
+
+/// PostgreSQL's reserved words, which cannot be used as unquoted names.
+const POSTGRES_RESERVED: &[&str] = &[
+    "ALL",
+    "ANALYSE",
+    "ANALYZE",
+    "AND",
+    "ANY",
+    "ARRAY",
+    "AS",
+    "ASC",
+    "ASYMMETRIC",
+    "AUTHORIZATION",
+    "BINARY",
+    "BOTH",
+    "CASE",
+    "CAST",
+    "CHECK",
+    "COLLATE",
+    "COLLATION",
+    "COLUMN",
+    "CONCURRENTLY",
+    "CONSTRAINT",
+    "CREATE",
+    "CROSS",
+    "CURRENT_CATALOG",
+    "CURRENT_DATE",
+    "CURRENT_ROLE",
+    "CURRENT_SCHEMA",
+    "CURRENT_TIME",
+    "CURRENT_TIMESTAMP",
+    "CURRENT_USER",
+    "DEFAULT",
+    "DEFERRABLE",
+    "DESC",
+    "DISTINCT",
+    "DO",
+    "ELSE",
+    "END",
+    "EXCEPT",
+    "FALSE",
+    "FETCH",
+    "FOR",
+    "FOREIGN",
+    "FREEZE",
+    "FROM",
+    "FULL",
+    "GRANT",
+    "GROUP",
+    "HAVING",
+    "ILIKE",
+    "IN",
+    "INITIALLY",
+    "INNER",
+    "INTERSECT",
+    "INTO",
+    "IS",
+    "ISNULL",
+    "JOIN",
+    "LATERAL",
+    "LEADING",
+    "LEFT",
+    "LIKE",
+    "LIMIT",
+    "LOCALTIME",
+    "LOCALTIMESTAMP",
+    "NATURAL",
+    "NOT",
+    "NOTNULL",
+    "NULL",
+    "OFFSET",
+    "ON",
+    "ONLY",
+    "OR",
+    "ORDER",
+    "OUTER",
+    "OVERLAPS",
+    "PLACING",
+    "PRIMARY",
+    "REFERENCES",
+    "RETURNING",
+    "RIGHT",
+    "SELECT",
+    "SESSION_USER",
+    "SIMILAR",
+    "SOME",
+    "SYMMETRIC",
+    "SYSTEM_USER",
+    "TABLE",
+    "TABLESAMPLE",
+    "THEN",
+    "TO",
+    "TRAILING",
+    "TRUE",
+    "UNION",
+    "UNIQUE",
+    "USER",
+    "USING",
+    "VARIADIC",
+    "VERBOSE",
+    "WHEN",
+    "WHERE",
+    "WINDOW",
+    "WITH",
+];
+
+/// Reserved words MySQL also reads as functions when written without parentheses, with
+/// the same meaning in PostgreSQL.
+const SHARED_FUNCTION_WORDS: &[&str] = &[
+    "CURRENT_DATE",
+    "CURRENT_TIME",
+    "CURRENT_TIMESTAMP",
+    "CURRENT_USER",
+    "LOCALTIME",
+    "LOCALTIMESTAMP",
+];
+
+/// MySQL functions PostgreSQL has nothing like.
+const NO_EQUIVALENT_FUNCTIONS: &[&str] = &[
+    "FIELD",
+    "FIND_IN_SET",
+    "FOUND_ROWS",
+    "GROUP_CONCAT",
+    "INET_ATON",
+    "INET_NTOA",
+    "LAST_INSERT_ID",
+    "WEEK",
+    "YEARWEEK",
+];
+
+/// MySQL table options that only configure MySQL's storage, dropped in translation.
+const STORAGE_OPTIONS: &[&str] = &[
+    "ENGINE",
+    "CHARSET",
+    "CHARACTER SET",
+    "COLLATE",
+    "ROW_FORMAT",
+];
+
+/// MySQL `DATE_FORMAT` specifiers and the `TO_CHAR` patterns for them.
+const DATE_FORMAT_SPECIFIERS: &[(char, &str)] = &[
+    ('Y', "YYYY"),
+    ('y', "YY"),
+    ('m', "MM"),
+    ('c', "FMMM"),
+    ('d', "DD"),
+    ('e', "FMDD"),
+    ('H', "HH24"),
+    ('k', "FMHH24"),
+    ('h', "HH12"),
+    ('I', "HH12"),
+    ('l', "FMHH12"),
+    ('i', "MI"),
+    ('s', "SS"),
+    ('S', "SS"),
+    ('f', "US"),
+    ('p', "AM"),
+    ('M', "FMMonth"),
+    ('b', "Mon"),
+    ('W', "FMDay"),
+    ('a', "Dy"),
+    ('j', "DDD"),
+    ('T', "HH24:MI:SS"),
+    ('r', "HH12:MI:SS AM"),
+];
+
+/// Why a construct was not translated.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reason {
+    /// The target has no equivalent of the construct, e.g. `GROUP_CONCAT`, which is
+    /// left as it is.
+    NoEquivalent(String),
+    /// The target dialect cannot parse the translated statement; holds the error.
+    DoesNotParse(String),
+    /// `||`, which MySQL reads as `OR` unless `sql_mode` has `PIPES_AS_CONCAT`, left
+    /// as the string concatenation it is in the target.
+    PipesAsConcat,
+}
+
+/// A construct [`transpile_sql`] could not translate, located in the source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Untranslated {
+    pub start: Location,
+    pub end: Location,
+    pub reason: Reason,
+}
+
+/// SQL translated by [`transpile_sql`].
+#[derive(Debug, Clone)]
+pub struct Transpiled {
+    pub sql: String,
+    /// What could not be translated, in source order.
+    pub untranslated: Vec<Untranslated>,
+}
+
+/// Translate `content` from the dialect `from` to the dialect `to`, laid out as
+/// `format` and the case rules of `lint` ask.
+///
+/// Fails when there is no translation between the dialects or `content` does not parse.
+pub fn transpile_sql(
+    content: &str,
+    from: &str,
+    to: &str,
+    format: &FormatConfig,
+    lint: &LintConfig,
+) -> Result<Transpiled> {
+    let (source, target) = (get_dialect(from)?, get_dialect(to)?);
+    if !(source.is::<MySqlDialect>() && target.is::<PostgreSqlDialect>()) {
+        let supported: Vec<String> = TRANSLATIONS
+            .iter()
+            .map(|(from, to)| format!("{} to {}", from, to))
+            .collect();
+        bail!(
+            "Unsupported translation: {} to {} (supported: {})",
+            from,
+            to,
+            supported.join(", ")
+        );
+    }
+    let syntax_error =
+        |err: &dyn std::fmt::Display| anyhow!("Cannot transpile SQL with syntax errors: {}", err);
+    let tokens = Tokenizer::new(source.as_ref(), content)
+        .tokenize_with_location()
+        .map_err(|err| syntax_error(&err))?;
+    let statements = Parser::new(source.as_ref())
+        .with_tokens_with_locations(respell_names(tokens, source.as_ref()))
+        .parse_statements()
+        .map_err(|err| syntax_error(&err))?;
+    let comments = Comments::place(content, source.as_ref(), statements.len())?;
+
+    let mut untranslated = Vec::new();
+    let mut translated = Vec::new();
+    for (i, original) in statements.iter().enumerate() {
+        let found = untranslatable(original);
+        let mut statement = original.clone();
+        if let ControlFlow::Break(err) = VisitMut::visit(&mut statement, &mut Translator) {
+            return Err(err);
+        }
+        let indexes = take_indexes(&mut statement);
+
+        let mut parses = true;
+        let mut chunk: String = comments.before[i]
+            .iter()
+            .map(|comment| format!("{}\n", comment))
+            .collect();
+        let laid_out = lay_out(&statement, target.as_ref(), format.indent_width);
+        for sql in std::iter::once(laid_out).chain(indexes) {
+            let sql = format!("{};\n", sql);
+            // The formatter reflows queries and DML, and recases the rest. What the
+            // target cannot parse is printed as it is.
+            chunk +=
+                &format_sql(&sql, target.as_ref(), format, lint).unwrap_or_else(|_| sql.clone());
+            if let Err(err) = Parser::parse_sql(target.as_ref(), &sql) {
+                // A construct reported already is the likely cause; report the
+                // statement only when nothing else explains it.
+                let explained = found
+                    .iter()
+                    .any(|(reason, _)| matches!(reason, Reason::NoEquivalent(_)));
+                if parses && !explained {
+                    let span = original.span();
+                    if span.start.line > 0 {
+                        untranslated.push(Untranslated {
+                            start: span.start,
+                            end: span.end,
+                            reason: Reason::DoesNotParse(err.to_string()),
+                        });
+                    }
+                }
+                parses = false;
+            }
+        }
+        if !comments.after[i].is_empty() {
+            // On the line the statement ends on, as in the source.
+            chunk.truncate(chunk.trim_end().len());
+            chunk += &format!(" {}\n", comments.after[i].join(" "));
+        }
+        translated.push(chunk);
+        untranslated.extend(found.into_iter().map(|(reason, span)| Untranslated {
+            start: span.start,
+            end: span.end,
+            reason,
+        }));
+    }
+    untranslated.sort_by_key(|u| (u.start.line, u.start.column));
+    let mut sql = translated.join("\n");
+    if !comments.end.is_empty() {
+        if !sql.is_empty() {
+            sql.push('\n');
+        }
+        sql += &comments.end.join("\n");
+        sql.push('\n');
+    }
+    Ok(Transpiled { sql, untranslated })
+}
+
+/// The comments of the source, placed by the statements they belong with.
+struct Comments {
+    /// Per statement: those on the lines before it, then those inside it, which
+    /// have no place in the printed statement.
+    before: Vec<Vec<String>>,
+    /// Per statement: those after it on the line it ends on.
+    after: Vec<Vec<String>>,
+    /// Those after the last statement.
+    end: Vec<String>,
+}
+
+impl Comments {
+    /// Place the comments of `content`, which parses to `count` statements.
+    fn place(content: &str, dialect: &dyn Dialect, count: usize) -> Result<Self> {
+        let tokens = Tokenizer::new(dialect, content).tokenize_with_location()?;
+        let comments: Vec<(Location, String)> = tokens
+            .iter()
+            .filter_map(|t| match &t.token {
+                Token::Whitespace(whitespace) => Some((t.span.start, comment(whitespace)?)),
+                _ => None,
+            })
+            .collect();
+
+        // Where each statement starts and ends, its semicolon included.
+        let mut spans = Vec::new();
+        let mut parser = Parser::new(dialect).with_tokens_with_locations(tokens.clone());
+        loop {
+            while parser.consume_token(&Token::SemiColon) {}
+            if parser.peek_token_ref().token == Token::EOF {
+                break;
+            }
+            let start = parser.peek_token_ref().span.start;
+            parser.parse_statement()?;
+            let _ = parser.consume_token(&Token::SemiColon);
+            // The parser may have stepped onto whitespace looking for more.
+            let end = tokens
+                .iter()
+                .take(parser.get_current_index() + 1)
+                .rev()
+                .find(|t| !matches!(t.token, Token::Whitespace(_)))
+                .map_or(start, |t| t.span.end);
+            spans.push((start, end));
+        }
+        if spans.len() != count {
+            // Not the statements the file parses to; keep the comments at the end.
+            spans.clear();
+        }
+
+        let mut placed = Self {
+            before: vec![Vec::new(); count],
+            after: vec![Vec::new(); count],
+            end: Vec::new(),
+        };
+        let at = |location: Location| (location.line, location.column);
+        for (location, text) in comments {
+            // The first statement ending after the comment starts.
+            match spans.iter().position(|(_, end)| at(*end) > at(location)) {
+                Some(i) if i > 0 && location.line == spans[i - 1].1.line => {
+                    placed.after[i - 1].push(text)
+                }
+                Some(i) => placed.before[i].push(text),
+                None => match spans.last() {
+                    Some((_, end)) if location.line == end.line => {
+                        placed.after[spans.len() - 1].push(text)
+                    }
+                    _ => placed.end.push(text),
+                },
+            }
+        }
+        Ok(placed)
+    }
+}
+
+/// The text of a comment, written for PostgreSQL.
+fn comment(whitespace: &Whitespace) -> Option<String> {
+    match whitespace {
+        Whitespace::SingleLineComment { comment, prefix } => {
+            // PostgreSQL has no `#` comments.
+            let prefix = if prefix == "#" { "--" } else { prefix };
+            Some(format!("{}{}", prefix, comment.trim_end()))
+        }
+        Whitespace::MultiLineComment(text) => Some(format!("/*{}*/", text)),
+        _ => None,
+    }
+}
+
+/// `statement` printed with sqlparser's layout (one column of a `CREATE TABLE` per
+/// line, ...), which the formatter keeps for statements it does not reflow, indented by
+/// `indent_width`. Printed on one line when the layout would change what it means, as
+/// it could for a string literal over several lines.
+fn lay_out(statement: &Statement, dialect: &dyn Dialect, indent_width: usize) -> String {
+    let plain = statement.to_string();
+    let laid_out = format!("{:#}", statement)
+        .lines()
+        .map(|line| {
+            let text = line.trim_start();
+            let depth = (line.len() - text.len()) / 2;
+            format!("{}{}", " ".repeat(depth * indent_width), text.trim_end())
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    match (
+        Parser::parse_sql(dialect, &plain),
+        Parser::parse_sql(dialect, &laid_out),
+    ) {
+        (Ok(before), Ok(after)) if before == after => laid_out,
+        _ => plain,
+    }
+}
+
+/// Spell the names in `tokens` as PostgreSQL reads them. Backquoted names are
+/// unquoted, and unquoted names PostgreSQL reserves are double quoted, such as a column
+/// called `user`, which it would read as `CURRENT_USER`. Function names are left alone,
+/// and so are the words both databases read as functions.
+fn respell_names(mut tokens: Vec<TokenWithSpan>, dialect: &dyn Dialect) -> Vec<TokenWithSpan> {
+    let roles = token_roles(&tokens, dialect, &LintConfig::default());
+    for (token, role) in tokens.iter_mut().zip(roles) {
+        let Token::Word(word) = &mut token.token else {
+            continue;
+        };
+        if word.quote_style == Some('`') {
+            unquote(word);
+        } else if role == Some(Role::Identifier) {
+            let name = word.value.to_uppercase();
+            if POSTGRES_RESERVED.contains(&name.as_str())
+                && !SHARED_FUNCTION_WORDS.contains(&name.as_str())
+            {
+                *word = Word {
+                    value: word.value.to_lowercase(),
+                    quote_style: Some('"'),
+                    keyword: Keyword::NoKeyword,
+                };
+            }
+        }
+    }
+    tokens
+}
+
+/// A backquoted name, folded to lower case as PostgreSQL folds unquoted names, so that
+/// it still matches where it is written without quotes. It is only double quoted when
+/// PostgreSQL needs it to be.
+fn unquote(word: &mut Word) {
+    let name = word.value.to_lowercase();
+    let plain = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_lowercase() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        && !POSTGRES_RESERVED.contains(&name.to_uppercase().as_str());
+    *word = Word {
+        value: name,
+        quote_style: if plain { None } else { Some('"') },
+        keyword: Keyword::NoKeyword,
+    };
+}
+
+/// Rewrites the nodes PostgreSQL spells differently. Nodes below are rewritten before
+/// the nodes above them, so that a call rewritten as SQL has its arguments rewritten.
+struct Translator;
+
+impl VisitorMut for Translator {
+    type Break = anyhow::Error;
+
+    fn post_visit_query(&mut self, query: &mut Query) -> ControlFlow<Self::Break> {
+        // `LIMIT offset, limit`
+        if let Some(LimitClause::OffsetCommaLimit { offset, limit }) = &query.limit_clause {
+            query.limit_clause = Some(LimitClause::LimitOffset {
+                limit: Some(limit.clone()),
+                offset: Some(Offset {
+                    value: offset.clone(),
+                    rows: OffsetRows::None,
+                }),
+                limit_by: Vec::new(),
+            });
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_expr(&mut self, expr: &mut Expr) -> ControlFlow<Self::Break> {
+        match translate(expr) {
+            Ok(Some(replacement)) => *expr = replacement,
+            Ok(None) => {}
+            Err(err) => return ControlFlow::Break(err),
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_statement(&mut self, statement: &mut Statement) -> ControlFlow<Self::Break> {
+        match statement {
+            Statement::CreateTable(create) => {
+                create.columns.iter_mut().for_each(column);
+                create.constraints.iter_mut().for_each(unique_key);
+                drop_storage_options(&mut create.table_options);
+            }
+            Statement::AlterTable(alter) => {
+                for operation in &mut alter.operations {
+                    match operation {
+                        AlterTableOperation::AddColumn { column_def, .. } => column(column_def),
+                        AlterTableOperation::AddConstraint { constraint, .. } => {
+                            unique_key(constraint)
+                        }
+                        AlterTableOperation::ChangeColumn { data_type, .. }
+                        | AlterTableOperation::ModifyColumn { data_type, .. }
+                        | AlterTableOperation::AlterColumn {
+                            op: AlterColumnOperation::SetDataType { data_type, .. },
+                            ..
+                        } => column_type(data_type),
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_value(&mut self, value: &mut ValueWithSpan) -> ControlFlow<Self::Break> {
+        // MySQL reads `"text"` as a string, PostgreSQL as a name.
+        if let Value::DoubleQuotedString(text) = &mut value.value {
+            value.value = Value::SingleQuotedString(std::mem::take(text));
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+/// Rewrite an expression PostgreSQL spells differently in place, or return the
+/// expression to replace it with.
+fn translate(expr: &mut Expr) -> Result<Option<Expr>> {
+    match expr {
+        Expr::Function(call) => function(call),
+        Expr::Interval(value) => interval(value),
+        // `a <=> b`
+        Expr::BinaryOp {
+            left,
+            op: BinaryOperator::Spaceship,
+            right,
+        } => Ok(Some(Expr::IsNotDistinctFrom(left.clone(), right.clone()))),
+        // `a DIV b`
+        Expr::BinaryOp {
+            left,
+            op: BinaryOperator::MyIntegerDivide,
+            right,
+        } => parse_expr(&format!("DIV({}, {})", left, right)).map(Some),
+        // `CONVERT(a, type)`
+        Expr::Convert {
+            expr,
+            data_type: Some(data_type),
+            charset: None,
+            ..
+        } => Ok(Some(Expr::Cast {
+            kind: CastKind::Cast,
+            expr: expr.clone(),
+            data_type: data_type_for(data_type, true).unwrap_or_else(|| data_type.clone()),
+            array: false,
+            format: None,
+        })),
+        Expr::Cast { data_type, .. } => {
+            if let Some(translated) = data_type_for(data_type, true) {
+                *data_type = translated;
+            }
+            Ok(None)
+        }
+        // `a REGEXP b`
+        Expr::RLike {
+            negated,
+            expr,
+            pattern,
+            ..
+        } => Ok(Some(Expr::BinaryOp {
+            left: expr.clone(),
+            op: if *negated {
+                BinaryOperator::PGRegexNotMatch
+            } else {
+                BinaryOperator::PGRegexMatch
+            },
+            right: pattern.clone(),
+        })),
+        _ => Ok(None),
+    }
+}
+
+/// A column definition: `TINYINT(1)` flags become booleans, with `0` and `1` defaults
+/// as `FALSE` and `TRUE`, `AUTO_INCREMENT` becomes an identity, and character sets and
+/// collations, which name MySQL ones, are dropped.
+fn column(column: &mut ColumnDef) {
+    let boolean = column.data_type == DataType::TinyInt(Some(1));
+    column.options.retain(|option| {
+        !matches!(
+            option.option,
+            ColumnOption::CharacterSet(_) | ColumnOption::Collation(_)
+        )
+    });
+    for option in &mut column.options {
+        match &mut option.option {
+            ColumnOption::DialectSpecific(tokens) if matches!(tokens.first(), Some(Token::Word(w)) if w.keyword == Keyword::AUTO_INCREMENT) =>
+            {
+                option.option = ColumnOption::Generated {
+                    generated_as: GeneratedAs::ByDefault,
+                    sequence_options: Some(Vec::new()),
+                    generation_expr: None,
+                    generation_expr_mode: None,
+                    generated_keyword: true,
+                };
+            }
+            ColumnOption::Default(Expr::Value(ValueWithSpan {
+                value: value @ Value::Number(..),
+                ..
+            })) if boolean => match value {
+                Value::Number(n, _) if n == "0" => *value = Value::Boolean(false),
+                Value::Number(n, _) if n == "1" => *value = Value::Boolean(true),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+    if boolean {
+        column.data_type = DataType::Boolean;
+    } else {
+        column_type(&mut column.data_type);
+    }
+}
+
+fn column_type(data_type: &mut DataType) {
+    if let Some(translated) = data_type_for(data_type, false) {
+        *data_type = translated;
+    }
+}
+
+/// PostgreSQL's spelling of a MySQL data type it does not have, as a column type or as
+/// the type of a `CAST`.
+fn data_type_for(data_type: &DataType, cast: bool) -> Option<DataType> {
+    Some(match data_type {
+        // A string of any length, where PostgreSQL would cut it to one character.
+        DataType::Char(None) if cast => DataType::Text,
+        DataType::TinyInt(_) | DataType::TinyIntUnsigned(_) => DataType::SmallInt(None),
+        DataType::SmallIntUnsigned(_) | DataType::MediumInt(_) | DataType::MediumIntUnsigned(_) => {
+            DataType::Integer(None)
+        }
+        // `CAST(x AS SIGNED)` and `CAST(x AS UNSIGNED)` too.
+        DataType::IntUnsigned(_)
+        | DataType::IntegerUnsigned(_)
+        | DataType::Signed
+        | DataType::SignedInteger
+        | DataType::Unsigned
+        | DataType::UnsignedInteger => DataType::BigInt(None),
+        DataType::BigIntUnsigned(_) => DataType::Numeric(ExactNumberInfo::Precision(20)),
+        DataType::DecimalUnsigned(info) => DataType::Decimal(*info),
+        DataType::Datetime(precision) => DataType::Timestamp(*precision, TimezoneInfo::None),
+        DataType::Double(_) | DataType::DoublePrecisionUnsigned => DataType::DoublePrecision,
+        DataType::TinyText | DataType::MediumText | DataType::LongText => DataType::Text,
+        DataType::Blob(_)
+        | DataType::TinyBlob
+        | DataType::MediumBlob
+        | DataType::LongBlob
+        | DataType::Binary(_)
+        | DataType::Varbinary(_) => DataType::Bytea,
+        _ => return None,
+    })
+}
+
+/// `UNIQUE KEY name (...)` as a plain `CONSTRAINT name UNIQUE (...)`.
+fn unique_key(constraint: &mut TableConstraint) {
+    let TableConstraint::Unique(unique) = constraint else {
+        return;
+    };
+    if unique.index_type_display != KeyOrIndexDisplay::None || unique.index_name.is_some() {
+        unique.name = unique.name.take().or(unique.index_name.take());
+        unique.index_name = None;
+        unique.index_type = None;
+        unique.index_type_display = KeyOrIndexDisplay::None;
+    }
+}
+
+/// Drop the MySQL storage options of a `CREATE TABLE`.
+fn drop_storage_options(options: &mut CreateTableOptions) {
+    let CreateTableOptions::Plain(plain) = options else {
+        return;
+    };
+    plain.retain(|option| !option_key(option).is_some_and(is_storage_option));
+    if plain.is_empty() {
+        *options = CreateTableOptions::None;
+    }
+}
+
+/// The name of a `key = value` table option.
+fn option_key(option: &SqlOption) -> Option<&str> {
+    match option {
+        SqlOption::KeyValue { key, .. } => Some(&key.value),
+        SqlOption::NamedParenthesizedList(list) => Some(&list.key.value),
+        _ => None,
+    }
+}
+
+fn is_storage_option(key: &str) -> bool {
+    let key = key.to_uppercase();
+    STORAGE_OPTIONS.contains(&key.trim_start_matches("DEFAULT "))
+}
+
+/// `KEY` and `INDEX` entries of a `CREATE TABLE` as `CREATE INDEX` statements to run
+/// after it, since PostgreSQL only declares indexes on their own.
+fn take_indexes(statement: &mut Statement) -> Vec<String> {
+    let Statement::CreateTable(create) = statement else {
+        return Vec::new();
+    };
+    let mut indexes = Vec::new();
+    for constraint in std::mem::take(&mut create.constraints) {
+        match constraint {
+            TableConstraint::Index(index) => {
+                let columns: Vec<String> = index.columns.iter().map(ToString::to_string).collect();
+                let name = index.name.map(|n| format!("{} ", n)).unwrap_or_default();
+                indexes.push(format!(
+                    "CREATE INDEX {}ON {} ({})",
+                    name,
+                    create.name,
+                    columns.join(", ")
+                ));
+            }
+            other => create.constraints.push(other),
+        }
+    }
+    indexes
+}
+
+/// A call to a MySQL function PostgreSQL spells differently.
+fn function(function: &Function) -> Result<Option<Expr>> {
+    let (Some(name), Some(args)) = (function_name(function), function_args(function)) else {
+        return Ok(None);
+    };
+    let args_sql: Vec<String> = args.iter().map(ToString::to_string).collect();
+    let sql = match (name.as_str(), args_sql.as_slice()) {
+        ("IFNULL", [value, fallback]) => format!("COALESCE({}, {})", value, fallback),
+        ("IF", [condition, then, otherwise]) => {
+            format!(
+                "CASE WHEN {} THEN {} ELSE {} END",
+                condition, then, otherwise
+            )
+        }
+        ("CURDATE", []) => "CURRENT_DATE".to_string(),
+        ("CURTIME", []) => "CURRENT_TIME".to_string(),
+        ("UTC_TIMESTAMP", []) => "(CURRENT_TIMESTAMP AT TIME ZONE 'UTC')".to_string(),
+        ("DATE_ADD" | "ADDDATE", [date, interval]) => format!("({} + {})", date, interval),
+        ("DATE_SUB" | "SUBDATE", [date, interval]) => format!("({} - {})", date, interval),
+        ("DATEDIFF", [end, start]) => {
+            format!("(CAST({} AS DATE) - CAST({} AS DATE))", end, start)
+        }
+        ("DATE", [date]) => format!("CAST({} AS DATE)", date),
+        ("UNIX_TIMESTAMP", []) => "EXTRACT(EPOCH FROM CURRENT_TIMESTAMP)".to_string(),
+        ("UNIX_TIMESTAMP", [date]) => format!("EXTRACT(EPOCH FROM {})", date),
+        ("FROM_UNIXTIME", [seconds]) => format!("TO_TIMESTAMP({})", seconds),
+        ("YEAR" | "MONTH" | "DAY" | "HOUR" | "MINUTE" | "SECOND", [date]) => {
+            format!("EXTRACT({} FROM {})", name, date)
+        }
+        ("DAYOFMONTH", [date]) => format!("EXTRACT(DAY FROM {})", date),
+        ("DATE_FORMAT", [date, _]) => match date_format(&args) {
+            Some(pattern) => format!("TO_CHAR({}, {})", date, pattern),
+            None => return Ok(None),
+        },
+        ("STR_TO_DATE", [text, _]) => match date_format(&args) {
+            Some(pattern) => format!("TO_TIMESTAMP({}, {})", text, pattern),
+            None => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+    parse_expr(&sql).map(Some)
+}
+
+/// Upper-cased name of a call to an unqualified function. Window functions are left
+/// alone.
+fn function_name(function: &Function) -> Option<String> {
+    if function.over.is_some() {
+        return None;
+    }
+    match function.name.0.as_slice() {
+        [ObjectNamePart::Identifier(ident)] if ident.quote_style.is_none() => {
+            Some(ident.value.to_uppercase())
+        }
+        _ => None,
+    }
+}
+
+/// Arguments of a call, when each is a plain expression; none for a call without
+/// parentheses such as `CURRENT_DATE`.
+fn function_args(function: &Function) -> Option<Vec<&Expr>> {
+    match &function.args {
+        FunctionArguments::None => Some(Vec::new()),
+        FunctionArguments::List(list)
+            if list.duplicate_treatment.is_none() && list.clauses.is_empty() =>
+        {
+            list.args
+                .iter()
+                .map(|arg| match arg {
+                    FunctionArg::Unnamed(FunctionArgExpr::Expr(expr)) => Some(expr),
+                    _ => None,
+                })
+                .collect()
+        }
+        _ => None,
+    }
+}
+
+/// The format of a `DATE_FORMAT` or `STR_TO_DATE` call, its second argument, as a
+/// quoted `TO_CHAR` pattern.
+fn date_format(args: &[&Expr]) -> Option<String> {
+    let format = match args.get(1)? {
+        Expr::Value(ValueWithSpan {
+            value: Value::SingleQuotedString(format) | Value::DoubleQuotedString(format),
+            ..
+        }) => format,
+        _ => return None,
+    };
+    let pattern = to_char_pattern(format)?;
+    Some(format!("'{}'", pattern.replace('\'', "''")))
+}
+
+/// A MySQL date format as a `TO_CHAR` pattern, or `None` when it uses a specifier
+/// `TO_CHAR` has no pattern for. Literal text with letters in it is double quoted, so
+/// that it is not read as patterns.
+fn to_char_pattern(format: &str) -> Option<String> {
+    let mut pattern = String::new();
+    let mut literal = String::new();
+    let flush = |pattern: &mut String, literal: &mut String| {
+        if literal.chars().any(|c| c.is_ascii_alphabetic()) {
+            *pattern += &format!("\"{}\"", literal.replace('"', "\\\""));
+        } else {
+            *pattern += literal;
+        }
+        literal.clear();
+    };
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '%' => match chars.next()? {
+                '%' => literal.push('%'),
+                specifier => {
+                    let (_, to_char) = DATE_FORMAT_SPECIFIERS
+                        .iter()
+                        .find(|(s, _)| *s == specifier)?;
+                    flush(&mut pattern, &mut literal);
+                    pattern += to_char;
+                }
+            },
+            c => literal.push(c),
+        }
+    }
+    flush(&mut pattern, &mut literal);
+    Some(pattern)
+}
+
+/// `INTERVAL 7 DAY`: MySQL writes the quantity as a number or an expression, while
+/// PostgreSQL wants a string.
+fn interval(interval: &mut Interval) -> Result<Option<Expr>> {
+    match interval.value.as_mut() {
+        Expr::Value(ValueWithSpan {
+            value: Value::SingleQuotedString(_),
+            ..
+        }) => return Ok(None),
+        Expr::Value(ValueWithSpan {
+            value: value @ Value::Number(..),
+            ..
+        }) => {
+            *value = Value::SingleQuotedString(value.to_string());
+            return Ok(None);
+        }
+        _ => {}
+    }
+    // A column or an expression: that many single units.
+    let unit = Interval {
+        value: Box::new(Expr::value(Value::SingleQuotedString("1".to_string()))),
+        ..interval.clone()
+    };
+    let sql = format!("({} * {})", interval.value, Expr::Interval(unit));
+    parse_expr(&sql).map(Some)
+}
+
+/// `sql` parsed as a PostgreSQL expression.
+fn parse_expr(sql: &str) -> Result<Expr> {
+    Ok(Parser::new(&PostgreSqlDialect {})
+        .try_with_sql(sql)?
+        .parse_expr()?)
+}
+
+/// The constructs in `statement` that are not translated, or not for certain, and
+/// where they are.
+fn untranslatable(statement: &Statement) -> Vec<(Reason, Span)> {
+    let mut finder = Untranslatable::default();
+    let _ = statement.visit(&mut finder);
+    finder.found
+}
+
+#[derive(Default)]
+struct Untranslatable {
+    found: Vec<(Reason, Span)>,
+}
+
+impl Untranslatable {
+    /// A construct PostgreSQL has no equivalent of.
+    fn add(&mut self, construct: &str, span: Span) {
+        self.found
+            .push((Reason::NoEquivalent(construct.to_string()), span));
+    }
+
+    fn column(&mut self, column: &ColumnDef) {
+        if let DataType::Enum(..) | DataType::Set(_) = column.data_type {
+            let name = if matches!(column.data_type, DataType::Enum(..)) {
+                "ENUM"
+            } else {
+                "SET"
+            };
+            self.add(name, column.span());
+        }
+        for option in &column.options {
+            match &option.option {
+                ColumnOption::OnUpdate(expr) => self.add("ON UPDATE", expr.span()),
+                ColumnOption::Comment(_) => self.add("COMMENT", column.span()),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Visitor for Untranslatable {
+    type Break = ();
+
+    fn pre_visit_select(&mut self, select: &Select) -> ControlFlow<()> {
+        // `HIGH_PRIORITY`, `SQL_CALC_FOUND_ROWS`, ...
+        if let Some(modifiers) = &select.select_modifiers {
+            let span = select.select_token.0.span;
+            for (set, name) in [
+                (modifiers.high_priority, "HIGH_PRIORITY"),
+                (modifiers.straight_join, "STRAIGHT_JOIN"),
+                (modifiers.sql_small_result, "SQL_SMALL_RESULT"),
+                (modifiers.sql_big_result, "SQL_BIG_RESULT"),
+                (modifiers.sql_buffer_result, "SQL_BUFFER_RESULT"),
+                (modifiers.sql_no_cache, "SQL_NO_CACHE"),
+                (modifiers.sql_calc_found_rows, "SQL_CALC_FOUND_ROWS"),
+            ] {
+                if set {
+                    self.add(name, span);
+                }
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_table_factor(&mut self, table_factor: &TableFactor) -> ControlFlow<()> {
+        if let TableFactor::Table {
+            partitions,
+            index_hints,
+            ..
+        } = table_factor
+        {
+            if !partitions.is_empty() {
+                self.add("PARTITION", table_factor.span());
+            }
+            for hint in index_hints {
+                self.add(&format!("{} INDEX", hint.hint_type), table_factor.span());
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expr(&mut self, expr: &Expr) -> ControlFlow<()> {
+        match expr {
+            Expr::BinaryOp {
+                op: BinaryOperator::Xor,
+                ..
+            } => self.add("XOR", expr.span()),
+            // `@total := @total + 1`
+            Expr::BinaryOp {
+                op: BinaryOperator::Assignment,
+                ..
+            } => self.add(":=", expr.span()),
+            Expr::BinaryOp {
+                op: BinaryOperator::StringConcat,
+                ..
+            } => self.found.push((Reason::PipesAsConcat, expr.span())),
+            Expr::Convert {
+                charset: Some(_), ..
+            } => self.add("CONVERT ... USING", expr.span()),
+            Expr::Function(call) => match function_name(call) {
+                Some(name) if NO_EQUIVALENT_FUNCTIONS.contains(&name.as_str()) => {
+                    self.add(&name, expr.span())
+                }
+                Some(name) if matches!(name.as_str(), "DATE_FORMAT" | "STR_TO_DATE") => {
+                    let args = function_args(call).unwrap_or_default();
+                    if args.len() != 2 || date_format(&args).is_none() {
+                        self.add(&name, expr.span());
+                    }
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_statement(&mut self, statement: &Statement) -> ControlFlow<()> {
+        match statement {
+            Statement::Insert(insert @ Insert { on, .. }) => {
+                let span = insert.insert_token.0.span;
+                if insert.replace_into {
+                    self.add("REPLACE INTO", span);
+                }
+                if insert.ignore {
+                    self.add("IGNORE", span);
+                }
+                if let Some(OnInsert::DuplicateKeyUpdate(assignments)) = on {
+                    let span = Span::union_iter(assignments.iter().map(|a| a.span()));
+                    self.add("ON DUPLICATE KEY UPDATE", span);
+                }
+            }
+            Statement::CreateTable(create) => {
+                create.columns.iter().for_each(|c| self.column(c));
+                for constraint in &create.constraints {
+                    if let TableConstraint::FulltextOrSpatial(index) = constraint {
+                        let name = if index.fulltext {
+                            "FULLTEXT"
+                        } else {
+                            "SPATIAL"
+                        };
+                        self.add(name, constraint.span());
+                    }
+                }
+                if let CreateTableOptions::Plain(options) = &create.table_options {
+                    for option in options {
+                        // The key of an option is parsed without a span; its value
+                        // has one, and a comment neither.
+                        let span = Some(option.span())
+                            .filter(|span| span.start.line > 0)
+                            .unwrap_or_else(|| statement.span());
+                        match option_key(option) {
+                            Some(key) if !is_storage_option(key) => {
+                                self.add(&key.to_uppercase(), span)
+                            }
+                            None if matches!(option, SqlOption::Comment(_)) => {
+                                self.add("COMMENT", span)
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                if create.comment.is_some() {
+                    self.add("COMMENT", statement.span());
+                }
+            }
+            Statement::AlterTable(alter) => {
+                for operation in &alter.operations {
+                    if let AlterTableOperation::AddColumn { column_def, .. } = operation {
+                        self.column(column_def);
+                    }
+                }
+            }
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+/// Translate files and directories to the dialect `to`, from the dialect each one is
+/// configured with. The SQL is written to stdout, each file after a `-- path` comment
+/// when there are several, or with `out_dir` to a file at the same relative path under
+/// it. What could not be translated is reported on stderr.
+///
+/// Returns the number of files that could not be translated in full.
+pub fn transpile(
+    inputs: &Inputs,
+    resolver: &Resolver,
+    to: &str,
+    out_dir: Option<&Path>,
+    jobs: usize,
+    messages: &Messages,
+) -> Result<usize> {
+    let database = database_name(get_dialect(to)?.as_ref()).unwrap_or(to);
+    let sources = collect_sources(inputs, resolver)?;
+    if out_dir.is_some() && sources.iter().any(|s| matches!(s, Source::Text { .. })) {
+        anyhow::bail!("stdin or --sql input cannot be written to --out-dir");
+    }
+    if sources.is_empty() {
+        eprintln!("{}", "No SQL files found".yellow());
+        return Ok(0);
+    }
+
+    let results = process(
+        &sources,
+        resolver,
+        jobs,
+        None,
+        messages,
+        |content, settings| {
+            // A file that cannot be translated is reported without stopping the others.
+            Ok(transpile_sql(
+                content,
+                &settings.dialect,
+                to,
+                &settings.format,
+                &settings.lint,
+            ))
+        },
+    )?;
+
+    let several = sources.len() > 1 && out_dir.is_none();
+    let mut failures = 0;
+    for (source, (content, transpiled)) in sources.iter().zip(results) {
+        let file = source.name();
+        let transpiled = match transpiled {
+            Ok(transpiled) => transpiled,
+            Err(err) => {
+                failures += 1;
+                eprintln!("{}", format!("✗ {} - {:#}", file, err).red());
+                continue;
+            }
+        };
+        if !transpiled.untranslated.is_empty() {
+            failures += 1;
+            eprint!(
+                "{}",
+                render(file, &content, &transpiled.untranslated, database, messages)
+            );
+        }
+
+        match out_dir {
+            Some(dir) => {
+                let out = dir.join(relative(file));
+                if let Some(parent) = out.parent() {
+                    fs::create_dir_all(parent)
+                        .with_context(|| format!("Failed to create: {}", parent.display()))?;
+                }
+                fs::write(&out, &transpiled.sql)
+                    .with_context(|| format!("Failed to write: {}", out.display()))?;
+                println!(
+                    "{}",
+                    messages
+                        .transpiled(file, &out.display().to_string())
+                        .green()
+                );
+            }
+            None => {
+                if several {
+                    println!("-- {}", file);
+                }
+                print!("{}", transpiled.sql);
+            }
+        }
+    }
+
+    Ok(failures)
+}
+
+/// `path` without its root and `..` parts, to place it under an output directory.
+fn relative(path: &str) -> PathBuf {
+    Path::new(path)
+        .components()
+        .filter(|c| matches!(c, Component::Normal(_)))
+        .collect()
+}
+
+/// Text report for one file of what could not be translated, with highlighted source.
+fn render(
+    file: &str,
+    content: &str,
+    untranslated: &[Untranslated],
+    database: &str,
+    messages: &Messages,
+) -> String {
+    let mut out = format!(
+        "{}\n",
+        messages
+            .file_untranslated(file, untranslated.len())
+            .yellow()
+    );
+    for item in untranslated {
+        let (line, column) = (item.start.line as usize, item.start.column as usize);
+        let message = match &item.reason {
+            Reason::NoEquivalent(construct) => messages.no_equivalent(construct, database),
+            Reason::DoesNotParse(error) => messages.translation_does_not_parse(database, error),
+            Reason::PipesAsConcat => messages.pipes_as_concat(database),
+        };
+        out += &format!("  {}\n", messages.untranslated(line, column, &message));
+        out += &format!(
+            "{}\n\n",
+            SourceHighlighter::display_error_with_hint(content, line, column, None, 2)
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transpile(sql: &str) -> Transpiled {
+        transpile_sql(
+            sql,
+            "mysql",
+            "postgres",
+            &FormatConfig::default(),
+            &LintConfig::default(),
+        )
+        .unwrap()
+    }
+
+    /// The translation on one line, for comparing.
+    fn translated(sql: &str) -> String {
+        let transpiled = transpile(sql);
+        assert_eq!(transpiled.untranslated, [], "{}", transpiled.sql);
+        transpiled
+            .sql
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn constructs(sql: &str) -> Vec<String> {
+        transpile(sql)
+            .untranslated
+            .into_iter()
+            .map(|u| match u.reason {
+                Reason::NoEquivalent(construct) => construct,
+                Reason::DoesNotParse(error) => error,
+                Reason::PipesAsConcat => "||".to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_identifiers() {
+        assert_eq!(
+            translated("SELECT `id`, `User Name`, `order` FROM `Users`"),
+            "SELECT id, \"user name\", \"order\" FROM users;"
+        );
+    }
+
+    #[test]
+    fn test_reserved_names_are_quoted() {
+        assert_eq!(
+            translated("SELECT `user`, user, User, CURRENT_USER, t.end FROM t"),
+            "SELECT \"user\", \"user\", \"user\", CURRENT_USER, t.\"end\" FROM t;"
+        );
+        assert_eq!(
+            translated("CREATE TABLE grant (user VARCHAR(10), `check` INT)"),
+            "CREATE TABLE \"grant\" ( \"user\" VARCHAR(10), \"check\" INT );"
+        );
+    }
+
+    #[test]
+    fn test_comments_are_carried_over() {
+        let transpiled = transpile(
+            "-- keep me\n\
+             # hash comment\n\
+             SELECT a FROM t /* why */\n\
+             WHERE b = 1; -- after\n\
+             /* before the insert */\n\
+             INSERT INTO t (a) VALUES (1);\n\
+             -- the end\n",
+        );
+        assert_eq!(transpiled.untranslated, []);
+        assert_eq!(
+            transpiled.sql,
+            "-- keep me\n\
+             -- hash comment\n\
+             /* why */\n\
+             SELECT a\n\
+             FROM t\n\
+             WHERE b = 1; -- after\n\
+             \n\
+             /* before the insert */\n\
+             INSERT INTO t (a)\n\
+             VALUES (1);\n\
+             \n\
+             -- the end\n"
+        );
+
+        assert_eq!(
+            transpile("SELECT a FROM t /* why */").sql,
+            "SELECT a\nFROM t; /* why */\n"
+        );
+        assert_eq!(transpile("-- nothing else\n").sql, "-- nothing else\n");
+    }
+
+    #[test]
+    fn test_limit_offset() {
+        assert_eq!(
+            translated("SELECT id FROM t LIMIT 20, 10"),
+            "SELECT id FROM t LIMIT 10 OFFSET 20;"
+        );
+        assert_eq!(
+            translated("SELECT id FROM t LIMIT 10"),
+            "SELECT id FROM t LIMIT 10;"
+        );
+    }
+
+    #[test]
+    fn test_functions() {
+        assert_eq!(
+            translated("SELECT IFNULL(a, 0), IF(a > 1, 'x', \"y\") FROM t"),
+            "SELECT COALESCE(a, 0), CASE WHEN a > 1 THEN 'x' ELSE 'y' END FROM t;"
+        );
+        assert_eq!(
+            translated("SELECT a DIV 2 FROM t WHERE b REGEXP '^x' AND c NOT REGEXP 'y'"),
+            "SELECT DIV(a, 2) FROM t WHERE b ~ '^x' AND c !~ 'y';"
+        );
+        assert_eq!(
+            translated("SELECT CAST(a AS UNSIGNED), CONVERT(b, CHAR), CAST(c AS CHAR(3)) FROM t"),
+            "SELECT CAST(a AS BIGINT), CAST(b AS TEXT), CAST(c AS CHAR(3)) FROM t;"
+        );
+    }
+
+    #[test]
+    fn test_null_safe_equal() {
+        assert_eq!(
+            translated("SELECT a FROM t WHERE b <=> NULL AND c <=> d"),
+            "SELECT a FROM t WHERE b IS NOT DISTINCT FROM NULL AND c IS NOT DISTINCT FROM d;"
+        );
+    }
+
+    #[test]
+    fn test_pipes_are_reported() {
+        let transpiled = transpile("SELECT a\nFROM t WHERE b || c");
+        assert!(transpiled.sql.contains("b || c"), "{}", transpiled.sql);
+        let [pipes] = transpiled.untranslated.as_slice() else {
+            panic!("{:?}", transpiled.untranslated);
+        };
+        assert_eq!((pipes.start.line, pipes.start.column), (2, 14));
+        assert_eq!(pipes.reason, Reason::PipesAsConcat);
+    }
+
+    #[test]
+    fn test_date_functions() {
+        assert_eq!(
+            translated("SELECT CURDATE(), DATE_SUB(NOW(), INTERVAL 7 DAY), d + INTERVAL n HOUR"),
+            "SELECT CURRENT_DATE, (NOW() - INTERVAL '7' DAY), d + (n * INTERVAL '1' HOUR);"
+        );
+        assert_eq!(
+            translated("SELECT DATEDIFF(a, b), YEAR(a), UNIX_TIMESTAMP(a) FROM t"),
+            "SELECT (CAST(a AS DATE) - CAST(b AS DATE)), EXTRACT(YEAR FROM a), \
+             EXTRACT(EPOCH FROM a) FROM t;"
+        );
+        assert_eq!(
+            translated("SELECT DATE_FORMAT(d, '%Y-%m-%d %H:%i at %p') FROM t"),
+            "SELECT TO_CHAR(d, 'YYYY-MM-DD HH24:MI\" at \"AM') FROM t;"
+        );
+    }
+
+    #[test]
+    fn test_to_char_pattern() {
+        assert_eq!(to_char_pattern("%d/%m/%Y").unwrap(), "DD/MM/YYYY");
+        assert_eq!(to_char_pattern("100%%").unwrap(), "100%");
+        // `%U` (week of the year, from Sunday) has no pattern.
+        assert_eq!(to_char_pattern("%Y-%U"), None);
+        assert_eq!(to_char_pattern("%"), None);
+    }
+
+    #[test]
+    fn test_create_table() {
+        let sql = "CREATE TABLE `users` (\n\
+                   id INT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,\n\
+                   name VARCHAR(50) CHARACTER SET utf8mb4 NOT NULL,\n\
+                   active TINYINT(1) NOT NULL DEFAULT 1,\n\
+                   created_at DATETIME,\n\
+                   body LONGTEXT,\n\
+                   UNIQUE KEY uq_name (name),\n\
+                   KEY idx_created (created_at)\n\
+                   ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4";
+        let transpiled = transpile(sql);
+        assert_eq!(transpiled.untranslated, []);
+        assert_eq!(
+            transpiled.sql,
+            "CREATE TABLE users (
+    id BIGINT NOT NULL GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    name VARCHAR(50) NOT NULL,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP,
+    body TEXT,
+    CONSTRAINT uq_name UNIQUE (name)
+);
+CREATE INDEX idx_created ON users (created_at);
+"
+        );
+    }
+
+    #[test]
+    fn test_no_equivalent_is_reported_and_kept() {
+        let transpiled = transpile(
+            "SELECT 1;\nSELECT GROUP_CONCAT(name) FROM t;\nSELECT DATE_FORMAT(d, '%U') FROM t;",
+        );
+        assert!(
+            transpiled.sql.contains("GROUP_CONCAT(name)"),
+            "{}",
+            transpiled.sql
+        );
+        let [group_concat, date_format] = transpiled.untranslated.as_slice() else {
+            panic!("{:?}", transpiled.untranslated);
+        };
+        assert_eq!((group_concat.start.line, group_concat.start.column), (2, 8));
+        assert_eq!(
+            group_concat.reason,
+            Reason::NoEquivalent("GROUP_CONCAT".to_string())
+        );
+        assert_eq!(date_format.start.line, 3);
+
+        assert_eq!(
+            constructs("INSERT INTO t (a) VALUES (1) ON DUPLICATE KEY UPDATE a = 2"),
+            ["ON DUPLICATE KEY UPDATE"]
+        );
+        assert_eq!(
+            constructs("CREATE TABLE t (s ENUM('a', 'b')) AUTO_INCREMENT=10"),
+            ["ENUM", "AUTO_INCREMENT"]
+        );
+        assert_eq!(constructs("SELECT a FROM t USE INDEX (i)"), ["USE INDEX"]);
+        assert_eq!(
+            constructs("SELECT SQL_CALC_FOUND_ROWS @n := @n + 1 FROM t"),
+            ["SQL_CALC_FOUND_ROWS", ":="]
+        );
+    }
+
+    #[test]
+    fn test_unsupported_translation() {
+        let err = transpile_sql(
+            "SELECT 1",
+            "postgres",
+            "mysql",
+            &FormatConfig::default(),
+            &LintConfig::default(),
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Unsupported translation: postgres to mysql (supported: mysql to postgres)"));
+    }
+
+    #[test]
+    fn test_syntax_errors_fail() {
+        let err = transpile_sql(
+            "SELECT FROM",
+            "mysql",
+            "postgres",
+            &FormatConfig::default(),
+            &LintConfig::default(),
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Cannot transpile SQL with syntax errors"));
+    }
+
+    #[test]
+    fn test_relative() {
+        assert_eq!(relative("/tmp/a/b.sql"), Path::new("tmp/a/b.sql"));
+        assert_eq!(relative("../sql/./b.sql"), Path::new("sql/b.sql"));
+    }
+}
@@ -0,0 +1,435 @@
+//! `--watch` for `check` and `lint`: re-run on files as they change.
+//!
+//! Every result is kept per file, so a burst of saves only re-runs the files that
+//! changed while the screen is redrawn from all of them. Editing a `.sqlex.toml`
+//! re-runs everything, as it may change the settings of any file; if the config is
+//! invalid, the error is shown above the previous results until it is fixed.
+
+use crate::baseline::Baseline;
+use crate::cache::Cache;
+use crate::checker::{
+    check_content, get_dialect, render_lint_warnings, render_syntax_errors, Inputs,
+};
+use crate::config::{Overrides, Resolver, CONFIG_FILE_NAME};
+use crate::detect;
+use crate::i18n::Messages;
+use crate::linter::Linter;
+use anyhow::{bail, Result};
+use colored::Colorize;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+use walkdir::WalkDir;
+
+/// How long the file system has to stay quiet before a burst of events is handled.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// What to run on each changed file.
+#[derive(Clone, Copy)]
+pub enum Task<'a> {
+    Check,
+    Lint { baseline: Option<&'a Baseline> },
+}
+
+/// The result of running the task on one file.
+struct Outcome {
+    count: usize,
+    text: String,
+}
+
+/// A path given on the command line. `absolute` is canonical, as are the paths in
+/// file system events on some platforms (e.g. `/private/var` rather than `/var`).
+enum Watched {
+    File { name: String, absolute: PathBuf },
+    Dir { name: String, absolute: PathBuf },
+}
+
+impl Watched {
+    /// The name a changed file is reported under, if this input covers it.
+    fn name_for(&self, path: &Path, resolver: &Resolver) -> Result<Option<String>> {
+        match self {
+            Watched::File { name, absolute } => Ok((path == absolute).then(|| name.clone())),
+            Watched::Dir { .. } => match self.name_in_dir(path) {
+                Some(name) if resolver.is_selected(path)? => {
+                    Ok(Some(name.to_string_lossy().to_string()))
+                }
+                _ => Ok(None),
+            },
+        }
+    }
+
+    /// The name a path in this input's directory goes by, whatever kind of path it is.
+    fn name_in_dir(&self, path: &Path) -> Option<PathBuf> {
+        match self {
+            Watched::File { .. } => None,
+            Watched::Dir { name, absolute } => path
+                .strip_prefix(absolute)
+                .ok()
+                .map(|relative| Path::new(name).join(relative)),
+        }
+    }
+}
+
+/// Run `task` on the inputs, then again on every file that changes, until interrupted.
+pub fn run(
+    inputs: &Inputs,
+    overrides: &Overrides,
+    task: Task,
+    cache: Option<&Cache>,
+    messages: &Messages,
+) -> Result<()> {
+    if inputs.sql.is_some() || inputs.paths.iter().any(|p| p == "-") {
+        bail!("--watch cannot be used with stdin or --sql input");
+    }
+
+    let mut watched = Vec::new();
+    for path in &inputs.paths {
+        let p = Path::new(path);
+        if p.is_dir() {
+            watched.push(Watched::Dir {
+                name: path.clone(),
+                absolute: p.canonicalize()?,
+            });
+        } else if p.is_file() && path.ends_with(".sql") {
+            watched.push(Watched::File {
+                name: path.clone(),
+                absolute: p.canonicalize()?,
+            });
+        }
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+    for input in &watched {
+        match input {
+            Watched::Dir { absolute, .. } => {
+                watcher.watch(absolute, RecursiveMode::Recursive)?;
+            }
+            // Watch the directory rather than the file itself: editors often save by
+            // replacing the file, which would end a watch on the old one.
+            Watched::File { absolute, .. } => {
+                if let Some(dir) = absolute.parent() {
+                    watcher.watch(dir, RecursiveMode::NonRecursive)?;
+                }
+            }
+        }
+    }
+
+    let mut resolver = Resolver::new(overrides.clone());
+    let mut results = run_all(&watched, &resolver, task, cache, messages)?;
+    redraw(&results, None, task, messages);
+
+    while let Ok(event) = receiver.recv() {
+        let mut changed = BTreeSet::new();
+        collect_changes(event, &mut changed);
+        // Debounce: keep collecting until the file system has been quiet for a moment.
+        while let Ok(event) = receiver.recv_timeout(DEBOUNCE) {
+            collect_changes(event, &mut changed);
+        }
+        if changed.is_empty() {
+            continue;
+        }
+
+        let config_changed = changed
+            .iter()
+            .any(|path| path.file_name().is_some_and(|n| n == CONFIG_FILE_NAME));
+        // A broken config is shown above the previous results until it is fixed.
+        let rerun = if config_changed {
+            resolver = Resolver::new(overrides.clone());
+            run_all(&watched, &resolver, task, cache, messages).map(|all| results = all)
+        } else {
+            run_changed(
+                &changed,
+                &watched,
+                &resolver,
+                task,
+                cache,
+                messages,
+                &mut results,
+            )
+        };
+        redraw(&results, rerun.err().as_ref(), task, messages);
+    }
+
+    Ok(())
+}
+
+/// Add the paths an event changed. Access events are skipped: reading the files
+/// ourselves produces them.
+fn collect_changes(event: notify::Result<Event>, changed: &mut BTreeSet<PathBuf>) {
+    match event {
+        Ok(event) if !matches!(event.kind, EventKind::Access(_)) => changed.extend(event.paths),
+        Ok(_) => {}
+        Err(err) => eprintln!("{}", err.to_string().red()),
+    }
+}
+
+fn run_all(
+    watched: &[Watched],
+    resolver: &Resolver,
+    task: Task,
+    cache: Option<&Cache>,
+    messages: &Messages,
+) -> Result<BTreeMap<String, Outcome>> {
+    let mut names = Vec::new();
+    for input in watched {
+        match input {
+            Watched::File { name, .. } => names.push(name.clone()),
+            Watched::Dir { name, .. } => names.extend(selected_files(Path::new(name), resolver)?),
+        }
+    }
+
+    Ok(names
+        .into_iter()
+        .filter_map(|name| {
+            let outcome = run_file(&name, resolver, task, cache, messages)?;
+            Some((name, outcome))
+        })
+        .collect())
+}
+
+/// Update `results` for the paths in `changed`.
+fn run_changed(
+    changed: &BTreeSet<PathBuf>,
+    watched: &[Watched],
+    resolver: &Resolver,
+    task: Task,
+    cache: Option<&Cache>,
+    messages: &Messages,
+    results: &mut BTreeMap<String, Outcome>,
+) -> Result<()> {
+    for path in changed {
+        for input in watched {
+            if let Some(name) = input.name_for(path, resolver)? {
+                match run_file(&name, resolver, task, cache, messages) {
+                    Some(outcome) => results.insert(name, outcome),
+                    // Deleted (or no longer readable).
+                    None => results.remove(&name),
+                };
+            } else if let Some(dir) = input.name_in_dir(path) {
+                // Removing or renaming a directory reports only the directory, so
+                // drop what was under it and run on whatever is there now.
+                results.retain(|name, _| !Path::new(name).starts_with(&dir));
+                if path.is_dir() {
+                    for name in selected_files(&dir, resolver)? {
+                        if let Some(outcome) = run_file(&name, resolver, task, cache, messages) {
+                            results.insert(name, outcome);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The selected files under `dir`, named by joining their paths onto it.
+fn selected_files(dir: &Path, resolver: &Resolver) -> Result<Vec<String>> {
+    let mut names = Vec::new();
+    for entry in WalkDir::new(dir).into_iter().filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.is_file() && resolver.is_selected(path)? {
+            names.push(path.to_string_lossy().to_string());
+        }
+    }
+    Ok(names)
+}
+
+/// Run `task` on one file, or `None` if it cannot be read. Problems with the settings
+/// are shown in place of the file's report.
+fn run_file(
+    name: &str,
+    resolver: &Resolver,
+    task: Task,
+    cache: Option<&Cache>,
+    messages: &Messages,
+) -> Option<Outcome> {
+    let content = fs::read_to_string(name).ok()?;
+    let outcome =
+        analyze(name, &content, resolver, task, cache, messages).unwrap_or_else(|err| Outcome {
+            count: 1,
+            text: format!("{}\n", format!("✗ {} - {:#}", name, err).red()),
+        });
+    Some(outcome)
+}
+
+fn analyze(
+    name: &str,
+    content: &str,
+    resolver: &Resolver,
+    task: Task,
+    cache: Option<&Cache>,
+    messages: &Messages,
+) -> Result<Outcome> {
+    let settings = resolver.settings_for(Path::new(name))?;
+    let (settings, detection) = detect::resolve(&settings, Path::new(name), content, cache)?;
+    if let Some(detection) = detection {
+        eprintln!("{}", messages.dialect_detected(name, &detection).dimmed());
+    }
+    let settings = settings.into_owned();
+    let dialect = get_dialect(&settings.dialect)?;
+
+    Ok(match task {
+        Task::Check => {
+            let errors = check_content(content, dialect.as_ref(), &settings, messages);
+            Outcome {
+                count: errors.len(),
+                text: render_syntax_errors(name, content, &errors, messages),
+            }
+        }
+        Task::Lint { baseline } => {
+            let mut errors = Linter::new(settings.lint).lint(content, dialect.as_ref(), messages);
+            if let Some(baseline) = baseline {
+                errors = baseline.filter(name, content, errors);
+            }
+            Outcome {
+                count: errors.len(),
+                text: render_lint_warnings(name, &errors, messages),
+            }
+        }
+    })
+}
+
+fn redraw(
+    results: &BTreeMap<String, Outcome>,
+    problem: Option<&anyhow::Error>,
+    task: Task,
+    messages: &Messages,
+) {
+    if io::stdout().is_terminal() {
+        // Clear the screen and move the cursor home.
+        print!("\x1b[2J\x1b[H");
+    }
+    if let Some(err) = problem {
+        println!("{}", format!("✗ {:#}", err).red());
+    }
+    for outcome in results.values() {
+        print!("{}", outcome.text);
+    }
+
+    let total = results.values().map(|o| o.count).sum();
+    let summary = match task {
+        Task::Check => messages.summary(results.len(), total),
+        Task::Lint { .. } => messages.lint_summary(results.len(), total),
+    };
+    println!("{}", summary);
+    println!("{}", messages.watching().dimmed());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_name_for_matches_inputs() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().to_path_buf();
+        let resolver = Resolver::new(Overrides::default());
+
+        let input = Watched::Dir {
+            name: "sql".to_string(),
+            absolute: root.clone(),
+        };
+        assert_eq!(
+            input
+                .name_for(&root.join("sub").join("a.sql"), &resolver)
+                .unwrap(),
+            Some(
+                Path::new("sql")
+                    .join("sub")
+                    .join("a.sql")
+                    .to_string_lossy()
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            input.name_for(&root.join("a.txt"), &resolver).unwrap(),
+            None
+        );
+
+        let input = Watched::File {
+            name: "a.sql".to_string(),
+            absolute: root.join("a.sql"),
+        };
+        assert_eq!(
+            input.name_for(&root.join("a.sql"), &resolver).unwrap(),
+            Some("a.sql".to_string())
+        );
+        assert_eq!(
+            input.name_for(&root.join("b.sql"), &resolver).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_removed_directory_drops_its_results() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::write(root.join("sub").join("a.sql"), "SELECT 1;\n").unwrap();
+        fs::write(root.join("b.sql"), "SELECT 1;\n").unwrap();
+        let resolver = Resolver::new(Overrides::default());
+        let messages = Messages::new("en");
+        let watched = [Watched::Dir {
+            name: root.to_string_lossy().to_string(),
+            absolute: root.clone(),
+        }];
+
+        let mut results = run_all(&watched, &resolver, Task::Check, None, &messages).unwrap();
+        assert_eq!(results.len(), 2);
+
+        fs::rename(root.join("sub"), root.join("moved")).unwrap();
+        let changed = BTreeSet::from([root.join("sub"), root.join("moved")]);
+        run_changed(
+            &changed,
+            &watched,
+            &resolver,
+            Task::Check,
+            None,
+            &messages,
+            &mut results,
+        )
+        .unwrap();
+        let names: Vec<_> = results.keys().map(PathBuf::from).collect();
+        assert_eq!(
+            names,
+            vec![root.join("b.sql"), root.join("moved").join("a.sql")]
+        );
+
+        fs::remove_dir_all(root.join("moved")).unwrap();
+        let changed = BTreeSet::from([root.join("moved")]);
+        run_changed(
+            &changed,
+            &watched,
+            &resolver,
+            Task::Check,
+            None,
+            &messages,
+            &mut results,
+        )
+        .unwrap();
+        assert_eq!(results.len(), 1);
+    }
+
+    #[test]
+    fn test_stdin_input_is_rejected() {
+        let inputs = Inputs {
+            paths: vec!["-".to_string()],
+            ..Default::default()
+        };
+        let err = run(
+            &inputs,
+            &Overrides::default(),
+            Task::Check,
+            None,
+            &Messages::new("en"),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("--watch"));
+    }
+}
@@ -34,6 +34,26 @@ mod check_command {
         assert!(stdout.contains("OK") || stdout.contains("問題なし"));
     }
 
+    #[test]
+    fn test_reports_every_broken_statement() {
+        let dir = TempDir::new().unwrap();
+        let path = create_temp_sql(
+            &dir,
+            "broken.sql",
+            "SELECT FROM;\nSELECT 1;\nINSERT INTO VALUES (1);\nSELECT id FROM users WHERE;\n",
+        );
+
+        let output = sqlex()
+            .args(["--lang", "en", "check", &path])
+            .output()
+            .expect("Failed to execute");
+
+        assert!(!output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("3 error(s)"), "stdout: {}", stdout);
+        assert!(stdout.contains("Total: 1 file(s), 3 error(s)"));
+    }
+
     #[test]
     fn test_invalid_sql_fails() {
         let dir = TempDir::new().unwrap();
@@ -107,6 +127,39 @@ mod check_command {
         let stdout = String::from_utf8_lossy(&output.stdout);
         assert!(stdout.contains("問題なし"));
     }
+
+    #[test]
+    fn test_parallel_output_matches_sequential() {
+        let dir = TempDir::new().unwrap();
+        for i in 0..40 {
+            let sql = if i % 3 == 0 {
+                "SELECT FROM;\n"
+            } else {
+                "select * from users\n"
+            };
+            create_temp_sql(&dir, &format!("q{:02}.sql", i), sql);
+        }
+        let path = dir.path().to_string_lossy().to_string();
+
+        for command in ["check", "lint"] {
+            let run = |jobs: &str| {
+                sqlex()
+                    .args(["--lang", "en", command, "--jobs", jobs, &path])
+                    .output()
+                    .expect("Failed to execute")
+            };
+            let sequential = run("1");
+            let parallel = run("4");
+
+            assert_eq!(sequential.status.code(), parallel.status.code());
+            assert_eq!(sequential.stdout, parallel.stdout);
+            // Files are reported in path order.
+            let stdout = String::from_utf8_lossy(&parallel.stdout);
+            let first = stdout.find("q00.sql").unwrap();
+            let last = stdout.find("q39.sql").unwrap();
+            assert!(first < last);
+        }
+    }
 }
 
 mod lint_command {
@@ -155,6 +208,40 @@ mod lint_command {
         assert!(output.status.success());
     }
 
+    #[test]
+    fn test_lint_function_type_and_identifier_case() {
+        let dir = TempDir::new().unwrap();
+        let path = create_temp_sql(
+            &dir,
+            "roles.sql",
+            "SELECT COUNT(Id), CAST(total AS int) FROM orders;",
+        );
+
+        let output = sqlex()
+            .args([
+                "--lang",
+                "en",
+                "lint",
+                "--function-case",
+                "lower",
+                "--identifier-case",
+                "lower",
+                &path,
+            ])
+            .output()
+            .expect("Failed to execute");
+
+        assert!(!output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            stdout.contains("[function-case] line 1:8 - Function name 'COUNT' should be 'count'")
+        );
+        assert!(stdout.contains("[identifier-case] line 1:14 - Identifier 'Id' should be 'id'"));
+        assert!(stdout.contains("[function-case] line 1:19"));
+        assert!(stdout.contains("[type-case] line 1:33 - Data type 'int' should be 'INT'"));
+        assert!(!stdout.contains("keyword-case"));
+    }
+
     #[test]
     fn test_lint_select_star() {
         let dir = TempDir::new().unwrap();
@@ -198,6 +285,72 @@ mod lint_command {
         let stdout = String::from_utf8_lossy(&output.stdout);
         assert!(stdout.contains("trailing-semicolon"));
     }
+
+    #[test]
+    fn test_suppression_comments() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("report.sql");
+        fs::write(
+            &path,
+            "-- sqlex-disable-next-line no-select-star\nSELECT * FROM users;\n\
+             -- sqlex-disable keyword-case\nselect id from orders;\n-- sqlex-enable\nSELECT id FROM items;\n",
+        )
+        .unwrap();
+
+        let output = sqlex()
+            .args(["lint", &path.to_string_lossy()])
+            .output()
+            .expect("Failed to execute");
+
+        assert!(output.status.success());
+
+        fs::write(
+            &path,
+            "-- sqlex-disable-next-line no-select-star\nSELECT id FROM users;\n",
+        )
+        .unwrap();
+
+        let output = sqlex()
+            .args(["lint", &path.to_string_lossy()])
+            .output()
+            .expect("Failed to execute");
+
+        assert!(!output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("[unused-suppression]"));
+    }
+
+    #[test]
+    fn test_baseline_reports_only_new_findings() {
+        let dir = TempDir::new().unwrap();
+        let sql = create_temp_sql(&dir, "legacy.sql", "SELECT * FROM users;\n");
+        let baseline = dir.path().join("sqlex-baseline.json");
+        let baseline = baseline.to_string_lossy();
+
+        let output = sqlex()
+            .args(["lint", "--write-baseline", &baseline, &sql])
+            .output()
+            .expect("Failed to execute");
+        assert!(output.status.success());
+
+        // Existing findings pass even after the line moves.
+        fs::write(&sql, "-- moved\nSELECT * FROM users;\n").unwrap();
+        let output = sqlex()
+            .args(["lint", "--baseline", &baseline, &sql])
+            .output()
+            .expect("Failed to execute");
+        assert!(output.status.success());
+
+        fs::write(&sql, "SELECT * FROM users;\nSELECT * FROM orders;\n").unwrap();
+        let output = sqlex()
+            .args(["--lang", "en", "lint", "--baseline", &baseline, &sql])
+            .output()
+            .expect("Failed to execute");
+        assert!(!output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("1 warning(s)"));
+        assert!(stdout.contains("line 2:8"));
+    }
 }
 
 mod fix_command {
@@ -229,6 +382,83 @@ mod fix_command {
         assert!(stdout.contains("Would fix") || stdout.contains("修正予定"));
     }
 
+    #[test]
+    fn test_fix_check_fails_on_unfixed_files() {
+        let dir = TempDir::new().unwrap();
+        let messy = create_temp_sql(&dir, "messy.sql", "select id from users");
+        let clean = create_temp_sql(&dir, "clean.sql", "SELECT id FROM users;\n");
+
+        let output = sqlex()
+            .args(["--lang", "en", "fix", "--check", &messy, &clean])
+            .output()
+            .expect("Failed to execute");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        assert!(!output.status.success());
+        assert!(stdout.contains("Would fix"));
+        assert!(stdout.contains("messy.sql"));
+        assert!(!stdout.contains("clean.sql"));
+        assert!(stdout.contains("1 file(s) would be fixed"));
+        assert_eq!(fs::read_to_string(&messy).unwrap(), "select id from users");
+
+        let output = sqlex()
+            .args(["fix", "--check", &clean])
+            .output()
+            .expect("Failed to execute");
+        assert!(output.status.success());
+        assert!(output.stdout.is_empty());
+    }
+
+    #[test]
+    fn test_fix_check_respects_disabled_trailing_semicolon() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join(".sqlex.toml"),
+            "[lint]\ntrailing-semicolon = false\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("q.sql"), "SELECT id FROM users\n").unwrap();
+
+        let output = sqlex()
+            .current_dir(dir.path())
+            .args(["lint", "q.sql"])
+            .output()
+            .expect("Failed to execute");
+        assert!(output.status.success());
+
+        let output = sqlex()
+            .current_dir(dir.path())
+            .args(["fix", "--check", "q.sql"])
+            .output()
+            .expect("Failed to execute");
+        assert!(output.status.success());
+        assert!(output.stdout.is_empty());
+
+        let output = sqlex()
+            .current_dir(dir.path())
+            .args(["fix", "q.sql"])
+            .output()
+            .expect("Failed to execute");
+        assert!(output.status.success());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("q.sql")).unwrap(),
+            "SELECT id FROM users\n"
+        );
+    }
+
+    #[test]
+    fn test_fix_check_with_diff() {
+        let output = sqlex()
+            .args(["fix", "--check", "-f", "diff", "--sql", "select 1;"])
+            .output()
+            .expect("Failed to execute");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        assert!(!output.status.success());
+        assert!(stdout.contains("-select 1;"));
+        assert!(stdout.contains("+SELECT 1;"));
+    }
+
     #[test]
     fn test_fix_applies_changes() {
         let dir = TempDir::new().unwrap();
@@ -266,6 +496,56 @@ mod fix_command {
         assert!(actual.contains("USERS"));
     }
 
+    #[test]
+    fn test_fix_recases_keywords_by_context() {
+        let dir = TempDir::new().unwrap();
+        let path = create_temp_sql(
+            &dir,
+            "returning.sql",
+            "update orders set status = 'done' where name ilike 'a%' returning id;",
+        );
+
+        let output = sqlex()
+            .args(["fix", "-d", "postgres", &path])
+            .output()
+            .expect("Failed to execute");
+
+        assert!(output.status.success());
+        // Columns named like keywords keep their case; every keyword is recased.
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "UPDATE orders SET status = 'done' WHERE name ILIKE 'a%' RETURNING id;"
+        );
+    }
+
+    #[test]
+    fn test_fix_applies_every_case_rule() {
+        let dir = TempDir::new().unwrap();
+        let path = create_temp_sql(
+            &dir,
+            "roles.sql",
+            "select Max(Price) from Products where Name = 'X';",
+        );
+
+        let output = sqlex()
+            .args([
+                "fix",
+                "--function-case",
+                "upper",
+                "--identifier-case",
+                "consistent",
+                &path,
+            ])
+            .output()
+            .expect("Failed to execute");
+
+        assert!(output.status.success());
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "SELECT MAX(Price) FROM Products WHERE Name = 'X';"
+        );
+    }
+
     #[test]
     fn test_fix_dry_run_diff_format() {
         let dir = TempDir::new().unwrap();
@@ -306,6 +586,1441 @@ mod fix_command {
     }
 }
 
+mod json_output {
+    use super::*;
+    use serde_json::Value;
+    use tempfile::TempDir;
+
+    fn create_temp_sql(dir: &TempDir, name: &str, content: &str) -> String {
+        let path = dir.path().join(name);
+        fs::write(&path, content).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn test_check_json_reports_syntax_errors() {
+        let dir = TempDir::new().unwrap();
+        let path = create_temp_sql(&dir, "invalid.sql", "SELECT id FROM users WHERE;");
+
+        let output = sqlex()
+            .args(["check", "--format", "json", &path])
+            .output()
+            .expect("Failed to execute");
+
+        assert!(!output.status.success());
+        let doc: Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(doc["version"], 1);
+        assert_eq!(doc["command"], "check");
+        let d = &doc["files"][0]["diagnostics"][0];
+        assert_eq!(d["rule"], "syntax-error");
+        assert_eq!(d["severity"], "error");
+        assert_eq!(doc["summary"]["errors"], 1);
+    }
+
+    #[test]
+    fn test_lint_ndjson_streams_one_object_per_line() {
+        let dir = TempDir::new().unwrap();
+        let path = create_temp_sql(&dir, "lower.sql", "select id from users;");
+
+        let output = sqlex()
+            .args(["lint", "-f", "ndjson", &path])
+            .output()
+            .expect("Failed to execute");
+
+        assert!(!output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let records: Vec<Value> = stdout
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(records.len(), 2);
+        assert!(records.iter().all(|r| r["rule"] == "keyword-case"));
+        assert_eq!(records[1]["column"], 11);
+        assert_eq!(records[1]["end_column"], 15);
+    }
+
+    #[test]
+    fn test_lint_sarif_log() {
+        let dir = TempDir::new().unwrap();
+        let path = create_temp_sql(&dir, "q.sql", "select id from users");
+
+        let output = sqlex()
+            .args(["--lang", "en", "lint", "--format", "sarif", &path])
+            .output()
+            .expect("Failed to execute");
+
+        assert!(!output.status.success());
+        let log: Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(log["version"], "2.1.0");
+        let run = &log["runs"][0];
+        let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+        assert!(rules.iter().any(|r| r["id"] == "trailing-semicolon"));
+
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), 3);
+        // Both keyword-case and trailing-semicolon findings carry fixes.
+        assert!(results.iter().all(|r| r["fixes"].is_array()));
+        let semicolon = results
+            .iter()
+            .find(|r| r["ruleId"] == "trailing-semicolon")
+            .unwrap();
+        let replacement = &semicolon["fixes"][0]["artifactChanges"][0]["replacements"][0];
+        assert_eq!(replacement["insertedContent"]["text"], ";\n");
+    }
+
+    #[test]
+    fn test_check_sarif_log() {
+        let dir = TempDir::new().unwrap();
+        let path = create_temp_sql(&dir, "invalid.sql", "SELECT id FROM users WHERE;");
+
+        let output = sqlex()
+            .args(["check", "--format", "sarif", &path])
+            .output()
+            .expect("Failed to execute");
+
+        assert!(!output.status.success());
+        let log: Value = serde_json::from_slice(&output.stdout).unwrap();
+        let result = &log["runs"][0]["results"][0];
+        assert_eq!(result["ruleId"], "syntax-error");
+        assert_eq!(result["level"], "error");
+        assert!(result["fixes"].is_null());
+    }
+
+    #[test]
+    fn test_github_annotations() {
+        let dir = TempDir::new().unwrap();
+        let bad = create_temp_sql(&dir, "bad.sql", "SELECT id FROM users WHERE;");
+        let star = create_temp_sql(&dir, "star.sql", "SELECT * FROM users;");
+
+        let output = sqlex()
+            .args(["check", "--format", "github", &bad])
+            .output()
+            .expect("Failed to execute");
+        assert!(!output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.starts_with("::error file="));
+        assert!(stdout.contains("title=sqlex (syntax-error)::"));
+
+        let output = sqlex()
+            .args(["lint", "--format", "github", &star])
+            .output()
+            .expect("Failed to execute");
+        assert!(!output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.starts_with("::warning file="));
+        assert!(stdout.contains("line=1,col=8,"));
+        assert!(stdout.contains("title=sqlex (no-select-star)::"));
+    }
+
+    #[test]
+    fn test_junit_and_checkstyle_reports() {
+        let dir = TempDir::new().unwrap();
+        create_temp_sql(&dir, "ok.sql", "SELECT id FROM users;");
+        create_temp_sql(&dir, "star.sql", "SELECT * FROM users;");
+        let root = dir.path().to_string_lossy().to_string();
+
+        let output = sqlex()
+            .args(["--lang", "en", "lint", "--format", "junit", &root])
+            .output()
+            .expect("Failed to execute");
+        assert!(!output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.starts_with("<?xml"));
+        assert!(stdout.contains("tests=\"2\" failures=\"1\""));
+        assert_eq!(stdout.matches("<testcase ").count(), 2);
+        assert!(stdout.contains("<failure message=\"Avoid SELECT *"));
+
+        let output = sqlex()
+            .args(["lint", "--format", "checkstyle", &root])
+            .output()
+            .expect("Failed to execute");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("<checkstyle version="));
+        assert!(stdout.contains("<error line=\"1\" column=\"8\" severity=\"warning\""));
+        assert!(stdout.contains("source=\"sqlex.no-select-star\""));
+    }
+
+    #[test]
+    fn test_fix_dry_run_json_lists_edits() {
+        let dir = TempDir::new().unwrap();
+        let content = "select id from users";
+        let path = create_temp_sql(&dir, "messy.sql", content);
+
+        let output = sqlex()
+            .args(["fix", "--dry-run", "-f", "json", &path])
+            .output()
+            .expect("Failed to execute");
+
+        assert!(output.status.success());
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+        let doc: Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(doc["dry_run"], true);
+        let edits = doc["files"][0]["edits"].as_array().unwrap();
+        assert_eq!(edits.len(), 3);
+        assert_eq!(edits[2]["rule"], "trailing-semicolon");
+    }
+}
+
+mod config_file {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_config_sets_dialect() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join(".sqlex.toml"), "dialect = \"mysql\"\n").unwrap();
+        fs::write(dir.path().join("q.sql"), "SELECT `id` FROM `users`;").unwrap();
+
+        let output = sqlex()
+            .current_dir(dir.path())
+            .args(["check", "."])
+            .output()
+            .expect("Failed to execute");
+
+        assert!(output.status.success());
+    }
+
+    #[test]
+    fn test_cli_flag_overrides_config() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join(".sqlex.toml"),
+            "[lint]\nkeyword-case = \"lower\"\nno-select-star = false\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("q.sql"), "SELECT * FROM users;").unwrap();
+
+        // Config alone: lowercase keywords expected, SELECT * allowed.
+        let output = sqlex()
+            .current_dir(dir.path())
+            .args(["--lang", "en", "lint", "q.sql"])
+            .output()
+            .expect("Failed to execute");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("keyword-case"));
+        assert!(!stdout.contains("no-select-star"));
+
+        // Flags take precedence over the config file.
+        let output = sqlex()
+            .current_dir(dir.path())
+            .args(["lint", "--keyword-case", "upper", "q.sql"])
+            .output()
+            .expect("Failed to execute");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(!stdout.contains("keyword-case"));
+    }
+
+    #[test]
+    fn test_config_extra_and_excluded_keywords() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join(".sqlex.toml"),
+            "[lint]\nextra-keywords = [\"foo\"]\nexclude-keywords = [\"limit\"]\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("q.sql"), "SELECT foo FROM t limit 1;").unwrap();
+
+        let output = sqlex()
+            .current_dir(dir.path())
+            .args(["--lang", "en", "lint", "q.sql"])
+            .output()
+            .expect("Failed to execute");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("'foo'"));
+        assert!(!stdout.contains("'limit'"));
+    }
+
+    #[test]
+    fn test_config_exclude_skips_files() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join(".sqlex.toml"),
+            "exclude = [\"vendor/**\"]\n",
+        )
+        .unwrap();
+        fs::create_dir(dir.path().join("vendor")).unwrap();
+        fs::write(dir.path().join("vendor").join("bad.sql"), "SELECT FROM;").unwrap();
+        fs::write(dir.path().join("ok.sql"), "SELECT 1;").unwrap();
+
+        let output = sqlex()
+            .current_dir(dir.path())
+            .args(["check", "."])
+            .output()
+            .expect("Failed to execute");
+
+        assert!(output.status.success());
+    }
+
+    #[test]
+    fn test_per_directory_dialects_in_one_run() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join(".sqlex.toml"),
+            "[[overrides]]\nfiles = [\"app/migrations/**\"]\ndialect = \"postgres\"\n",
+        )
+        .unwrap();
+        let warehouse = dir.path().join("warehouse");
+        let migrations = dir.path().join("app").join("migrations");
+        fs::create_dir_all(&warehouse).unwrap();
+        fs::create_dir_all(&migrations).unwrap();
+        fs::write(warehouse.join(".sqlex.toml"), "dialect = \"bigquery\"\n").unwrap();
+        // Backtick identifiers need BigQuery; `::` casts need PostgreSQL.
+        fs::write(warehouse.join("q.sql"), "SELECT `col` FROM `ds.t`;").unwrap();
+        fs::write(migrations.join("001.sql"), "SELECT '1'::int;").unwrap();
+
+        let output = sqlex()
+            .current_dir(dir.path())
+            .args(["check", "."])
+            .output()
+            .expect("Failed to execute");
+
+        assert!(output.status.success());
+    }
+
+    #[test]
+    fn test_invalid_config_is_reported() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join(".sqlex.toml"), "dialect = [").unwrap();
+        fs::write(dir.path().join("q.sql"), "SELECT 1;").unwrap();
+
+        let output = sqlex()
+            .current_dir(dir.path())
+            .args(["check", "q.sql"])
+            .output()
+            .expect("Failed to execute");
+
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Invalid config"));
+    }
+}
+
+mod stdin_and_inline {
+    use super::*;
+    use std::io::Write;
+    use std::process::{Output, Stdio};
+    use tempfile::TempDir;
+
+    fn run_with_stdin(args: &[&str], stdin: &str) -> Output {
+        let mut child = sqlex()
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("Failed to execute");
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(stdin.as_bytes())
+            .unwrap();
+        child.wait_with_output().unwrap()
+    }
+
+    #[test]
+    fn test_check_reads_stdin() {
+        let output = run_with_stdin(&["--lang", "en", "check", "-"], "SELECT FROM;");
+
+        assert!(!output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("<stdin> - 1 error(s)"));
+    }
+
+    #[test]
+    fn test_stdin_filename_used_for_report_and_config() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join(".sqlex.toml"),
+            "[lint]\nkeyword-case = \"lower\"\n",
+        )
+        .unwrap();
+        let name = dir.path().join("query.sql");
+        let name = name.to_string_lossy();
+
+        let output = run_with_stdin(
+            &["lint", "--stdin-filename", &name, "--format", "json", "-"],
+            "select id from users;",
+        );
+
+        assert!(output.status.success());
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(json["files"][0]["path"], name.as_ref());
+        assert_eq!(json["summary"]["warnings"], 0);
+    }
+
+    #[test]
+    fn test_lint_inline_sql() {
+        let output = sqlex()
+            .args(["--lang", "en", "lint", "--sql", "select id from users;"])
+            .output()
+            .expect("Failed to execute");
+
+        assert!(!output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("<sql> - 2 warning(s)"));
+    }
+
+    #[test]
+    fn test_fix_stdin_writes_fixed_sql_to_stdout() {
+        let output = run_with_stdin(&["fix", "-"], "select id from users");
+
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "SELECT id FROM users;\n"
+        );
+    }
+
+    #[test]
+    fn test_fix_inline_sql_echoes_unchanged_input() {
+        let output = sqlex()
+            .args(["fix", "--sql", "SELECT 1;"])
+            .output()
+            .expect("Failed to execute");
+
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "SELECT 1;");
+    }
+
+    #[test]
+    fn test_fix_rejects_inline_sql_with_files() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("a.sql");
+        fs::write(&path, "select 1").unwrap();
+
+        let output = sqlex()
+            .args(["fix", "--sql", "select 2", &path.to_string_lossy()])
+            .output()
+            .expect("Failed to execute");
+
+        assert!(!output.status.success());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "select 1");
+    }
+}
+
+mod lsp_server {
+    use super::*;
+    use serde_json::{json, Value};
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::process::{ChildStdout, Stdio};
+    use tempfile::TempDir;
+
+    fn send(stdin: &mut impl Write, message: Value) {
+        let body = message.to_string();
+        write!(stdin, "Content-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+        stdin.flush().unwrap();
+    }
+
+    fn receive(stdout: &mut BufReader<ChildStdout>) -> Value {
+        let mut length = 0;
+        loop {
+            let mut header = String::new();
+            stdout.read_line(&mut header).unwrap();
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(value) = header.strip_prefix("Content-Length: ") {
+                length = value.parse().unwrap();
+            }
+        }
+        let mut body = vec![0; length];
+        stdout.read_exact(&mut body).unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    fn file_uri(path: &std::path::Path) -> String {
+        let path = path.to_string_lossy().replace('\\', "/");
+        if path.starts_with('/') {
+            format!("file://{}", path)
+        } else {
+            format!("file:///{}", path)
+        }
+    }
+
+    #[test]
+    fn test_diagnostics_and_formatting_over_stdio() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join(".sqlex.toml"),
+            "[lint]\nkeyword-case = \"lower\"\n",
+        )
+        .unwrap();
+        let uri = file_uri(&dir.path().join("query.sql"));
+
+        let mut child = sqlex()
+            .arg("lsp")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Failed to execute");
+        let mut stdin = child.stdin.take().unwrap();
+        let mut stdout = BufReader::new(child.stdout.take().unwrap());
+
+        send(
+            &mut stdin,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {"capabilities": {}}}),
+        );
+        let response = receive(&mut stdout);
+        assert_eq!(
+            response["result"]["capabilities"]["documentFormattingProvider"],
+            true
+        );
+        send(
+            &mut stdin,
+            json!({"jsonrpc": "2.0", "method": "initialized", "params": {}}),
+        );
+
+        send(
+            &mut stdin,
+            json!({"jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {
+                "textDocument": {"uri": uri, "languageId": "sql", "version": 1, "text": "SELECT * FROM users"}
+            }}),
+        );
+        let notification = receive(&mut stdout);
+        assert_eq!(notification["method"], "textDocument/publishDiagnostics");
+        let codes: Vec<&str> = notification["params"]["diagnostics"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|d| d["code"].as_str().unwrap())
+            .collect();
+        // keyword-case = "lower" comes from the config next to the document.
+        assert_eq!(
+            codes,
+            vec![
+                "keyword-case",
+                "keyword-case",
+                "no-select-star",
+                "trailing-semicolon"
+            ]
+        );
+
+        send(
+            &mut stdin,
+            json!({"jsonrpc": "2.0", "id": 2, "method": "textDocument/formatting", "params": {
+                "textDocument": {"uri": uri},
+                "options": {"tabSize": 4, "insertSpaces": true}
+            }}),
+        );
+        let response = receive(&mut stdout);
+        assert_eq!(response["id"], 2);
+        assert_eq!(response["result"][0]["newText"], "select *\nfrom users;\n");
+
+        send(
+            &mut stdin,
+            json!({"jsonrpc": "2.0", "id": 3, "method": "shutdown"}),
+        );
+        assert_eq!(receive(&mut stdout)["id"], 3);
+        send(&mut stdin, json!({"jsonrpc": "2.0", "method": "exit"}));
+
+        assert!(child.wait().unwrap().success());
+    }
+}
+
+mod watch_mode {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::process::Stdio;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_watch_rechecks_changed_files() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("query.sql");
+        fs::write(&file, "SELECT 1;\n").unwrap();
+
+        let mut child = sqlex()
+            .args(["--lang", "en", "check", "--watch"])
+            .arg(dir.path())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Failed to execute");
+
+        // Forward summary lines so the test can wait for each redraw.
+        let stdout = child.stdout.take().unwrap();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if line.starts_with("Total:") && sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        let timeout = Duration::from_secs(10);
+
+        let first = receiver.recv_timeout(timeout);
+        fs::write(&file, "SELECT FROM;\n").unwrap();
+        let second = receiver.recv_timeout(timeout);
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        assert_eq!(first.unwrap(), "Total: 1 file(s), 0 error(s)");
+        assert_eq!(second.unwrap(), "Total: 1 file(s), 1 error(s)");
+    }
+
+    #[test]
+    fn test_watch_survives_an_invalid_config() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("query.sql");
+        let config = dir.path().join(".sqlex.toml");
+        fs::write(&file, "SELECT 1;\n").unwrap();
+
+        let mut child = sqlex()
+            .args(["--lang", "en", "check", "--watch"])
+            .arg(dir.path())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Failed to execute");
+
+        // Forward config errors and summary lines so the test can follow each redraw.
+        let stdout = child.stdout.take().unwrap();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                let wanted = line.starts_with("Total:") || line.contains("Invalid config");
+                if wanted && sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        let timeout = Duration::from_secs(10);
+
+        let first = receiver.recv_timeout(timeout);
+        fs::write(&config, "dialect = [\n").unwrap();
+        let problem = receiver.recv_timeout(timeout);
+        let kept = receiver.recv_timeout(timeout);
+        fs::write(&file, "SELECT FROM;\n").unwrap();
+        fs::remove_file(&config).unwrap();
+        // The file change alone still runs into the broken config, so wait for the
+        // redraw that follows removing it.
+        let fixed = (0..4)
+            .map_while(|_| receiver.recv_timeout(timeout).ok())
+            .find(|line| line.ends_with("1 error(s)"));
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        assert_eq!(first.unwrap(), "Total: 1 file(s), 0 error(s)");
+        assert!(problem.unwrap().contains("Invalid config"));
+        assert_eq!(kept.unwrap(), "Total: 1 file(s), 0 error(s)");
+        assert_eq!(fixed.unwrap(), "Total: 1 file(s), 1 error(s)");
+    }
+
+    #[test]
+    fn test_watch_rejects_machine_readable_output() {
+        let dir = TempDir::new().unwrap();
+
+        let output = sqlex()
+            .args(["check", "--watch", "--format", "json"])
+            .arg(dir.path())
+            .output()
+            .expect("Failed to execute");
+
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("--watch"));
+    }
+}
+
+mod result_cache {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn cache_entries(dir: &std::path::Path) -> Vec<std::path::PathBuf> {
+        let versioned = dir.join(".sqlex_cache").join(env!("CARGO_PKG_VERSION"));
+        fs::read_dir(versioned)
+            .map(|entries| entries.map(|e| e.unwrap().path()).collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_repeat_runs_reuse_cached_results() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("q.sql"), "SELECT * FROM users WHERE;").unwrap();
+
+        let output = sqlex()
+            .current_dir(dir.path())
+            .args(["check", "q.sql"])
+            .output()
+            .expect("Failed to execute");
+        assert!(!output.status.success());
+        let entries = cache_entries(dir.path());
+        assert_eq!(entries.len(), 1);
+        assert!(dir.path().join(".sqlex_cache/.gitignore").exists());
+
+        // Tamper with the entry: a cached run reports what the cache says.
+        fs::write(&entries[0], "[]").unwrap();
+        let output = sqlex()
+            .current_dir(dir.path())
+            .args(["check", "q.sql"])
+            .output()
+            .expect("Failed to execute");
+        assert!(output.status.success());
+
+        let output = sqlex()
+            .current_dir(dir.path())
+            .args(["check", "--no-cache", "q.sql"])
+            .output()
+            .expect("Failed to execute");
+        assert!(!output.status.success());
+    }
+
+    #[test]
+    fn test_changed_settings_miss_the_cache() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("q.sql"), "select id from users;").unwrap();
+
+        for case in ["upper", "lower"] {
+            sqlex()
+                .current_dir(dir.path())
+                .args(["lint", "--keyword-case", case, "q.sql"])
+                .output()
+                .expect("Failed to execute");
+        }
+        assert_eq!(cache_entries(dir.path()).len(), 2);
+    }
+
+    #[test]
+    fn test_cache_clean_removes_cache() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("q.sql"), "SELECT 1;").unwrap();
+
+        sqlex()
+            .current_dir(dir.path())
+            .args(["lint", "q.sql"])
+            .output()
+            .expect("Failed to execute");
+        assert!(dir.path().join(".sqlex_cache").exists());
+
+        let output = sqlex()
+            .current_dir(dir.path())
+            .args(["--lang", "en", "cache", "clean"])
+            .output()
+            .expect("Failed to execute");
+        assert!(output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout).contains("Removed cache"));
+        assert!(!dir.path().join(".sqlex_cache").exists());
+    }
+
+    #[test]
+    fn test_no_cache_writes_nothing() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("q.sql"), "SELECT 1;").unwrap();
+
+        sqlex()
+            .current_dir(dir.path())
+            .args(["lint", "--no-cache", "q.sql"])
+            .output()
+            .expect("Failed to execute");
+        assert!(!dir.path().join(".sqlex_cache").exists());
+    }
+}
+
+mod diff_base {
+    use super::*;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .current_dir(dir)
+            .args([
+                "-c",
+                "user.name=sqlex",
+                "-c",
+                "user.email=sqlex@example.com",
+            ])
+            .args(args)
+            .output()
+            .expect("Failed to execute git")
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    /// A repository with one commit holding `legacy.sql` and `untouched.sql`.
+    fn repo(legacy: &str, untouched: &str) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("legacy.sql"), legacy).unwrap();
+        fs::write(dir.path().join("untouched.sql"), untouched).unwrap();
+        git(dir.path(), &["init", "-q"]);
+        git(dir.path(), &["add", "."]);
+        git(dir.path(), &["commit", "-q", "-m", "initial"]);
+        dir
+    }
+
+    #[test]
+    fn test_lint_reports_only_changed_lines() {
+        let dir = repo("select 1;\nselect 2;\n", "select 3;\n");
+        fs::write(
+            dir.path().join("legacy.sql"),
+            "select 1;\nselect 2;\nselect 4;\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("new.sql"), "select 5;\n").unwrap();
+
+        let output = sqlex()
+            .current_dir(dir.path())
+            .args([
+                "--lang",
+                "en",
+                "lint",
+                "--no-cache",
+                "--diff-base",
+                "HEAD",
+                ".",
+            ])
+            .output()
+            .expect("Failed to execute");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        assert!(!output.status.success());
+        // The new line of legacy.sql and all of the untracked new.sql.
+        assert!(stdout.contains("legacy.sql"));
+        assert!(stdout.contains("line 3:"));
+        assert!(stdout.contains("new.sql"));
+        assert_eq!(stdout.matches("[keyword-case]").count(), 2);
+        assert!(!stdout.contains("untouched.sql"));
+    }
+
+    #[test]
+    fn test_check_reports_syntax_errors_anywhere_in_changed_files() {
+        let dir = repo(
+            "SELECT * FROM users WHERE;\n",
+            "SELECT * FROM users WHERE;\n",
+        );
+        fs::write(
+            dir.path().join("legacy.sql"),
+            "SELECT * FROM users WHERE;\nSELECT 1;\n",
+        )
+        .unwrap();
+
+        let output = sqlex()
+            .current_dir(dir.path())
+            .args(["check", "--no-cache", "--diff-base", "HEAD", "."])
+            .output()
+            .expect("Failed to execute");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        assert!(!output.status.success());
+        assert!(stdout.contains("legacy.sql"));
+        assert!(!stdout.contains("untouched.sql"));
+    }
+
+    #[test]
+    fn test_unknown_revision_is_an_error() {
+        let dir = repo("SELECT 1;\n", "SELECT 1;\n");
+
+        let output = sqlex()
+            .current_dir(dir.path())
+            .args(["check", "--diff-base", "no-such-branch", "."])
+            .output()
+            .expect("Failed to execute");
+
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("Invalid diff base"));
+    }
+}
+
+mod format_command {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_format_rewrites_files() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("q.sql");
+        fs::write(&file, "select id,name from users where id=1").unwrap();
+
+        let output = sqlex()
+            .args(["--lang", "en", "format"])
+            .arg(&file)
+            .output()
+            .expect("Failed to execute");
+
+        assert!(output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout).contains("Formatted"));
+        assert_eq!(
+            fs::read_to_string(&file).unwrap(),
+            "SELECT id, name\nFROM users\nWHERE id=1;\n"
+        );
+    }
+
+    #[test]
+    fn test_format_dry_run_prints_diff() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("q.sql");
+        fs::write(&file, "select 1").unwrap();
+
+        let output = sqlex()
+            .args(["format", "--dry-run"])
+            .arg(&file)
+            .output()
+            .expect("Failed to execute");
+
+        assert!(output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout).contains("+SELECT 1;"));
+        assert_eq!(fs::read_to_string(&file).unwrap(), "select 1");
+    }
+
+    #[test]
+    fn test_format_uses_config_layout() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join(".sqlex.toml"),
+            "[format]\nmax-line-length = 10\ncomma-style = \"leading\"\nindent-width = 2\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("q.sql"), "SELECT id, name FROM users;").unwrap();
+
+        let output = sqlex()
+            .current_dir(dir.path())
+            .args(["format", "q.sql"])
+            .output()
+            .expect("Failed to execute");
+
+        assert!(output.status.success());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("q.sql")).unwrap(),
+            "SELECT\n  id\n  , name\nFROM users;\n"
+        );
+    }
+
+    #[test]
+    fn test_format_stdin_writes_stdout() {
+        let output = sqlex()
+            .args(["format", "--join-on", "new-line", "--sql"])
+            .arg("select a.id from a join b on a.id = b.id")
+            .output()
+            .expect("Failed to execute");
+
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "SELECT a.id\nFROM a\nJOIN b\n    ON a.id = b.id;\n"
+        );
+    }
+
+    #[test]
+    fn test_format_leaves_broken_files_alone() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("broken.sql");
+        fs::write(&file, "select * from users where").unwrap();
+
+        let output = sqlex()
+            .arg("format")
+            .arg(&file)
+            .output()
+            .expect("Failed to execute");
+
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("broken.sql"));
+        assert_eq!(
+            fs::read_to_string(&file).unwrap(),
+            "select * from users where"
+        );
+    }
+}
+
+mod dialects {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Whether `sql` passes `sqlex check` in `dialect`.
+    fn accepts(dialect: &str, sql: &str) -> bool {
+        sqlex()
+            .args(["check", "--no-cache", "--dialect", dialect, "--sql", sql])
+            .output()
+            .expect("Failed to execute")
+            .status
+            .success()
+    }
+
+    #[test]
+    fn test_mssql_and_aliases() {
+        let sql = "DECLARE @x INT = 1;";
+        for dialect in ["mssql", "tsql", "sqlserver"] {
+            assert!(accepts(dialect, sql), "{dialect} should accept {sql}");
+        }
+        assert!(!accepts("generic", sql));
+    }
+
+    #[test]
+    fn test_snowflake() {
+        for sql in ["CREATE STAGE s;", "COPY INTO t FROM @s;"] {
+            assert!(accepts("snowflake", sql), "snowflake should accept {sql}");
+            assert!(!accepts("generic", sql));
+        }
+    }
+
+    #[test]
+    fn test_redshift() {
+        let sql = "SELECT #col FROM t;";
+        assert!(accepts("redshift", sql));
+        assert!(!accepts("mysql", sql));
+    }
+
+    #[test]
+    fn test_clickhouse() {
+        for sql in [
+            "SELECT id FROM t FINAL PREWHERE x = 1 SETTINGS max_threads = 2;",
+            "SELECT id FROM t LIMIT 1 BY id;",
+        ] {
+            assert!(accepts("clickhouse", sql), "clickhouse should accept {sql}");
+            assert!(!accepts("postgres", sql));
+        }
+    }
+
+    #[test]
+    fn test_duckdb() {
+        for sql in ["SELECT * EXCLUDE (a) FROM t;", "INSTALL httpfs;"] {
+            assert!(accepts("duckdb", sql), "duckdb should accept {sql}");
+            assert!(!accepts("postgres", sql));
+        }
+    }
+
+    #[test]
+    fn test_hive() {
+        assert!(accepts(
+            "hive",
+            "CREATE TABLE t (id INT) PARTITIONED BY (dt STRING) STORED AS PARQUET;"
+        ));
+        assert!(accepts(
+            "hive",
+            "SELECT id FROM t DISTRIBUTE BY id SORT BY id;"
+        ));
+    }
+
+    #[test]
+    fn test_databricks() {
+        let sql = "SELECT id FROM t TIMESTAMP AS OF '2024-01-01';";
+        assert!(accepts("databricks", sql));
+        assert!(!accepts("generic", sql));
+    }
+
+    #[test]
+    fn test_ansi() {
+        assert!(accepts(
+            "ansi",
+            "SELECT id FROM t FETCH FIRST 10 ROWS ONLY;"
+        ));
+    }
+
+    #[test]
+    fn test_auto_detects_each_file() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("a.sql"), "DECLARE @x INT = 1;\n").unwrap();
+        fs::write(
+            dir.path().join("b.sql"),
+            "SELECT id FROM t FINAL PREWHERE x = 1;\n",
+        )
+        .unwrap();
+
+        let output = sqlex()
+            .args(["--lang", "en", "check", "--no-cache", "--dialect", "auto"])
+            .arg(dir.path())
+            .output()
+            .expect("Failed to execute");
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(output.status.success(), "stderr: {}", stderr);
+        assert!(
+            stderr.contains("a.sql: detected dialect mssql"),
+            "stderr: {}",
+            stderr
+        );
+        assert!(
+            stderr.contains("b.sql: detected dialect clickhouse"),
+            "stderr: {}",
+            stderr
+        );
+        assert!(stderr.contains("% confidence)"));
+    }
+
+    #[test]
+    fn test_auto_from_config_falls_back_when_nothing_parses() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join(".sqlex.toml"), "dialect = \"auto\"\n").unwrap();
+        fs::create_dir(dir.path().join("postgres")).unwrap();
+        fs::write(dir.path().join("postgres/a.sql"), "SELECT id FROM t;\n").unwrap();
+        fs::write(dir.path().join("broken.sql"), "SELECT FROM;\n").unwrap();
+
+        let output = sqlex()
+            .args(["--lang", "en", "check", "--no-cache", "."])
+            .current_dir(dir.path())
+            .output()
+            .expect("Failed to execute");
+
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("detected dialect postgres"),
+            "stderr: {}",
+            stderr
+        );
+        assert!(
+            stderr.contains("broken.sql: no dialect parses this file cleanly, using generic"),
+            "stderr: {}",
+            stderr
+        );
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            stdout.contains("Total: 2 file(s), 1 error(s)"),
+            "stdout: {}",
+            stdout
+        );
+    }
+
+    #[test]
+    fn test_lint_knows_dialect_keywords() {
+        let output = sqlex()
+            .args([
+                "--lang",
+                "en",
+                "lint",
+                "--dialect",
+                "mssql",
+                "--sql",
+                "SELECT id FROM t WITH (nolock);",
+            ])
+            .output()
+            .expect("Failed to execute");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("'nolock'"), "stdout: {}", stdout);
+
+        let output = sqlex()
+            .args([
+                "fix",
+                "--dialect",
+                "clickhouse",
+                "--sql",
+                "select id from t final prewhere x = 1",
+            ])
+            .output()
+            .expect("Failed to execute");
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "SELECT id FROM t FINAL PREWHERE x = 1;\n"
+        );
+    }
+}
+
+mod portability {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_queries(dir: &TempDir) -> String {
+        let path = dir.path().join("queries.sql");
+        fs::write(&path, "SELECT 1;\nSELECT id FROM t LIMIT 1 BY id;\n").unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn test_reports_dialects_rejecting_each_statement() {
+        let dir = TempDir::new().unwrap();
+        let path = write_queries(&dir);
+
+        let output = sqlex()
+            .args(["--lang", "en", "check", "--no-cache"])
+            .args(["--dialects", "clickhouse,postgres", &path])
+            .output()
+            .expect("Failed to execute");
+
+        assert!(!output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            stdout.contains("1 statement(s) not portable"),
+            "stdout: {}",
+            stdout
+        );
+        assert!(stdout.contains("Statement at line 2: SELECT id FROM t LIMIT 1 BY id;"));
+        assert!(
+            stdout.contains("postgres (line 2, col 26)"),
+            "stdout: {}",
+            stdout
+        );
+        assert!(!stdout.contains("clickhouse (line"));
+        assert!(stdout.contains("Statements rejected per dialect:"));
+        assert!(stdout.contains("Total: 1 file(s), 2 statement(s), 1 not portable"));
+    }
+
+    #[test]
+    fn test_portable_files_pass() {
+        let output = sqlex()
+            .args([
+                "--lang",
+                "en",
+                "check",
+                "--dialects",
+                "postgres,sqlite,mysql",
+            ])
+            .args(["--sql", "SELECT id FROM users WHERE id = 1;"])
+            .output()
+            .expect("Failed to execute");
+
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("✓ <sql> - OK"), "stdout: {}", stdout);
+        assert!(stdout.contains("0 not portable"));
+    }
+
+    #[test]
+    fn test_json_names_the_rejecting_dialect() {
+        let dir = TempDir::new().unwrap();
+        let path = write_queries(&dir);
+
+        let output = sqlex()
+            .args(["--lang", "en", "check", "--no-cache", "-f", "json"])
+            .args(["--dialects", "clickhouse,postgres", &path])
+            .output()
+            .expect("Failed to execute");
+
+        assert!(!output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let report: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        let text = report.to_string();
+        assert!(text.contains("Rejected by postgres"), "stdout: {}", stdout);
+        assert!(!text.contains("Rejected by clickhouse"));
+    }
+
+    #[test]
+    fn test_unknown_dialect_errors() {
+        let output = sqlex()
+            .args([
+                "check",
+                "--dialects",
+                "postgres,oracle",
+                "--sql",
+                "SELECT 1;",
+            ])
+            .output()
+            .expect("Failed to execute");
+
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("Unsupported dialect: oracle"),
+            "stderr: {}",
+            stderr
+        );
+    }
+}
+
+mod target_version {
+    use super::*;
+    use tempfile::TempDir;
+
+    const CTE: &str = "WITH a AS (SELECT 1 AS x)\nSELECT ROW_NUMBER() OVER (ORDER BY x) FROM a;\n";
+
+    #[test]
+    fn test_target_flags_constructs_newer_than_the_version() {
+        let output = sqlex()
+            .args([
+                "--lang",
+                "en",
+                "check",
+                "--no-cache",
+                "--target",
+                "mysql@5.7",
+            ])
+            .args(["--sql", CTE])
+            .output()
+            .expect("Failed to execute");
+
+        assert!(!output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            stdout.contains(
+                "Version error (line 1, col 1): WITH requires MySQL 8.0 or later (target: 5.7)"
+            ),
+            "stdout: {}",
+            stdout
+        );
+        assert!(stdout.contains("line 2, col 8): OVER requires MySQL 8.0 or later"));
+        assert!(!stdout.contains("Syntax error"));
+    }
+
+    #[test]
+    fn test_newer_target_passes() {
+        let output = sqlex()
+            .args(["check", "--no-cache", "--target", "mysql@8.0", "--sql", CTE])
+            .output()
+            .expect("Failed to execute");
+
+        assert!(output.status.success());
+    }
+
+    #[test]
+    fn test_target_version_from_config() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join(".sqlex.toml"),
+            "dialect = \"postgres\"\ntarget-version = \"12\"\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("merge.sql"),
+            "MERGE INTO t USING s ON t.id = s.id WHEN MATCHED THEN DELETE;\n",
+        )
+        .unwrap();
+
+        let output = sqlex()
+            .args(["--lang", "en", "check", "--no-cache", "-f", "json", "."])
+            .current_dir(dir.path())
+            .output()
+            .expect("Failed to execute");
+
+        assert!(!output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            stdout.contains("MERGE requires PostgreSQL 15 or later (target: 12)"),
+            "stdout: {}",
+            stdout
+        );
+        assert!(stdout.contains("\"target-version\""), "stdout: {}", stdout);
+    }
+
+    #[test]
+    fn test_target_rejects_dialects_without_versions() {
+        for target in ["generic@1", "bigquery@2", "mssql@2019"] {
+            let output = sqlex()
+                .args(["check", "--no-cache", "--target", target, "--sql", CTE])
+                .output()
+                .expect("Failed to execute");
+
+            assert!(!output.status.success());
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            assert!(
+                stderr.contains("(supported: mysql, postgres, sqlite)"),
+                "stderr: {}",
+                stderr
+            );
+        }
+    }
+
+    #[test]
+    fn test_target_needs_a_version() {
+        let output = sqlex()
+            .args(["check", "--target", "mysql", "--sql", "SELECT 1;"])
+            .output()
+            .expect("Failed to execute");
+
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("DIALECT@VERSION"), "stderr: {}", stderr);
+    }
+}
+
+mod transpile {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_transpile_mysql_to_postgres() {
+        let output = sqlex()
+            .args(["transpile", "--from", "mysql", "--to", "postgres", "--sql"])
+            .arg("SELECT `id`, IFNULL(`name`, 'n/a') FROM `users` LIMIT 20, 10")
+            .output()
+            .expect("Failed to execute");
+
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert_eq!(
+            stdout,
+            "SELECT id, COALESCE(name, 'n/a')\nFROM users\nLIMIT 10\nOFFSET 20;\n"
+        );
+    }
+
+    #[test]
+    fn test_untranslated_constructs_are_reported() {
+        let output = sqlex()
+            .args([
+                "--lang",
+                "en",
+                "transpile",
+                "--from",
+                "mysql",
+                "--to",
+                "postgres",
+            ])
+            .args(["--sql", "SELECT 1;\nSELECT GROUP_CONCAT(name) FROM users;"])
+            .output()
+            .expect("Failed to execute");
+
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains(
+                "Not translated (line 2, col 8): GROUP_CONCAT has no PostgreSQL equivalent"
+            ),
+            "stderr: {}",
+            stderr
+        );
+        // The rest is still translated.
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            stdout.contains("SELECT GROUP_CONCAT(name)"),
+            "stdout: {}",
+            stdout
+        );
+    }
+
+    #[test]
+    fn test_out_dir_with_configured_dialect() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join(".sqlex.toml"), "dialect = \"mysql\"\n").unwrap();
+        fs::create_dir(dir.path().join("sql")).unwrap();
+        fs::write(
+            dir.path().join("sql/users.sql"),
+            "CREATE TABLE `users` (`id` INT NOT NULL AUTO_INCREMENT PRIMARY KEY) ENGINE=InnoDB;\n",
+        )
+        .unwrap();
+
+        let output = sqlex()
+            .args(["--lang", "en", "transpile", "--to", "postgres"])
+            .args(["--out-dir", "out", "sql"])
+            .current_dir(dir.path())
+            .output()
+            .expect("Failed to execute");
+
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("Transpiled: sql/users.sql -> out/sql/users.sql"));
+        let translated = fs::read_to_string(dir.path().join("out/sql/users.sql")).unwrap();
+        assert_eq!(
+            translated,
+            "CREATE TABLE users (\n    id INT NOT NULL GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY\n);\n"
+        );
+    }
+
+    #[test]
+    fn test_unsupported_translation() {
+        let output = sqlex()
+            .args([
+                "transpile",
+                "--from",
+                "postgres",
+                "--to",
+                "mysql",
+                "--sql",
+                "SELECT 1",
+            ])
+            .output()
+            .expect("Failed to execute");
+
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("Unsupported translation: postgres to mysql"),
+            "stderr: {}",
+            stderr
+        );
+    }
+}
+
 mod help_and_version {
     use super::*;
 